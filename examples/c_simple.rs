@@ -5,9 +5,12 @@ fn main() {
     init();
 
     let project = Project::from_path("./examples/samples/simple/main.bc").unwrap();
-    let vm = VM::new("main", &project).unwrap();
+    let mut vm = VM::new("main", &project).unwrap();
 
-    for path in vm {
-        println!("Path result: {:?}", path);
+    while let Some(path) = vm.run() {
+        match path.and_then(|value| value.display(&vm.solver, true)) {
+            Ok(value) => println!("Path result: {}", value),
+            Err(err) => println!("Path error: {:?}", err),
+        }
     }
 }
@@ -22,6 +22,7 @@ impl fmt::Display for ConcreteValue {
                     }
                 }
             }
+            Pointer(addr) => write!(f, "{addr:#x} (pointer)"),
             Array(elements) => {
                 let elements = elements
                     .iter()
@@ -104,6 +105,13 @@ impl fmt::Display for PathResult {
                 writeln!(f, "{n:4}: {}", value.value)?;
             }
         }
+
+        if !self.output.is_empty() {
+            writeln!(f, "\nOutput:")?;
+            for line in self.output.lines() {
+                writeln!(f, "    {line}")?;
+            }
+        }
         Ok(())
     }
 }
@@ -9,8 +9,14 @@ use x0001e::{
 
 mod display_impls;
 mod run;
+mod rust_test;
 
-pub use run::{run, run_project};
+#[cfg(feature = "serde")]
+pub use run::to_json;
+pub use run::{
+    collect_project, collect_project_with_deadline, collect_vm, run, run_all_entry_points,
+    run_project,
+};
 
 /// Result for a single path of execution.
 ///
@@ -18,7 +24,12 @@ pub use run::{run, run_project};
 /// stack trace to where the error occured.
 ///
 /// All input variables and variables used in `symbolic` calls will also have solutions available.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub struct PathResult {
     /// Which path this is.
     pub path: usize,
@@ -34,6 +45,33 @@ pub struct PathResult {
 
     /// Variables explicitly marked as symbolic.
     pub symbolics: Vec<Variable>,
+
+    /// The instruction that terminated the path.
+    ///
+    /// This is `None` if the path failed before execution started.
+    pub location: Option<InstructionLocation>,
+
+    /// Output written by `puts` and `printf` on the path.
+    pub output: String,
+}
+
+/// Location of an instruction in the analyzed code.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate")
+)]
+pub struct InstructionLocation {
+    /// Demangled name of the function.
+    pub function_name: String,
+
+    /// Label of the basic block in the function.
+    pub block_label: String,
+
+    /// Index of the instruction in the basic block, the terminator has the index after the last
+    /// instruction.
+    pub instruction_index: usize,
 }
 
 /// Status of the path.
@@ -41,6 +79,11 @@ pub struct PathResult {
 /// If the path succeeded the return value (if any) is contained in that variant. Otherwise,
 /// the reason for failure is contained in the `Failure` variant.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum PathStatus {
     /// The path finished successfully.
     Ok(Option<Variable>),
@@ -53,10 +96,18 @@ pub enum PathStatus {
 ///
 /// Contains the error message, where the error happend and the stack trace from the point of failure.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub struct ErrorReason {
     /// Error message from the received error.
     pub error_message: String,
 
+    /// Kind of the received error, the name of the [x0001e::VMError] variant.
+    pub error_kind: String,
+
     /// For which line in the execution the error was encountered.
     pub error_location: Option<String>,
 
@@ -68,6 +119,11 @@ pub struct ErrorReason {
 
 /// One line in the stack trace. Contains the name of the function and the line where it occurred.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub struct LineTrace {
     /// Name of the function.
     pub function_name: String,
@@ -78,18 +134,31 @@ pub struct LineTrace {
 
 /// A concrete solution to a symbol.
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub struct Variable {
     /// `name` is the source name of the variable, if it exists.
     pub name: Option<String>,
 
     /// The concrete value of the symbol.
     pub value: ConcreteValue,
+
+    /// If the value depends on a tainted input, see [x0001e::VM::taint_parameter].
+    pub tainted: bool,
 }
 
 /// A concrete value from a symbol.
 ///
 ///
 #[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde_crate::Serialize, serde_crate::Deserialize),
+    serde(crate = "serde_crate")
+)]
 pub enum ConcreteValue {
     /// Integer value of size.
     ///
@@ -101,6 +170,9 @@ pub enum ConcreteValue {
         bits: u32,
     },
 
+    /// Pointer, the value is the address it points to.
+    Pointer(u64),
+
     /// Array or vector of values
     Array(Vec<ConcreteValue>),
 
@@ -124,10 +196,7 @@ impl ConcreteValue {
                 bits: *bits,
             },
 
-            PointerType { .. } => Self::Value {
-                value: u64::from_str_radix(binary_str, 2).unwrap(),
-                bits: project.ptr_size,
-            },
+            PointerType { .. } => Self::Pointer(u64::from_str_radix(binary_str, 2).unwrap()),
 
             VectorType {
                 element_type,
@@ -140,10 +209,13 @@ impl ConcreteValue {
             } => {
                 let el_size = project.bit_size(element_type).unwrap() as usize;
 
+                // The binary string starts with the most significant bit, while the first element
+                // is stored in the least significant bits.
                 let mut elements = Vec::new();
                 for i in 0..*num_elements {
-                    let start = i * el_size;
-                    let end = (i + 1) * el_size;
+                    let (offset, _) = project.bit_offset_concrete(ty, i as u64).unwrap();
+                    let end = binary_str.len() - offset as usize;
+                    let start = end - el_size;
                     let s = &binary_str[start..end];
                     let element = Self::from_binary_str(s, element_type, project);
                     elements.push(element);
@@ -153,15 +225,17 @@ impl ConcreteValue {
             }
 
             StructType { element_types, .. } => {
+                // As for arrays, the first field is stored in the least significant bits, and the
+                // fields are placed by the data layout.
                 let mut fields = Vec::new();
-                let mut current_offset = 0;
-                for el_ty in element_types {
+                for (i, el_ty) in element_types.iter().enumerate() {
+                    let (offset, _) = project.bit_offset_concrete(ty, i as u64).unwrap();
                     let size = project.bit_size(el_ty).unwrap() as usize;
-                    let end = current_offset + size;
-                    let s = &binary_str[current_offset..end];
+                    let end = binary_str.len() - offset as usize;
+                    let start = end - size;
+                    let s = &binary_str[start..end];
 
                     fields.push(Self::from_binary_str(s, el_ty, project));
-                    current_offset = end;
                 }
 
                 Self::Struct(fields)
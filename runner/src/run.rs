@@ -1,10 +1,10 @@
 use anyhow::Result;
 use rustc_demangle::demangle;
-use std::path::Path;
+use std::{collections::HashMap, path::Path, time::Duration};
 
 use crate::*;
 use x0001e::{
-    common::SolutionVariable, ir::*, solver::SolutionGenerator, Project, ReturnValue, VM,
+    common::SolutionVariable, ir::*, solver::SolutionGenerator, Project, ReturnValue, VMError, VM,
 };
 
 /// Helper to generate solutions from a list of `SolutionVariable`s.
@@ -24,7 +24,11 @@ fn generate_solutions<'a>(
             None => ConcreteValue::Unknown(value.as_01x_str().to_owned()),
         };
 
-        let variable = Variable { name, value };
+        let variable = Variable {
+            name,
+            value,
+            tainted: symbol.value.is_tainted(),
+        };
         variables.push(variable);
     }
 
@@ -45,93 +49,248 @@ pub fn run_project(project: &Project, function: &str) -> Result<()> {
     // Go through all paths.
     while let Some(path_result) = vm.run() {
         path_num += 1;
+        let path_result = to_path_result(&vm, path_num, path_result)?;
+        println!("{}", path_result);
+    }
+    println!("Paths: {path_num}");
 
-        // Cache for solutions.
-        //
-        // Solutions cannot be cached between paths, so instantiate a new one for each path.
-        let mut cache = SolutionGenerator::new(vm.solver.clone())?;
-
-        let inputs = generate_solutions(vm.parameters.iter(), &mut cache, project)?;
-        let symbolics = generate_solutions(vm.state.symbols.iter(), &mut cache, project)?;
-        // let inputs = vec![];
-        // let symbolics = vec![];
-
-        let result = match path_result {
-            Ok(return_value) => {
-                let return_value = match return_value {
-                    ReturnValue::Value(return_value) => {
-                        // Try to reconstruct the type based on the last executed instruction.
-                        let terminator = &vm.state.current_loc.block.term;
-                        let ty = match terminator {
-                            Terminator::Ret(instr) => match &instr.return_operand {
-                                Some(op) => Some(vm.state.type_of(op)),
-                                None => None,
-                            },
-                            _ => None,
-                        };
+    Ok(())
+}
 
-                        if let Some(ty) = ty {
-                            // Solve the return value.
-                            let value = cache.get_solution(&return_value)?;
-                            let value = ConcreteValue::from_binary_str(
-                                value.as_01x_str(),
-                                ty.as_ref(),
-                                project,
-                            );
-                            let variable = Variable { name: None, value };
-                            Some(variable)
-                        } else {
-                            None
-                        }
-                    }
-                    ReturnValue::Void => None,
-                };
+/// Run the analysis with a given Project and collect the results of all paths.
+///
+/// All results are kept in memory until every path has been explored. For long runs the paths of
+/// a [VM] can instead be consumed one at a time, since it is an iterator over the path results.
+pub fn collect_project(project: &Project, function: &str) -> Result<Vec<PathResult>> {
+    let vm = VM::new(function, project)?;
+    collect_vm(vm)
+}
 
-                PathStatus::Ok(return_value)
-            }
-            Err(error) => {
-                let error_message = format!("{}", error);
-                let error_location = vm
-                    .state
-                    .current_loc
-                    .source_loc
-                    .map(|location| format!("{}", location));
-
-                let mut stack_trace = Vec::new();
-                for callstack in vm.state.callstack.iter().rev() {
-                    // Demangled function names, leave out the hash as well.
-                    let demangled = demangle(&callstack.location.func.name);
-                    let function_name = format!("{demangled:#}");
-
-                    let line = LineTrace {
-                        function_name,
-                        line: callstack
-                            .location
-                            .source_loc
-                            .map(|location| format!("{location}")),
+/// Collect the results of all paths of an already configured [VM].
+///
+/// This allows setting up the VM before running it, e.g. with [VM::taint_parameter] to track
+/// which results depend on an input.
+pub fn collect_vm(mut vm: VM<'_>) -> Result<Vec<PathResult>> {
+    let mut results = Vec::new();
+    while let Some(path_result) = vm.run() {
+        let path_result = to_path_result(&vm, results.len() + 1, path_result)?;
+        results.push(path_result);
+    }
+
+    Ok(results)
+}
+
+/// Run the analysis with a given Project and collect the results of the paths explored before
+/// `deadline` has passed.
+///
+/// Returns the results together with a flag that is `true` if the deadline was reached before all
+/// paths were explored. See [VM::run_all_with_deadline].
+pub fn collect_project_with_deadline(
+    project: &Project,
+    function: &str,
+    deadline: Duration,
+) -> Result<(Vec<PathResult>, bool)> {
+    let mut vm = VM::new(function, project)?;
+
+    let mut path_num = 0;
+    let (results, timed_out) = vm.run_all_with_deadline(deadline, |vm, path_result| {
+        path_num += 1;
+        to_path_result(vm, path_num, path_result)
+    });
+    let results = results.into_iter().collect::<Result<_>>()?;
+
+    Ok((results, timed_out))
+}
+
+/// Run the analysis on every public function in the project and collect the results.
+///
+/// All parameters of each function are symbolic. The results are keyed by the name of the function
+/// in the IR. If a function cannot be analyzed, e.g. if the VM cannot be created for it, the error
+/// is returned as a failed path so the rest of the functions are still analyzed.
+///
+/// All results are kept in memory, see [collect_project].
+pub fn run_all_entry_points(project: &Project) -> HashMap<String, Vec<PathResult>> {
+    project
+        .get_public_functions()
+        .map(|(_, function)| {
+            let results = collect_entry_point(project, &function.name);
+            (function.name.clone(), results)
+        })
+        .collect()
+}
+
+/// Helper to collect the results of all paths of `function`, errors are returned as failed paths.
+fn collect_entry_point(project: &Project, function: &str) -> Vec<PathResult> {
+    let mut vm = match VM::new(function, project) {
+        Ok(vm) => vm,
+        Err(error) => return vec![failed_path(1, error.into())],
+    };
+
+    let mut results = Vec::new();
+    while let Some(path_result) = vm.run() {
+        let path_num = results.len() + 1;
+        let path_result = to_path_result(&vm, path_num, path_result)
+            .unwrap_or_else(|error| failed_path(path_num, error));
+        results.push(path_result);
+    }
+
+    results
+}
+
+/// Helper to create a failed path for an error that occurred outside of the execution.
+fn failed_path(path_num: usize, error: anyhow::Error) -> PathResult {
+    let error_kind = match error.downcast_ref::<VMError>() {
+        Some(error) => error.kind(),
+        None => "Other",
+    };
+
+    PathResult {
+        path: path_num,
+        result: PathStatus::Failed(ErrorReason {
+            error_message: format!("{error}"),
+            error_kind: error_kind.to_owned(),
+            error_location: None,
+            stack_trace: Vec::new(),
+        }),
+        inputs: Vec::new(),
+        symbolics: Vec::new(),
+        location: None,
+        output: String::new(),
+    }
+}
+
+/// Serialize the results of all paths to JSON.
+#[cfg(feature = "serde")]
+pub fn to_json(results: &[PathResult]) -> Result<String> {
+    Ok(serde_json::to_string_pretty(results)?)
+}
+
+/// Helper to generate the result for the path the VM just finished executing.
+fn to_path_result(
+    vm: &VM<'_>,
+    path_num: usize,
+    path_result: x0001e::Result<ReturnValue>,
+) -> Result<PathResult> {
+    let project = vm.project;
+
+    // Cache for solutions.
+    //
+    // Solutions cannot be cached between paths, so instantiate a new one for each path.
+    let mut cache = SolutionGenerator::new(vm.solver.clone())?;
+
+    let inputs = generate_solutions(vm.parameters.iter(), &mut cache, project)?;
+    let symbolics = generate_solutions(vm.state.symbols.iter(), &mut cache, project)?;
+
+    let result = match path_result {
+        Ok(return_value) => {
+            let return_value = match return_value {
+                ReturnValue::Value(return_value) => {
+                    // Try to reconstruct the type based on the last executed instruction.
+                    let terminator = &vm.state.current_loc.block.term;
+                    let ty = match terminator {
+                        Terminator::Ret(instr) => match &instr.return_operand {
+                            Some(op) => Some(vm.state.type_of(op)),
+                            None => None,
+                        },
+                        _ => None,
                     };
-                    stack_trace.push(line);
+
+                    if let Some(ty) = ty {
+                        // Solve the return value.
+                        let value = cache.get_solution(&return_value)?;
+                        let value = ConcreteValue::from_binary_str(
+                            value.as_01x_str(),
+                            ty.as_ref(),
+                            project,
+                        );
+                        let variable = Variable {
+                            name: None,
+                            value,
+                            tainted: return_value.is_tainted(),
+                        };
+                        Some(variable)
+                    } else {
+                        None
+                    }
                 }
+                // Aggregates may instead be returned through an `sret` parameter.
+                ReturnValue::Void => match vm.sret_value()? {
+                    Some((value, ty)) => {
+                        let tainted = value.is_tainted();
+                        let value = cache.get_solution(&value)?;
+                        let value = ConcreteValue::from_binary_str(
+                            value.as_01x_str(),
+                            ty.as_ref(),
+                            project,
+                        );
+                        Some(Variable {
+                            name: None,
+                            value,
+                            tainted,
+                        })
+                    }
+                    None => None,
+                },
+            };
+
+            PathStatus::Ok(return_value)
+        }
+        Err(error) => {
+            let error_message = format!("{}", error);
+            let error_kind = error.kind().to_owned();
+            let error_location = vm
+                .state
+                .current_loc
+                .source_loc
+                .map(|location| format!("{}", location));
 
-                let error_reason = ErrorReason {
-                    error_message,
-                    error_location,
-                    stack_trace,
+            let mut stack_trace = Vec::new();
+            for callstack in vm.state.callstack.iter().rev() {
+                // Demangled function names, leave out the hash as well.
+                let demangled = demangle(&callstack.location.func.name);
+                let function_name = format!("{demangled:#}");
+
+                let line = LineTrace {
+                    function_name,
+                    line: callstack
+                        .location
+                        .source_loc
+                        .map(|location| format!("{location}")),
                 };
-                PathStatus::Failed(error_reason)
+                stack_trace.push(line);
             }
-        };
 
-        let path_result = PathResult {
-            path: path_num,
-            result,
-            inputs,
-            symbolics,
-        };
+            let error_reason = ErrorReason {
+                error_message,
+                error_kind,
+                error_location,
+                stack_trace,
+            };
+            PathStatus::Failed(error_reason)
+        }
+    };
 
-        println!("{}", path_result);
-    }
-    println!("Paths: {path_num}");
+    Ok(PathResult {
+        path: path_num,
+        result,
+        inputs,
+        symbolics,
+        location: Some(instruction_location(vm)),
+        output: vm.output().to_owned(),
+    })
+}
 
-    Ok(())
+/// Helper to get the location of the instruction that terminated the path.
+fn instruction_location(vm: &VM<'_>) -> InstructionLocation {
+    let location = vm.instruction_location();
+    let block_label = match location.block {
+        Name::Name(name) => name.to_string(),
+        Name::Number(number) => number.to_string(),
+    };
+
+    InstructionLocation {
+        function_name: format!("{:#}", demangle(&location.function)),
+        block_label,
+        instruction_index: location.instruction,
+    }
 }
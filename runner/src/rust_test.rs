@@ -0,0 +1,64 @@
+use core::fmt::Write;
+
+use crate::*;
+
+impl PathResult {
+    /// Generate a Rust test that calls `fn_name` with the inputs of this path.
+    ///
+    /// `fn_name` is the path of the function as it is called from the test, e.g. `crate::get`.
+    /// Paths that failed with a panic get a `#[should_panic]` attribute, so the test passes as
+    /// long as the bug can be reproduced. An abort cannot be caught by the test harness, so those
+    /// are only noted in a comment like other errors.
+    ///
+    /// Integers are passed with `as _` so they convert to the parameter type, and arrays are passed
+    /// as array literals. Pointers, structs, and values of unknown type cannot be generated, these
+    /// are passed as `todo!()` and have to be filled in by hand.
+    pub fn to_rust_test(&self, fn_name: &str) -> String {
+        let name: String = fn_name
+            .rsplit("::")
+            .next()
+            .unwrap_or(fn_name)
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+            .collect();
+
+        let arguments = self
+            .inputs
+            .iter()
+            .map(|input| rust_literal(&input.value))
+            .collect::<Vec<_>>()
+            .join(", ");
+
+        let mut test = String::from("#[test]\n");
+        if let PathStatus::Failed(reason) = &self.result {
+            match reason.error_kind.as_str() {
+                "Panic" => test.push_str("#[should_panic]\n"),
+                _ => writeln!(test, "// The path failed with: {}", reason.error_message).unwrap(),
+            }
+        }
+        writeln!(test, "fn {name}_path_{}() {{", self.path).unwrap();
+        writeln!(test, "    {fn_name}({arguments});").unwrap();
+        test.push_str("}\n");
+
+        test
+    }
+}
+
+/// Returns a Rust expression for `value`.
+fn rust_literal(value: &ConcreteValue) -> String {
+    match value {
+        ConcreteValue::Value { value, bits: 1 } => (*value != 0).to_string(),
+        ConcreteValue::Value {
+            value,
+            bits: bits @ (8 | 16 | 32 | 64),
+        } => format!("{value:#x}_u{bits} as _"),
+        ConcreteValue::Value { value, .. } => format!("{value:#x} as _"),
+        ConcreteValue::Pointer(_) => "todo!(\"pointer input\")".to_owned(),
+        ConcreteValue::Array(elements) => {
+            let elements: Vec<_> = elements.iter().map(rust_literal).collect();
+            format!("[{}]", elements.join(", "))
+        }
+        ConcreteValue::Struct(_) => "todo!(\"struct input\")".to_owned(),
+        ConcreteValue::Unknown(_) => "todo!(\"input of unknown type\")".to_owned(),
+    }
+}
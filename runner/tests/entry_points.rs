@@ -0,0 +1,17 @@
+use runner::run_all_entry_points;
+use x0001e::Project;
+
+// Check that every public function is analyzed.
+#[test]
+fn structs_all_entry_points() {
+    let project = Project::from_path("../tests/samples/structs.bc").unwrap();
+    let results = run_all_entry_points(&project);
+
+    let functions: Vec<_> = project.get_public_functions().collect();
+    assert!(!functions.is_empty(), "expected public functions");
+    assert_eq!(results.len(), functions.len());
+    for (_, function) in functions {
+        let paths = &results[&function.name];
+        assert!(!paths.is_empty(), "expected results for {}", function.name);
+    }
+}
@@ -0,0 +1,15 @@
+#![cfg(feature = "serde")]
+use runner::{collect_project, to_json, PathResult};
+use x0001e::Project;
+
+// Check that the path results survive a round-trip through JSON.
+#[test]
+fn multiple_paths_round_trip() {
+    let project = Project::from_path("../tests/samples/multiple_paths.bc").unwrap();
+    let results = collect_project(&project, "foo").unwrap();
+    assert_eq!(results.len(), 2, "expected 2 paths");
+
+    let json = to_json(&results).unwrap();
+    let parsed: Vec<PathResult> = serde_json::from_str(&json).unwrap();
+    assert_eq!(parsed, results);
+}
@@ -0,0 +1,38 @@
+use runner::{collect_project, PathStatus};
+use x0001e::{ir::*, Project};
+
+// Check that the failed path is located at the panic the bounds check branches to.
+#[test]
+fn array_index_failed_location() {
+    let project = Project::from_path("../tests/samples/array_index.bc").unwrap();
+    let results = collect_project(&project, "array_index::get").unwrap();
+    assert_eq!(results.len(), 2, "expected 2 paths");
+
+    let (failed, ok): (Vec<_>, Vec<_>) = results
+        .iter()
+        .partition(|result| matches!(result.result, PathStatus::Failed(_)));
+    assert_eq!(failed.len(), 1, "expected 1 failed path");
+    let location = failed[0].location.as_ref().unwrap();
+    assert_eq!(location.function_name, "array_index::get");
+    assert_ne!(Some(location), ok[0].location.as_ref());
+
+    let (_, function) = project.find_entry_function("array_index::get").unwrap();
+    let bounds_check = function
+        .basic_blocks
+        .iter()
+        .find_map(|block| match &block.term {
+            Terminator::CondBr(branch) => Some(branch),
+            _ => None,
+        })
+        .expect("expected a bounds check");
+
+    let label = |name: &Name| match name {
+        Name::Name(name) => name.to_string(),
+        Name::Number(number) => number.to_string(),
+    };
+    let targets = [
+        label(&bounds_check.true_dest),
+        label(&bounds_check.false_dest),
+    ];
+    assert!(targets.contains(&location.block_label), "{location:?}");
+}
@@ -0,0 +1,12 @@
+use runner::{collect_project, PathStatus};
+use x0001e::Project;
+
+// Check that the output of `printf` and `puts` is part of the result of the path.
+#[test]
+fn printf_output() {
+    let project = Project::from_path("../tests/samples/printf.bc").unwrap();
+    let results = collect_project(&project, "print_value").unwrap();
+    assert_eq!(results.len(), 1, "expected 1 path");
+    assert!(matches!(results[0].result, PathStatus::Ok(_)));
+    assert_eq!(results[0].output, "value: -42, hex: ff, ok\ndone\n");
+}
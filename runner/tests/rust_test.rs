@@ -0,0 +1,90 @@
+use runner::{collect_project, ConcreteValue, PathStatus};
+use x0001e::Project;
+
+// Check that the out of bounds path of `get` generates a test that reproduces the panic.
+#[test]
+fn array_index_rust_test() {
+    let project = Project::from_path("../tests/samples/array_index.bc").unwrap();
+    let results = collect_project(&project, "array_index::get").unwrap();
+    let failed = results
+        .iter()
+        .find(|result| matches!(result.result, PathStatus::Failed(_)))
+        .expect("expected a failed path");
+
+    let index = match failed.inputs[0].value {
+        ConcreteValue::Value { value, bits: 64 } => value,
+        ref value => panic!("expected a 64-bit index, got {value:?}"),
+    };
+    assert!(index >= 4, "expected an out of bounds index, got {index}");
+
+    let expected = format!(
+        "#[test]\n#[should_panic]\nfn get_path_{}() {{\n    get({index:#x}_u64 as _);\n}}\n",
+        failed.path
+    );
+    assert_eq!(failed.to_rust_test("get"), expected);
+}
+
+// Check that vectors are passed as array literals.
+#[test]
+fn array_input_rust_test() {
+    let project = Project::from_path("../tests/samples/vector_input.bc").unwrap();
+    let results = collect_project(&project, "ignore_bytes").unwrap();
+    assert_eq!(results.len(), 1, "expected 1 path");
+
+    let bytes = match &results[0].inputs[0].value {
+        ConcreteValue::Array(elements) => elements
+            .iter()
+            .map(|element| match element {
+                ConcreteValue::Value { value, bits: 8 } => format!("{value:#x}_u8 as _"),
+                value => panic!("expected a byte, got {value:?}"),
+            })
+            .collect::<Vec<_>>(),
+        value => panic!("expected an array, got {value:?}"),
+    };
+    assert_eq!(bytes.len(), 16);
+
+    let expected = format!(
+        "#[test]\nfn ignore_bytes_path_{}() {{\n    crate::ignore_bytes([{}]);\n}}\n",
+        results[0].path,
+        bytes.join(", ")
+    );
+    assert_eq!(results[0].to_rust_test("crate::ignore_bytes"), expected);
+}
+
+// Check that pointers are left for the user to fill in.
+#[test]
+fn pointer_input_rust_test() {
+    let project = Project::from_path("../tests/samples/null_deref.bc").unwrap();
+    let results = collect_project(&project, "deref").unwrap();
+    assert!(!results.is_empty(), "expected at least 1 path");
+
+    for result in &results {
+        assert!(matches!(result.inputs[0].value, ConcreteValue::Pointer(_)));
+        let test = result.to_rust_test("deref");
+        assert!(
+            test.contains("    deref(todo!(\"pointer input\"));\n"),
+            "{test}"
+        );
+    }
+}
+
+// Check that aborts, which cannot be caught by `#[should_panic]`, are only noted in a comment.
+#[test]
+fn abort_rust_test() {
+    let project = Project::from_path("../tests/samples/panic_fmt.bc").unwrap();
+    let results = collect_project(&project, "panic_fmt::check").unwrap();
+    let failed = results
+        .iter()
+        .find(|result| matches!(result.result, PathStatus::Failed(_)))
+        .expect("expected a failed path");
+
+    let reason = match &failed.result {
+        PathStatus::Failed(reason) => reason,
+        PathStatus::Ok(_) => unreachable!(),
+    };
+    assert_eq!(reason.error_kind, "Abort");
+
+    let test = failed.to_rust_test("check");
+    assert!(!test.contains("#[should_panic]"), "{test}");
+    assert!(test.contains("// The path failed with:"), "{test}");
+}
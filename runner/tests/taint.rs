@@ -0,0 +1,18 @@
+use runner::{collect_vm, PathStatus};
+use x0001e::{Project, VM};
+
+// Check that results that depend on a tainted input are marked as tainted.
+#[test]
+fn leading_zeros_tainted() {
+    let project = Project::from_path("../tests/samples/leading_zeros.bc").unwrap();
+    let mut vm = VM::new("leading_zeros::count", &project).unwrap();
+    vm.taint_parameter(0).unwrap();
+
+    let results = collect_vm(vm).unwrap();
+    assert_eq!(results.len(), 1, "expected 1 path");
+    assert!(results[0].inputs[0].tainted);
+    match &results[0].result {
+        PathStatus::Ok(Some(value)) => assert!(value.tainted),
+        result => panic!("expected a return value, got {result:?}"),
+    }
+}
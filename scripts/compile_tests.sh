@@ -9,10 +9,16 @@ done
 opts="-C panic=abort -C link-dead-code=yes" # -C debuginfo=2"
 
 # Compile all .rs files used in tests.
+#
+# Samples that test unwinding need landing pads, so those are not compiled with panic=abort.
 for file in $(find tests/samples -name '*.rs'); do
     echo "generating files for $file"
-    rustc --emit=llvm-bc --edition=2021 -o ${file%.*}.bc $opts $file
-    rustc --emit=llvm-ir --edition=2021 -o ${file%.*}.ll $opts $file
+    case $file in
+        *unwind*) file_opts="-C panic=unwind -C link-dead-code=yes" ;;
+        *) file_opts=$opts ;;
+    esac
+    rustc --emit=llvm-bc --edition=2021 -o ${file%.*}.bc $file_opts $file
+    rustc --emit=llvm-ir --edition=2021 -o ${file%.*}.ll $file_opts $file
 done
 
 # Compile all .rs files used in doctests.
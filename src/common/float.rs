@@ -0,0 +1,184 @@
+//! Helpers for floating point values.
+//!
+//! Floating point is not supported by the solver, so floating point values can only be handled
+//! when they are concrete. Values are stored as their IEEE 754 bit pattern, and only `float` and
+//! `double` are supported.
+use llvm_ir::{constant::Float, types::FPType, Constant, Operand, Type};
+
+use crate::{
+    solver::BV,
+    vm::{Result, State, VMError},
+};
+
+fn unsupported() -> VMError {
+    VMError::UnsupportedInstruction("Floating point".to_owned())
+}
+
+/// Returns the value of a floating point operand, or `None` if it is symbolic.
+pub(crate) fn get_concrete_float(state: &State<'_>, operand: &Operand) -> Result<Option<f64>> {
+    // Floating point constants cannot be converted to a `BV`, so handle them separately.
+    if let Operand::ConstantOperand(constant) = operand {
+        if let Constant::Float(float) = constant.as_ref() {
+            return match float {
+                Float::Single(value) => Ok(Some(*value as f64)),
+                Float::Double(value) => Ok(Some(*value)),
+                _ => Err(unsupported()),
+            };
+        }
+    }
+
+    let ty = state.type_of(operand);
+    let bits = match get_concrete_bits(&state.get_var(operand)?) {
+        Some(bits) => bits,
+        None => return Ok(None),
+    };
+
+    match ty.as_ref() {
+        Type::FPType(FPType::Single) => Ok(Some(f32::from_bits(bits as u32) as f64)),
+        Type::FPType(FPType::Double) => Ok(Some(f64::from_bits(bits))),
+        _ => Err(unsupported()),
+    }
+}
+
+/// Floating point binary operations, see [float_binop].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(crate) enum FloatOp {
+    Add,
+    Sub,
+    Mul,
+    Div,
+    Rem,
+}
+
+/// Applies `op` to two floating point operands, the result has the floating point type `ty`.
+///
+/// Both operands must be concrete. The operations are calculated in double precision, which
+/// gives correctly rounded results for single precision as well.
+pub(crate) fn float_binop(
+    state: &State<'_>,
+    lhs: &Operand,
+    rhs: &Operand,
+    op: FloatOp,
+    ty: &Type,
+) -> Result<BV> {
+    let lhs = get_concrete_float(state, lhs)?;
+    let rhs = get_concrete_float(state, rhs)?;
+
+    let (lhs, rhs) = match (lhs, rhs) {
+        (Some(lhs), Some(rhs)) => (lhs, rhs),
+        _ => {
+            return Err(VMError::UnsupportedInstruction(
+                "Symbolic floating point".to_owned(),
+            ))
+        }
+    };
+
+    let result = match op {
+        FloatOp::Add => lhs + rhs,
+        FloatOp::Sub => lhs - rhs,
+        FloatOp::Mul => lhs * rhs,
+        FloatOp::Div => lhs / rhs,
+        FloatOp::Rem => lhs % rhs,
+    };
+    float_to_bv(state, result, ty)
+}
+
+/// Returns the value of a concrete integer operand, or `None` if it is symbolic.
+///
+/// If `signed` is set the value is sign extended, otherwise it is zero extended.
+pub(crate) fn get_concrete_int(
+    state: &State<'_>,
+    operand: &Operand,
+    signed: bool,
+) -> Result<Option<i128>> {
+    let value = state.get_var(operand)?;
+    if value.len() > 64 {
+        return Err(VMError::UnsupportedInstruction(
+            "Integers larger than 64 bits to floating point".to_owned(),
+        ));
+    }
+
+    let value = if signed {
+        get_concrete_bits(&value.resize_signed(64)).map(|bits| bits as i64 as i128)
+    } else {
+        get_concrete_bits(&value).map(|bits| bits as i128)
+    };
+    Ok(value)
+}
+
+/// Returns `value` as a floating point value of type `ty`.
+pub(crate) fn float_to_bv(state: &State<'_>, value: f64, ty: &Type) -> Result<BV> {
+    match ty {
+        Type::FPType(FPType::Single) => Ok(state
+            .solver
+            .bv_from_u64((value as f32).to_bits() as u64, 32)),
+        Type::FPType(FPType::Double) => Ok(state.solver.bv_from_u64(value.to_bits(), 64)),
+        _ => Err(unsupported()),
+    }
+}
+
+/// Converts an integral floating point value to an integer of size `bits`, at most 64 bits.
+///
+/// Returns `None` if the value does not fit in the integer.
+pub(crate) fn float_to_int(state: &State<'_>, value: f64, bits: u32, signed: bool) -> Option<BV> {
+    assert!(bits <= 64);
+    let (min, max) = int_range(bits, signed);
+    if value.is_nan() || value < min || value >= max {
+        return None;
+    }
+
+    let value = if signed {
+        value as i64 as u64
+    } else {
+        value as u64
+    };
+    let bv = state.solver.bv_from_u64(value, 64);
+    Some(bv.resize_unsigned(bits))
+}
+
+/// Converts a floating point value to an integer of size `bits`, saturating on overflow.
+///
+/// The value is truncated towards zero, and `NaN` is converted to zero.
+pub(crate) fn float_to_int_saturating(
+    state: &State<'_>,
+    value: f64,
+    bits: u32,
+    signed: bool,
+) -> BV {
+    if value.is_nan() {
+        return state.solver.bv_zero(bits);
+    }
+
+    let (min, max) = int_range(bits, signed);
+    if value < min {
+        if signed {
+            state.solver.bv_signed_min(bits)
+        } else {
+            state.solver.bv_zero(bits)
+        }
+    } else if value >= max {
+        if signed {
+            state.solver.bv_signed_max(bits)
+        } else {
+            state.solver.bv_unsigned_max(bits)
+        }
+    } else {
+        float_to_int(state, value.trunc(), bits, signed).unwrap()
+    }
+}
+
+/// Returns the range `min..max` of an integer of size `bits` as floating point values.
+fn int_range(bits: u32, signed: bool) -> (f64, f64) {
+    if signed {
+        let max = 2f64.powi(bits as i32 - 1);
+        (-max, max)
+    } else {
+        (0.0, 2f64.powi(bits as i32))
+    }
+}
+
+fn get_concrete_bits(value: &BV) -> Option<u64> {
+    value
+        .to_string_binary()
+        .map(|bits| u64::from_str_radix(&bits, 2).unwrap())
+}
@@ -1,4 +1,5 @@
 //! Structs, enums, and functions used across the project.
+mod float;
 mod op;
 mod ops;
 mod size;
@@ -13,5 +14,6 @@ pub use to_symbol::*;
 pub use to_value::*;
 pub use util::get_u64_solution_from_operand;
 
+pub(crate) use float::*;
 pub(crate) use ops::*;
 pub(crate) use size::*;
@@ -1,5 +1,6 @@
 use llvm_ir::{IntPredicate, Operand, Type};
 use log::error;
+use std::cell::Cell;
 
 use super::{Op, ToValue};
 use crate::{
@@ -10,7 +11,8 @@ use crate::{
 
 /// Calculate start and end offset into the aggregate.
 ///
-///
+/// The indices form a path into nested aggregates, so the offset is accumulated for each level.
+/// Returns the bit offset to the start of the element, and the bit offset one past its end.
 pub(crate) fn get_element_offset(
     state: &State<'_>,
     aggregate: &Operand,
@@ -19,6 +21,15 @@ pub(crate) fn get_element_offset(
     let mut ty = state.type_of(aggregate);
     let mut total_offset = 0;
     for index in indices.iter().copied() {
+        // Only structs and arrays can be indexed by `extractvalue` and `insertvalue`.
+        if !matches!(
+            ty.as_ref(),
+            Type::StructType { .. } | Type::NamedStructType { .. } | Type::ArrayType { .. }
+        ) {
+            error!("aggregate index into non-aggregate type {}", ty);
+            return Err(VMError::MalformedInstruction);
+        }
+
         let (offset, inner_ty) = state.project.bit_offset_concrete(&ty, index as u64)?;
 
         total_offset += offset;
@@ -36,12 +47,34 @@ pub(crate) fn extract_value(state: &State<'_>, aggregate: &Operand, indices: &[u
 
     // Get the value and check that the BV is big enough to accommodate our slice.
     let value = state.get_var(aggregate)?;
-    assert!(value.len() >= offset_upper_bound as u32);
+    if (value.len() as u64) < offset_upper_bound {
+        return Err(VMError::MalformedInstruction);
+    }
 
     let value = value.slice(total_offset as u32, offset_upper_bound as u32 - 1);
     Ok(value)
 }
 
+/// Replace the element at `indices` in `aggregate` with `element`.
+pub(crate) fn insert_value(
+    state: &State<'_>,
+    aggregate: &Operand,
+    element: &Operand,
+    indices: &[u32],
+) -> Result<BV> {
+    let (total_offset, offset_upper_bound) = get_element_offset(state, aggregate, indices)?;
+
+    let original = state.get_var(aggregate)?;
+    let value = state.get_var(element)?;
+    if (original.len() as u64) < offset_upper_bound
+        || value.len() as u64 != offset_upper_bound - total_offset
+    {
+        return Err(VMError::MalformedInstruction);
+    }
+
+    Ok(original.replace_part(total_offset as u32, value))
+}
+
 pub(crate) fn gep<'p, T, I>(
     state: &State<'_>,
     address: T,
@@ -69,14 +102,23 @@ where
     }
 
     for index in indices {
-        let (offset, ty) = if index.is_constant() {
+        // Struct indices are always constant, for other types the index is a signed integer which
+        // may be symbolic.
+        let is_struct = matches!(
+            curr_ty.as_ref(),
+            Type::StructType { .. } | Type::NamedStructType { .. }
+        );
+
+        let (offset, ty) = if is_struct {
             let index = index.to_value()?;
             let (offset, ty) = get_byte_offset_concrete(&curr_ty, index, state.project)?;
 
             let offset = state.solver.bv_from_u64(offset, ptr_size);
             (offset, ty)
         } else {
-            let index = state.get_var(index)?;
+            // Sign extend the index so negative indices work, and so it has the same width as
+            // the address.
+            let index = state.get_var(index)?.resize_signed(ptr_size);
             get_byte_offset_symbol(&curr_ty, &index, state.project)?
         };
 
@@ -90,7 +132,8 @@ where
 /// Perform a binary operation on two operands, returning the result.
 ///
 /// The input types must be either integers or a vector of integers. Vector operations are performed
-/// on a per element basis.
+/// on a per element basis. The result of `operation` does not have to be as wide as its inputs,
+/// for vectors the results for each element are concatenated. See [cmp_binop] for comparisons.
 ///
 /// TODO: No operations currently care about overflows and such.
 pub(crate) fn binop<F>(state: &State<'_>, lhs: &Operand, rhs: &Operand, operation: F) -> Result<BV>
@@ -105,10 +148,14 @@ where
     use Type::*;
     match (lhs_ty.as_ref(), rhs_ty.as_ref()) {
         // For simple integer types the result is trivial to do, just perform the operation.
-        (IntegerType { .. }, IntegerType { .. }) => Ok(operation(&lhs, &rhs)),
-
+        //
         // Some may support pointer operands, such as icmp, which work the same as integer ones.
-        (PointerType { .. }, PointerType { .. }) => Ok(operation(&lhs, &rhs)),
+        (IntegerType { .. }, IntegerType { .. }) | (PointerType { .. }, PointerType { .. }) => {
+            // Malformed bitcode can have operands of different widths, report that instead of
+            // panicking in the operation.
+            lhs.check_same_width(&rhs)?;
+            Ok(operation(&lhs, &rhs))
+        }
 
         // The docs do not really specify how the vector operations should work. But I'll assume it
         // is the operation on a per element basis.
@@ -194,10 +241,15 @@ where
             },
             VectorType {
                 element_type,
+                num_elements: target_num_elements,
                 scalable: false,
-                ..
             },
         ) => {
+            // Conversions are done element by element, so both must have the same length.
+            if num_elements != target_num_elements {
+                return Err(VMError::MalformedInstruction);
+            }
+
             let source_bits = state.project.bit_size(source_element_ty)?;
             let target_bits = state.project.bit_size(element_type)?;
             let num_elements = *num_elements as u32;
@@ -228,23 +280,57 @@ where
 /// Cast operand to type `ty`.
 ///
 /// Casting simply reinterprets the bits as a different type. As the system does not return types,
-/// this just returns the underlying symbol. The bit widths must match.
+/// this just returns the underlying symbol. The bit widths must match, otherwise
+/// [VMError::MalformedInstruction] is returned.
 pub(crate) fn cast_to<'p, T>(state: &State<'_>, ty: &Type, op: T) -> Result<BV>
 where
     T: Into<Op<'p>>,
 {
     let bv = state.get_var(op.into())?;
-    assert_eq!(bv.len(), state.project.bit_size(ty).unwrap() as u32);
+    if bv.len() != state.project.bit_size(ty)? {
+        return Err(VMError::MalformedInstruction);
+    }
     Ok(bv)
 }
 
+/// Perform a comparison on two operands, returning the result.
+///
+/// Works as [binop] except that `comparison` must return a single bit. So the result is one bit
+/// for integers, and for vectors one bit per element.
+pub(crate) fn cmp_binop<F>(
+    state: &State<'_>,
+    lhs: &Operand,
+    rhs: &Operand,
+    comparison: F,
+) -> Result<BV>
+where
+    F: Fn(&BV, &BV) -> BV,
+{
+    let valid = Cell::new(true);
+    let result = binop(state, lhs, rhs, |lhs, rhs| {
+        let result = comparison(lhs, rhs);
+        if result.len() != 1 {
+            valid.set(false);
+        }
+        result
+    })?;
+
+    if valid.get() {
+        Ok(result)
+    } else {
+        Err(VMError::InternalError(
+            "Comparison result must be a single bit",
+        ))
+    }
+}
+
 pub(crate) fn icmp(
     state: &State<'_>,
     lhs: &Operand,
     rhs: &Operand,
     predicate: IntPredicate,
 ) -> Result<BV> {
-    binop(state, lhs, rhs, |lhs, rhs| match predicate {
+    cmp_binop(state, lhs, rhs, |lhs, rhs| match predicate {
         IntPredicate::EQ => lhs.eq(&rhs),
         IntPredicate::NE => lhs.ne(&rhs),
         IntPredicate::UGT => lhs.ugt(&rhs),
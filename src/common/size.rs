@@ -5,10 +5,14 @@ use crate::{
     memory::to_bytes,
     project::Project,
     vm::{Result, VMError},
-    BV,
+    Solver, BV,
 };
 
 /// Calculates the size of the type in bits.
+///
+/// Aggregates are laid out as by the data layout, so structs include the padding between their
+/// members and at the end, and array elements are placed at their allocation size. This is the
+/// same layout as values of the type have in memory.
 pub fn size_in_bits(ty: &Type, project: &Project) -> Option<u64> {
     use Type::*;
 
@@ -18,22 +22,23 @@ pub fn size_in_bits(ty: &Type, project: &Project) -> Option<u64> {
         PointerType { .. } => Some(project.ptr_size as u64),
         FPType(fp_ty) => Some(fp_size_in_bits(fp_ty)),
 
-        // Vectors and arrays are similar, both have an element size and a number of elements.
+        // The elements of vectors are packed together.
         VectorType {
             element_type,
             num_elements,
             ..
-        }
-        | ArrayType {
+        } => size_in_bits(element_type, project).map(|size| *num_elements as u64 * size),
+
+        // The elements of arrays are placed at their allocation size.
+        ArrayType {
             element_type,
             num_elements,
-        } => size_in_bits(element_type, project).map(|size| *num_elements as u64 * size),
+        } => alloc_size_in_bits(element_type, project).map(|size| *num_elements as u64 * size),
 
-        // Sum the size of each member.
-        StructType { element_types, .. } => element_types
-            .iter()
-            .map(|ty| size_in_bits(ty, project))
-            .sum(),
+        StructType {
+            element_types,
+            is_packed,
+        } => struct_layout(element_types, *is_packed, project).map(|(_, size)| size),
 
         // For named structs get the underlying struct from the project.
         //
@@ -53,6 +58,96 @@ pub fn size_in_bits(ty: &Type, project: &Project) -> Option<u64> {
     }
 }
 
+/// Calculates the allocation size of the type in bits.
+///
+/// Unlike [size_in_bits] this is rounded up so that consecutive elements of an array are aligned.
+pub fn alloc_size_in_bits(ty: &Type, project: &Project) -> Option<u64> {
+    let size = size_in_bits(ty, project)?;
+    let align = abi_alignment_in_bits(ty, project)?;
+    Some(align_to(align_to(size, 8), align))
+}
+
+/// Returns the offset in bits of each member of a struct, and the size of the struct.
+///
+/// Members of structs that are not packed are placed at their alignment, and the size is padded
+/// to the alignment of the struct.
+fn struct_layout(
+    element_types: &[TypeRef],
+    is_packed: bool,
+    project: &Project,
+) -> Option<(Vec<u64>, u64)> {
+    let mut offsets = Vec::with_capacity(element_types.len());
+    let mut offset = 0;
+    let mut struct_align = 8;
+    for element_type in element_types {
+        if !is_packed {
+            let align = abi_alignment_in_bits(element_type, project)?;
+            offset = align_to(offset, align);
+            struct_align = struct_align.max(align);
+        }
+        offsets.push(offset);
+        offset += alloc_size_in_bits(element_type, project)?;
+    }
+
+    if !is_packed {
+        let agg_align = project.data_layout().alignments.agg_alignment().abi as u64;
+        offset = align_to(offset, struct_align.max(agg_align));
+    }
+    Some((offsets, offset))
+}
+
+/// Calculates the ABI alignment of the type in bits, as given by the data layout.
+///
+/// Structs are aligned as their most aligned member, or to a single byte if they are packed.
+pub fn abi_alignment_in_bits(ty: &Type, project: &Project) -> Option<u64> {
+    use Type::*;
+
+    let alignments = &project.data_layout().alignments;
+    match ty {
+        IntegerType { bits } => Some(alignments.int_alignment(*bits).abi as u64),
+        PointerType { addr_space, .. } => {
+            Some(alignments.ptr_alignment(*addr_space).alignment.abi as u64)
+        }
+        FPType(fp_ty) => Some(alignments.fp_alignment(*fp_ty).abi as u64),
+        VectorType { .. } => {
+            let size = size_in_bits(ty, project)?;
+            Some(alignments.vec_alignment(size as u32).abi as u64)
+        }
+        ArrayType { element_type, .. } => abi_alignment_in_bits(element_type, project),
+
+        StructType {
+            element_types,
+            is_packed,
+        } => {
+            if *is_packed {
+                return Some(8);
+            }
+
+            let mut align = (alignments.agg_alignment().abi as u64).max(8);
+            for element_type in element_types {
+                align = align.max(abi_alignment_in_bits(element_type, project)?);
+            }
+            Some(align)
+        }
+
+        NamedStructType { name } => match project.get_named_struct(name)? {
+            NamedStructDef::Opaque => None,
+            NamedStructDef::Defined(ty) => abi_alignment_in_bits(ty, project),
+        },
+
+        _ => None,
+    }
+}
+
+/// Round `value` up to the next multiple of `align`.
+fn align_to(value: u64, align: u64) -> u64 {
+    if align == 0 {
+        value
+    } else {
+        (value + align - 1) / align * align
+    }
+}
+
 /// Returns the size of a floating point type.
 pub fn fp_size_in_bits(ty: &FPType) -> u64 {
     match ty {
@@ -66,6 +161,61 @@ pub fn fp_size_in_bits(ty: &FPType) -> u64 {
     }
 }
 
+/// Combine the `members` of the aggregate `ty` into a single value with the layout of the type.
+///
+/// Each member is placed at its offset, and the padding between members and at the end of the
+/// aggregate is zero. Zero-sized members are passed as `None`. Returns `None` if the aggregate
+/// itself is zero-sized.
+pub fn layout_members(
+    ty: &Type,
+    members: impl IntoIterator<Item = Option<BV>>,
+    solver: &Solver,
+    project: &Project,
+) -> Result<Option<BV>> {
+    let size = size_in_bits(ty, project)
+        .ok_or_else(|| VMError::Other(anyhow!("Cannot take size of type")))?;
+
+    let mut parts = Vec::new();
+    let mut end = 0;
+    for (index, member) in members.into_iter().enumerate() {
+        let (offset, _) = get_bit_offset_concrete(ty, index as u64, project)?;
+        if offset < end {
+            return Err(VMError::MalformedInstruction);
+        }
+        if offset > end {
+            parts.push(solver.bv_zero((offset - end) as u32));
+        }
+
+        end = offset;
+        if let Some(member) = member {
+            end += member.len() as u64;
+            parts.push(member);
+        }
+    }
+
+    if end > size {
+        return Err(VMError::MalformedInstruction);
+    }
+    if size > end {
+        parts.push(solver.bv_zero((size - end) as u32));
+    }
+
+    // The first member is stored in the least significant bits.
+    Ok(parts.into_iter().reduce(|acc, v| v.concat(&acc)))
+}
+
+/// Returns the distance in bits between consecutive elements of `inner_ty` when indexing into
+/// `ty`.
+///
+/// The lanes of vectors are packed together, while elements behind pointers and in arrays are
+/// placed at their allocation size.
+fn element_stride_in_bits(ty: &Type, inner_ty: &Type, project: &Project) -> Option<u64> {
+    match ty {
+        Type::VectorType { .. } => size_in_bits(inner_ty, project),
+        _ => alloc_size_in_bits(inner_ty, project),
+    }
+}
+
 /// Calculate the offset in bytes from a concrete index.
 ///
 /// Note that the conversion from bits to bytes is performed after the offset has been calculated.
@@ -83,6 +233,8 @@ pub fn get_byte_offset_concrete(
 }
 
 /// Calculate the offset in bits from a concrete index.
+///
+/// The offsets follow the same layout as [size_in_bits] and [alloc_size_in_bits].
 pub fn get_bit_offset_concrete(ty: &Type, index: u64, project: &Project) -> Result<(u64, TypeRef)> {
     use Type::*;
 
@@ -95,28 +247,24 @@ pub fn get_bit_offset_concrete(ty: &Type, index: u64, project: &Project) -> Resu
         PointerType { pointee_type: inner_ty, .. }
         | VectorType { element_type: inner_ty, .. }
         | ArrayType { element_type: inner_ty, .. } => {
-            size_in_bits(inner_ty, project)
+            element_stride_in_bits(ty, inner_ty, project)
                 .map(|size| (size * index, inner_ty.clone()))
                 .ok_or_else(|| anyhow!("Cannot take size of type").into())
         },
 
-        // For structs we have to collect the size of all the members until the index.
-        //
-        // TODO: How are non packed structs handled?
+        // For structs the members are placed by the data layout.
         StructType {
             element_types,
-            is_packed: _,
+            is_packed,
         } => {
-            let offset = element_types
-                .iter()
-                .take(index as usize)
-                .map(|ty| size_in_bits(ty, project).ok_or_else(|| anyhow!("Oops").into()))
-                .sum::<Result<u64>>()?;
+            let (offsets, _) = struct_layout(element_types, *is_packed, project)
+                .ok_or_else(|| VMError::Other(anyhow!("Cannot take size of type")))?;
 
-            let inner_ty = element_types
+            let offset = offsets
                 .get(index as usize)
-                .cloned()
+                .copied()
                 .ok_or(VMError::MalformedInstruction)?;
+            let inner_ty = element_types[index as usize].clone();
 
             Ok((offset, inner_ty))
         }
@@ -154,7 +302,7 @@ pub fn get_byte_offset_symbol(ty: &Type, index: &BV, project: &Project) -> Resul
         PointerType { pointee_type: inner_ty, .. }
         | VectorType { element_type: inner_ty, .. }
         | ArrayType { element_type: inner_ty, .. } => {
-            let size = size_in_bits(inner_ty, project)
+            let size = element_stride_in_bits(ty, inner_ty, project)
                 .ok_or_else(|| VMError::Other(anyhow!("Cannot take size of type")))?;
 
             let size = to_bytes(size)?;
@@ -193,7 +341,7 @@ pub fn get_bit_offset_symbol(ty: &Type, index: &BV, project: &Project) -> Result
         PointerType { pointee_type: inner_ty, .. }
         | VectorType { element_type: inner_ty, .. }
         | ArrayType { element_type: inner_ty, .. } => {
-            let size = size_in_bits(inner_ty, project)
+            let size = element_stride_in_bits(ty, inner_ty, project)
                 .ok_or_else(|| VMError::Other(anyhow!("Cannot take size of type")))?;
 
             let size = index.get_solver().bv_from_u64(size, index.len());
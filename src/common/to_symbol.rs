@@ -5,7 +5,7 @@ use anyhow::anyhow;
 use llvm_ir::{Constant, ConstantRef, IntPredicate, Operand, Type};
 use log::warn;
 
-use super::{convert_to_map, gep, ToValue};
+use super::{convert_to_map, gep, layout_members, ToValue};
 use crate::{
     solver::BV,
     vm::{GlobalReferenceKind, Result, State, VMError},
@@ -104,13 +104,14 @@ pub fn const_to_symbol_zero_size(state: &State<'_>, constant: &Constant) -> Resu
         // TODO: Check vector types, e.g. a vector <4xi4> <i4 1, i4 2, i4 3, i4 4> is laid out as
         // 0x4321 in little endian systems. May be what I'm doing below.
         Array { elements: e, .. } | Vector(e) | Struct { values: e, .. } => {
-            // Process each member as maybe being zero-sized. All of them might be thus the reduce
-            // can receive zero elements.
+            // Process each member as maybe being zero-sized, and place them by the layout of the
+            // aggregate so any padding between them is included.
             let values = e
                 .iter()
-                .filter_map(|c| const_to_symbol_zero_size(state, c).transpose())
+                .map(|c| const_to_symbol_zero_size(state, c))
                 .collect::<Result<Vec<_>>>()?;
-            Ok(values.into_iter().reduce(|acc, v| v.concat(&acc)))
+            let ty = state.type_of(constant);
+            layout_members(&ty, values, &state.solver, state.project)
         }
 
         // Global reference should be added and initialized before starting execution.
@@ -121,25 +122,32 @@ pub fn const_to_symbol_zero_size(state: &State<'_>, constant: &Constant) -> Resu
             let global = state
                 .global_references
                 .get(name, state.current_loc.module)
-                .cloned()
                 .ok_or_else(|| VMError::Other(anyhow!("Global ref not found: {:?}", name)))?;
 
+            // Global variables are initialized the first time they are referenced. The flag is
+            // set on the stored reference, so later references do not overwrite any writes made
+            // to the global.
             if let GlobalReferenceKind::GlobalVariable { var, initialized } = &global.kind {
                 if !initialized.get() {
+                    // Mark it before converting the initializer, as it may refer to itself.
+                    initialized.set(true);
+
+                    // Aggregate initializers (arrays, structs, and any nesting of those) are
+                    // converted recursively into a single value covering the entire global.
                     if let Some(initializer) = &var.initializer {
-                        match const_to_symbol(state, initializer) {
-                            Ok(value) => {
+                        match const_to_symbol_zero_size(state, initializer) {
+                            Ok(Some(value)) => {
                                 let addr = state
                                     .solver
                                     .bv_from_u64(global.addr, state.project.ptr_size);
                                 state.mem.borrow_mut().write(&addr, value)?;
                             }
+                            Ok(None) => {}
                             Err(err) => {
                                 warn!("Error initializing global: {:?}", err);
                             }
                         }
                     }
-                    initialized.set(true);
                 }
             }
 
@@ -389,9 +397,15 @@ pub fn const_to_symbol_zero_size(state: &State<'_>, constant: &Constant) -> Resu
             Err(VMError::UnsupportedInstruction("Floating point".to_owned()))
         }
 
+        // llvm-ir does not say which block the address is of, so it can be any address. Indirect
+        // branches do not use the address, only those with a single destination are supported.
+        BlockAddress => {
+            let bits = state.project.ptr_size;
+            Ok(Some(state.solver.bv_fresh(bits, "blockaddress")))
+        }
+
         // TODO
         Poison(_) => todo!(),
-        BlockAddress => todo!(),
         TokenNone => todo!(),
     }
 }
@@ -4,19 +4,13 @@
 //! Current modules:
 //! - `rust` exposes functions for panics and allocations.
 //!
-use crate::{
-    hooks::FnInfo,
-    vm::{Result, ReturnValue, VM},
-};
+use crate::hooks::Hook;
 
 mod rust;
 
 pub use rust::RustModule;
 
-type UserDefinedFunction = (
-    &'static str,
-    fn(&mut VM<'_>, f: FnInfo) -> Result<ReturnValue>,
-);
+type UserDefinedFunction = (&'static str, Hook);
 
 pub trait CustomModule {
     fn get_name(&self) -> &'static str;
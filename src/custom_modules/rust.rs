@@ -1,11 +1,13 @@
+use anyhow::anyhow;
 use log::debug;
 
 use super::{CustomModule, UserDefinedFunction};
 use crate::{
     common::get_u64_solution_from_operand,
-    hooks::FnInfo,
+    hooks::HookCtx,
     memory::BITS_IN_BYTE,
-    vm::{Result, ReturnValue, VMError, VM},
+    solver::Solutions,
+    vm::{PanicReason, Result, ReturnValue, VMError},
 };
 
 /// Custom module for Rust functionality.
@@ -25,21 +27,85 @@ impl CustomModule for RustModule {
             ("__rust_dealloc", rust_dealloc),
             ("__rust_realloc", rust_realloc),
             ("__rust_alloc_zeroed", rust_alloc_zeroed),
-            ("core::panicking::panic_bounds_check", abort),
-            ("core::panicking::panic", abort),
+            ("core::panicking::panic_bounds_check", panic_bounds_check),
+            ("core::panicking::panic", panic),
             ("core::panicking::panic_fmt", abort),
+            ("std::panicking::try::cleanup", panic_cleanup),
+            ("__rust_panic_cleanup", panic_cleanup),
         ]
     }
 }
 
 /// Hook that tells the VM to abort.
-pub fn abort(_vm: &mut VM<'_>, _info: FnInfo) -> Result<ReturnValue> {
+pub fn abort(_ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
     debug!("Hook: ABORT");
     Err(VMError::Abort(-1))
 }
 
-fn rust_alloc(vm: &mut VM<'_>, info: FnInfo) -> Result<ReturnValue> {
+/// Hook for out of bounds panics, captures the index and length that caused the panic.
+pub fn panic_bounds_check(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    // fn panic_bounds_check(index: usize, len: usize, location: &Location<'_>) -> !
+    assert_eq!(ctx.args().len(), 3);
+    debug!("Hook: panic_bounds_check");
+
+    let index = get_u64_solution_from_operand(ctx.state(), &ctx.args()[0].0)?;
+    let len = get_u64_solution_from_operand(ctx.state(), &ctx.args()[1].0)?;
+
+    Err(VMError::Panic(PanicReason::BoundsCheck { index, len }))
+}
+
+/// Hook for panics with a static message, captures the message.
+///
+/// This is what the overflow checks in debug builds call, e.g. with "attempt to add with overflow".
+pub fn panic(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    // fn panic(expr: &'static str, location: &Location<'_>) -> !
+    //
+    // The `&str` is passed as a pointer and a length.
+    assert_eq!(ctx.args().len(), 3);
+    debug!("Hook: panic");
+
+    let addr = ctx.arg(0)?;
+    let len = get_u64_solution_from_operand(ctx.state(), &ctx.args()[1].0)?;
+
+    let mut bytes = Vec::new();
+    let one = ctx.solver().bv_from_u64(1, addr.len());
+    let mut addr = addr;
+    for _ in 0..len {
+        let byte = ctx.mem().read(&addr, BITS_IN_BYTE)?;
+        let byte = match ctx.solver().get_solutions_for_bv(&byte, 1)? {
+            Solutions::None => return Err(VMError::Unsat),
+            Solutions::Exactly(s) => s[0].as_u64().unwrap() as u8,
+            Solutions::AtLeast(_) => {
+                return Err(VMError::Other(anyhow!(
+                    "Expected a panic message with a concrete value"
+                )))
+            }
+        };
+        bytes.push(byte);
+        addr = addr.add(&one);
+    }
+
+    let message = String::from_utf8_lossy(&bytes).into_owned();
+    Err(VMError::Panic(PanicReason::Message(message)))
+}
+
+/// Hook for catching a panic in `catch_unwind`, stops the unwinding.
+///
+/// The panic payload is not modeled, so the returned `Box<dyn Any + Send>` is null and must not
+/// be used.
+pub fn panic_cleanup(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    // fn cleanup(payload: *mut u8) -> Box<dyn Any + Send + 'static>
+    debug!("Hook: panic_cleanup");
+    let (vm, _) = ctx.split();
+    vm.state.exception = None;
+
+    let bits = ctx.bit_size(ctx.return_type()?.as_ref())?;
+    Ok(ReturnValue::Value(ctx.solver().bv_zero(bits)))
+}
+
+fn rust_alloc(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
     // fn __rust_alloc(size: usize, align: usize) -> *mut u8;
+    let (vm, info) = ctx.split();
     assert_eq!(info.arguments.len(), 2);
 
     let size_in_bytes = &info.arguments[0].0;
@@ -55,15 +121,21 @@ fn rust_alloc(vm: &mut VM<'_>, info: FnInfo) -> Result<ReturnValue> {
     Ok(ReturnValue::Value(addr))
 }
 
-fn rust_dealloc(_vm: &mut VM<'_>, _info: FnInfo) -> Result<ReturnValue> {
+fn rust_dealloc(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
     // fn __rust_dealloc(ptr: *mut u8, size: usize, align: usize);
+    let (vm, info) = ctx.split();
+    assert_eq!(info.arguments.len(), 3);
+
+    // The memory is not reclaimed, the allocation is only marked as freed.
+    let addr = get_u64_solution_from_operand(&vm.state, &info.arguments[0].0)?;
+    vm.state.mem.borrow_mut().free(addr);
 
-    // Currently, de-allocating is not supported.
     Ok(ReturnValue::Void)
 }
 
-fn rust_realloc(vm: &mut VM<'_>, info: FnInfo) -> Result<ReturnValue> {
+fn rust_realloc(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
     // fn __rust_realloc(ptr: *mut u8, old_size: usize, align: usize, new_size: usize) -> *mut u8;
+    let (vm, info) = ctx.split();
     assert_eq!(info.arguments.len(), 4);
 
     let addr = &info.arguments[0].0;
@@ -88,8 +160,9 @@ fn rust_realloc(vm: &mut VM<'_>, info: FnInfo) -> Result<ReturnValue> {
     Ok(ReturnValue::Value(new_addr))
 }
 
-fn rust_alloc_zeroed(vm: &mut VM<'_>, info: FnInfo) -> Result<ReturnValue> {
+fn rust_alloc_zeroed(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
     // fn __rust_alloc_zeroed(size: usize, align: usize) -> *mut u8;
+    let (vm, info) = ctx.split();
     assert_eq!(info.arguments.len(), 2);
 
     let size_in_bytes = &info.arguments[0].0;
@@ -0,0 +1,126 @@
+//! Context passed to hooks.
+//!
+//! Hooks only get access to the VM through a [HookCtx], which exposes the arguments of the call and
+//! the parts of the VM a hook needs. This keeps hooks independent of the internals of the [VM], so
+//! these can change without breaking user hooks.
+use llvm_ir::{Type, TypeRef};
+use std::cell::RefMut;
+
+use super::{call_return_type, Argument, FnInfo};
+use crate::{
+    common::SolutionVariable,
+    memory::Memory,
+    vm::{Result, State, VMError, VM},
+    Project, Solver, BV,
+};
+
+/// The call a hook is executing, see [Hook](super::Hook).
+pub struct HookCtx<'b, 'a> {
+    vm: &'b mut VM<'a>,
+
+    info: FnInfo,
+}
+
+impl<'b, 'a> HookCtx<'b, 'a> {
+    pub(crate) fn new(vm: &'b mut VM<'a>, info: FnInfo) -> Self {
+        Self { vm, info }
+    }
+
+    /// Returns the arguments of the call.
+    pub fn args(&self) -> &[Argument] {
+        &self.info.arguments
+    }
+
+    /// Returns the value of the argument at `index`.
+    pub fn arg(&self, index: usize) -> Result<BV> {
+        self.vm.state.get_var(&self.info.arguments[index].0)
+    }
+
+    /// Returns the type of the argument at `index`.
+    pub fn arg_type(&self, index: usize) -> TypeRef {
+        self.info.argument_types[index].clone()
+    }
+
+    /// Returns the number of elements and the size of each element in bits of the argument at
+    /// `index`.
+    ///
+    /// Vectors have one element for each lane, other types are a single element.
+    pub fn arg_lanes(&self, index: usize) -> Result<(u32, u32)> {
+        match self.info.argument_types[index].as_ref() {
+            Type::VectorType {
+                element_type,
+                num_elements,
+                scalable: false,
+            } => Ok((*num_elements as u32, self.bit_size(element_type)?)),
+            Type::VectorType { scalable: true, .. } => Err(VMError::UnsupportedInstruction(
+                "Scalable vector".to_owned(),
+            )),
+            ty => Ok((1, self.bit_size(ty)?)),
+        }
+    }
+
+    /// Returns the return type of the call.
+    pub fn return_type(&self) -> Result<TypeRef> {
+        call_return_type(&*self.vm)
+    }
+
+    /// Returns the attributes of the call.
+    pub fn info(&self) -> &FnInfo {
+        &self.info
+    }
+
+    /// Returns the solver for the current path.
+    pub fn solver(&self) -> &Solver {
+        &self.vm.solver
+    }
+
+    /// Returns the memory of the current path.
+    ///
+    /// The memory is borrowed until the returned value is dropped.
+    pub fn mem(&self) -> RefMut<'_, Memory> {
+        self.vm.state.mem.borrow_mut()
+    }
+
+    /// Returns the size of a pointer in bits.
+    pub fn ptr_size(&self) -> u32 {
+        self.vm.project.ptr_size
+    }
+
+    /// Returns the size of `ty` in bits.
+    pub fn bit_size(&self, ty: &Type) -> Result<u32> {
+        self.vm.project.bit_size(ty)
+    }
+
+    /// Returns the project that is executed.
+    pub fn project(&self) -> &'a Project {
+        self.vm.project
+    }
+
+    /// Resolve a symbolic value to a single concrete value, see [VM::with_concretization_policy].
+    pub fn concretize(&mut self, value: &BV) -> Result<u64> {
+        self.vm.concretize(value)
+    }
+
+    /// Add a symbol that is part of the inputs of the path, so it is included in the solutions.
+    pub fn add_symbol(&mut self, symbol: SolutionVariable) {
+        self.vm.add_symbol(symbol);
+    }
+
+    /// Fork the path on the 1-bit `condition`, and return the value it has on the current path.
+    ///
+    /// If the condition can be both `true` and `false` a path is saved where it is `false`. That
+    /// path executes the call again, so the hook is called once for each value of the condition.
+    pub fn fork(&mut self, condition: &BV) -> Result<bool> {
+        self.vm.fork_on_condition(condition)
+    }
+
+    /// Returns the state of the current path, used by the built-in hooks.
+    pub(crate) fn state(&self) -> &State<'a> {
+        &self.vm.state
+    }
+
+    /// Returns the VM and the call, used by the built-in hooks that need the VM internals.
+    pub(crate) fn split(&mut self) -> (&mut VM<'a>, &FnInfo) {
+        (&mut *self.vm, &self.info)
+    }
+}
@@ -6,24 +6,24 @@
 //! ## Standard C/C++ intrinsics
 //!
 //! - [ ] `llvm.abs.*`
-//! - [ ] `llvm.smax.*`
+//! - [x] `llvm.smax.*`
 //! - [ ] `llvm.smin.*`
 //! - [x] `llvm.umax.*`
 //! - [ ] `llvm.umin.*`
 //! - [x] `llvm.memcpy`
-//! - [ ] `llvm.memcpy.inline`
+//! - [x] `llvm.memcpy.inline`
 //! - [ ] `llvm.memmove`
 //! - [x] `llvm.memset`
 //! - [ ] `llvm.sqrt.*`
-//! - [ ] `llvm.powi.*`
+//! - [x] `llvm.powi.*`
 //! - [ ] `llvm.sin.*`
 //! - [ ] `llvm.cos.*`
-//! - [ ] `llvm.pow.*`
-//! - [ ] `llvm.exp.*`
-//! - [ ] `llvm.exp2.*`
-//! - [ ] `llvm.log.*`
-//! - [ ] `llvm.log10.*`
-//! - [ ] `llvm.log2.*`
+//! - [x] `llvm.pow.*`
+//! - [x] `llvm.exp.*`
+//! - [x] `llvm.exp2.*`
+//! - [x] `llvm.log.*`
+//! - [x] `llvm.log10.*`
+//! - [x] `llvm.log2.*`
 //! - [ ] `llvm.fma.*`
 //! - [ ] `llvm.fabs.*`
 //! - [ ] `llvm.minnum.*`
@@ -38,10 +38,25 @@
 //! - [ ] `llvm.nearbyint.*`
 //! - [ ] `llvm.round.*`
 //! - [ ] `llvm.roundeven.*`
-//! - [ ] `llvm.lround.*`
-//! - [ ] `llvm.llround.*`
-//! - [ ] `llvm.lrint.*`
-//! - [ ] `llvm.llrint.*`
+//! - [x] `llvm.lround.*`
+//! - [x] `llvm.llround.*`
+//! - [x] `llvm.lrint.*`
+//! - [x] `llvm.llrint.*`
+//! - [x] `llvm.fptosi.sat.*`
+//! - [x] `llvm.fptoui.sat.*`
+//!
+//! ## Constrained floating point intrinsics
+//!
+//! - [x] `llvm.experimental.constrained.fadd.*`
+//! - [x] `llvm.experimental.constrained.fsub.*`
+//! - [x] `llvm.experimental.constrained.fmul.*`
+//! - [x] `llvm.experimental.constrained.fdiv.*`
+//! - [x] `llvm.experimental.constrained.frem.*`
+//!
+//! ## Bit manipulation intrinsics
+//!
+//! - [x] `llvm.ctlz.*`
+//! - [x] `llvm.cttz.*`
 //!
 //! ## Arithmetic with overflow intrinsics
 //!
@@ -66,25 +81,50 @@
 //! - [x] `llvm.expect`
 //! - [ ] `llvm.expect.with.probability`
 //! - [x] `llvm.assume`
+//! - [x] `llvm.var.annotation`
+//! - [x] `llvm.ptr.annotation.*`
+//! - [x] `llvm.sideeffect`
+//! - [x] `llvm.donothing`
+//! - [x] `llvm.stacksave`
+//! - [x] `llvm.stackrestore`
+//! - [x] `llvm.threadlocal.address.*`
+//! - [x] `llvm.objectsize.*`
+//! - [x] `llvm.prefetch`
+//! - [x] `llvm.readcyclecounter`
+//! - [x] `llvm.readsteadycounter`
+//! - [x] `llvm.experimental.noalias.scope.decl`
+//!
+//! ## Exception handling intrinsics
+//!
+//! - [x] `llvm.localescape`
+//! - [ ] `llvm.localrecover`
+//!
+//! ## Variable argument handling intrinsics
+//!
+//! - [x] `llvm.va_start`
+//! - [x] `llvm.va_end`
+//! - [x] `llvm.va_copy`
 //!
 //! [1]: https://llvm.org/docs/LangRef.html#intrinsic-functions
-use llvm_ir::Type;
 use log::trace;
-use radix_trie::Trie;
+use radix_trie::{Trie, TrieCommon};
 use std::collections::HashMap;
 
 use crate::{
-    common::{binop, get_u64_solution_from_operand},
-    hooks::{FnInfo, Hook},
+    common::{
+        binop, float_binop, float_to_bv, float_to_int, float_to_int_saturating, get_concrete_float,
+        get_u64_solution_from_operand, layout_members, FloatOp, SolutionVariable,
+    },
+    hooks::{Hook, HookCtx},
     memory::BITS_IN_BYTE,
-    vm::{Result, ReturnValue, VM},
-    VMError, BV,
+    vm::{Result, ReturnValue},
+    Solutions, VMError, BV,
 };
 
 /// Check if the given name is an LLVM intrinsic.
 ///
 /// Currently it checks that the name starts with `llvm.` which seems like a good approximation.
-pub(super) fn is_intrinsic(name: &str) -> bool {
+pub(crate) fn is_intrinsic(name: &str) -> bool {
     name.starts_with("llvm.")
 }
 
@@ -96,7 +136,12 @@ pub(super) fn is_intrinsic(name: &str) -> bool {
 /// Internally fixed length name intrinsics use a `[std::collections::HashMap]` so all lookups are
 /// constant time. Variable intrinsic names use a `[radix_trie::Trie]` so lookups are linear time of
 /// the retrieved name.
-pub(super) struct Intrinsics {
+///
+/// The default hooks can be replaced or removed with [Intrinsics::replace] and
+/// [Intrinsics::remove]. This is done either on the [crate::Project] before creating a [VM], or
+/// for a single [VM] with [VM::intrinsics_mut].
+#[derive(Clone)]
+pub struct Intrinsics {
     /// Fixed length intrinsic values, e.g. `llvm.va_start`.
     fixed: HashMap<String, Hook>,
 
@@ -117,11 +162,49 @@ impl Intrinsics {
 
         // Add fixed intrinsics.
         s.add_fixed("llvm.assume", llvm_assume);
+        s.add_fixed("llvm.sideeffect", noop);
+        s.add_fixed("llvm.donothing", noop);
+        s.add_fixed("llvm.stacksave", llvm_stacksave);
+        s.add_fixed("llvm.stackrestore", llvm_stackrestore);
+        s.add_fixed("llvm.va_start", llvm_va_start);
+        s.add_fixed("llvm.va_end", noop);
+        s.add_fixed("llvm.va_copy", llvm_va_copy);
+        s.add_fixed("llvm.prefetch", noop);
+        s.add_fixed("llvm.readcyclecounter", llvm_readcyclecounter);
+        s.add_fixed("llvm.readsteadycounter", llvm_readcyclecounter);
 
         // Add variable intrinsics.
         s.add_variable("llvm.memcpy.", llvm_memcpy);
+        // The trie resolves to the longest matching prefix, so this takes precedence over the
+        // plain `llvm.memcpy.` prefix.
+        s.add_variable("llvm.memcpy.inline.", llvm_memcpy_inline);
         s.add_variable("llvm.memset.", llvm_memset);
+        s.add_variable("llvm.smax.", llvm_smax);
         s.add_variable("llvm.umax.", llvm_umax);
+        s.add_variable("llvm.powi.", llvm_powi);
+        s.add_variable("llvm.pow.", llvm_pow);
+        s.add_variable("llvm.exp.", llvm_exp);
+        s.add_variable("llvm.exp2.", llvm_exp2);
+        s.add_variable("llvm.log.", llvm_log);
+        s.add_variable("llvm.log10.", llvm_log10);
+        s.add_variable("llvm.log2.", llvm_log2);
+        s.add_variable("llvm.lround.", llvm_lround);
+        s.add_variable("llvm.llround.", llvm_lround);
+        s.add_variable("llvm.lrint.", llvm_lrint);
+        s.add_variable("llvm.llrint.", llvm_lrint);
+        s.add_variable("llvm.fptosi.sat.", llvm_fptosi_sat);
+        s.add_variable("llvm.fptoui.sat.", llvm_fptoui_sat);
+
+        // The rounding mode and exception behavior cannot be changed, so the metadata operands
+        // are ignored.
+        s.add_variable("llvm.experimental.constrained.fadd.", llvm_constrained_fadd);
+        s.add_variable("llvm.experimental.constrained.fsub.", llvm_constrained_fsub);
+        s.add_variable("llvm.experimental.constrained.fmul.", llvm_constrained_fmul);
+        s.add_variable("llvm.experimental.constrained.fdiv.", llvm_constrained_fdiv);
+        s.add_variable("llvm.experimental.constrained.frem.", llvm_constrained_frem);
+
+        s.add_variable("llvm.ctlz.", llvm_ctlz);
+        s.add_variable("llvm.cttz.", llvm_cttz);
 
         s.add_variable("llvm.sadd.with.overflow.", llvm_sadd_with_overflow);
         s.add_variable("llvm.uadd.with.overflow.", llvm_uadd_with_overflow);
@@ -135,10 +218,25 @@ impl Intrinsics {
 
         s.add_variable("llvm.expect.", llvm_expect);
 
+        s.add_variable("llvm.threadlocal.address.", llvm_threadlocal_address);
+        s.add_variable("llvm.objectsize.", llvm_objectsize);
+        // Prefetching is only a performance hint, newer versions are overloaded on the pointer.
+        s.add_variable("llvm.prefetch.", noop);
+
+        // Scope declarations are only used for alias analysis. Other experimental intrinsics are
+        // not registered, since they can have semantics that are not modeled.
+        s.add_fixed("llvm.experimental.noalias.scope.decl", noop);
+
+        // Annotations do not affect execution.
+        s.add_fixed("llvm.var.annotation", noop);
+        s.add_variable("llvm.ptr.annotation.", llvm_ptr_annotation);
+
+        // Escaped allocations are not tracked, so `llvm.localrecover` is not supported.
+        s.add_fixed("llvm.localescape", noop);
+
         // Temporary.
         s.add_variable("llvm.dbg", noop);
         s.add_variable("llvm.lifetime", noop);
-        s.add_variable("llvm.experimental", noop);
 
         s
     }
@@ -157,19 +255,69 @@ impl Intrinsics {
         self.variable.insert(name.into(), hook);
     }
 
+    /// Replace the hook for the intrinsic `name`, returning the previous hook if any.
+    ///
+    /// The name should be the same as the one the intrinsic is registered with, so for variable
+    /// length intrinsics such as `llvm.umax.*` the name should be `llvm.umax.`. If no intrinsic
+    /// with the name exists it is added, as a variable length intrinsic if the name ends with a `.`
+    /// and as a fixed length intrinsic otherwise.
+    pub fn replace(&mut self, name: impl Into<String>, hook: Hook) -> Option<Hook> {
+        let name = name.into();
+        if let Some(existing) = self.fixed.get_mut(&name) {
+            Some(std::mem::replace(existing, hook))
+        } else if self.variable.get(&name).is_some() || name.ends_with('.') {
+            self.variable.insert(name, hook)
+        } else {
+            self.fixed.insert(name, hook)
+        }
+    }
+
+    /// Remove the hook for the intrinsic `name`, returning the removed hook if any.
+    ///
+    /// As for [Intrinsics::replace] the name should be the one the intrinsic is registered with.
+    pub fn remove(&mut self, name: &str) -> Option<Hook> {
+        self.fixed
+            .remove(name)
+            .or_else(|| self.variable.remove(name))
+    }
+
     /// Returns a reference to the hook of the given name. If the hook cannot be found `None` is
     /// returned.
     ///
     /// It first checks the fixed length names, and if such a name cannot be found it checks the
     /// variable length names.
-    pub(super) fn get(&self, name: &str) -> Option<&Hook> {
-        self.fixed
-            .get(name)
-            .or_else(|| self.variable.get_ancestor_value(name))
+    pub fn get(&self, name: &str) -> Option<&Hook> {
+        self.fixed.get(name).or_else(|| self.get_variable(name))
+    }
+
+    /// Returns the variable length intrinsic for `name` if any.
+    ///
+    /// The closest ancestor in the trie is only used if it ends at a `.` boundary in `name`, so e.g.
+    /// `llvm.dbgfoo` does not resolve to the `llvm.dbg` entry.
+    fn get_variable(&self, name: &str) -> Option<&Hook> {
+        let prefix = self.variable.get_ancestor(name)?.key()?;
+
+        if is_dotted_prefix(prefix, name) {
+            self.variable.get(prefix)
+        } else {
+            trace!("intrinsic {name} matched {prefix} which is not a dotted prefix");
+            None
+        }
+    }
+}
+
+/// Check that `prefix` is a prefix of `name` that ends at a `.` boundary.
+///
+/// The prefix may either end with a `.` itself, or the remaining part of `name` must be empty or
+/// start with a `.`.
+fn is_dotted_prefix(prefix: &str, name: &str) -> bool {
+    match name.strip_prefix(prefix) {
+        Some(rest) => prefix.ends_with('.') || rest.is_empty() || rest.starts_with('.'),
+        None => false,
     }
 }
 
-pub fn noop(_vm: &mut VM<'_>, _f: FnInfo) -> Result<ReturnValue> {
+pub fn noop(_ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
     Ok(ReturnValue::Void)
 }
 
@@ -178,7 +326,7 @@ pub fn noop(_vm: &mut VM<'_>, _f: FnInfo) -> Result<ReturnValue> {
 // -------------------------------------------------------------------------------------------------
 
 /// Copy a block of memory from the source to the destination.
-pub fn llvm_memcpy(vm: &mut VM<'_>, f: FnInfo) -> Result<ReturnValue> {
+pub fn llvm_memcpy(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
     // Arguments:
     // 1. Pointer to destination.
     // 2. Pointer to source.
@@ -192,60 +340,94 @@ pub fn llvm_memcpy(vm: &mut VM<'_>, f: FnInfo) -> Result<ReturnValue> {
     //
     // TODO: What is a `well-defined` value?
     // TODO: Check the isvolatile and the details of volatile operations.
-    assert_eq!(f.arguments.len(), 4);
+    assert_eq!(ctx.args().len(), 4);
     trace!("llvm_memcpy");
 
-    let (dst, _) = &f.arguments[0];
-    let (src, _) = &f.arguments[1];
-    let (size, _) = &f.arguments[2];
+    let size = ctx.arg(2)?;
+    let size = ctx.concretize(&size)?;
+
+    memcpy(ctx, size)
+}
+
+pub fn llvm_memcpy_inline(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    // Arguments are the same as for `llvm.memcpy`, except that the number of bytes to copy must
+    // be a constant.
+    assert_eq!(ctx.args().len(), 4);
+    trace!("llvm_memcpy_inline");
+
+    let size = ctx.arg(2)?;
+    let size = match ctx.solver().get_solutions_for_bv(&size, 1)? {
+        Solutions::None => return Err(VMError::Unsat),
+        Solutions::Exactly(s) => s[0].as_u64().unwrap(),
+        Solutions::AtLeast(_) => return Err(VMError::MalformedInstruction),
+    };
 
-    let dst = vm.state.get_var(dst)?;
-    let src = vm.state.get_var(src)?;
+    memcpy(ctx, size)
+}
+
+/// Copy `size` bytes from the source to the destination in a `llvm.memcpy` style call.
+fn memcpy(ctx: &mut HookCtx<'_, '_>, size: u64) -> Result<ReturnValue> {
+    if size == 0 {
+        return Ok(ReturnValue::Void);
+    }
+
+    let dst = ctx.arg(0)?;
+    let src = ctx.arg(1)?;
 
-    let size = get_u64_solution_from_operand(&vm.state, size)?;
     let size = size as u32 * BITS_IN_BYTE;
 
-    let value = vm.state.mem.borrow_mut().read(&src, size)?;
-    vm.state.mem.borrow_mut().write(&dst, value)?;
+    let value = ctx.mem().read(&src, size)?;
+    ctx.mem().write(&dst, value)?;
 
     Ok(ReturnValue::Void)
 }
 
-pub fn llvm_memset(vm: &mut VM<'_>, f: FnInfo) -> Result<ReturnValue> {
+pub fn llvm_memset(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
     // Arguments:
     // 1. Pointer to address to fill.
     // 2. Byte to to fill with.
     // 3. Number of bytes to fill.
     // 4. Indicates volatile access.
-    assert_eq!(f.arguments.len(), 4);
+    assert_eq!(ctx.args().len(), 4);
     trace!("llvm_memset");
 
-    let (dst, _) = &f.arguments[0];
-    let (value, _) = &f.arguments[1];
-    let (size, _) = &f.arguments[2];
-
-    let dst = vm.state.get_var(dst)?;
-    let value = vm.state.get_var(value)?;
+    let dst = ctx.arg(0)?;
+    let value = ctx.arg(1)?;
     assert_eq!(value.len(), BITS_IN_BYTE);
 
-    let size = get_u64_solution_from_operand(&vm.state, size)?;
+    let size = ctx.arg(2)?;
+    let size = ctx.concretize(&size)?;
 
     for byte in 0..size {
-        let offset = vm.solver.bv_from_u64(byte, vm.project.ptr_size);
+        let offset = ctx.solver().bv_from_u64(byte, ctx.ptr_size());
         let addr = dst.add(&offset);
 
-        vm.state.mem.borrow_mut().write(&addr, value.clone())?;
+        ctx.mem().write(&addr, value.clone())?;
     }
 
     Ok(ReturnValue::Void)
 }
 
-pub fn llvm_umax(vm: &mut VM<'_>, f: FnInfo) -> Result<ReturnValue> {
-    assert_eq!(f.arguments.len(), 2);
-    let lhs = &f.arguments[0].0;
-    let rhs = &f.arguments[1].0;
+pub fn llvm_smax(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    assert_eq!(ctx.args().len(), 2);
+    let lhs = &ctx.args()[0].0;
+    let rhs = &ctx.args()[1].0;
+
+    // `binop` operates per element on vectors, so this handles both scalars and vectors.
+    let result = binop(ctx.state(), lhs, rhs, |lhs, rhs| {
+        let condition = lhs.sgt(rhs);
+        condition.ite(lhs, rhs)
+    })?;
+
+    Ok(ReturnValue::Value(result))
+}
+
+pub fn llvm_umax(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    assert_eq!(ctx.args().len(), 2);
+    let lhs = &ctx.args()[0].0;
+    let rhs = &ctx.args()[1].0;
 
-    let result = binop(&vm.state, lhs, rhs, |lhs, rhs| {
+    let result = binop(ctx.state(), lhs, rhs, |lhs, rhs| {
         let condition = lhs.ugt(rhs);
         condition.ite(lhs, rhs)
     })?;
@@ -253,6 +435,242 @@ pub fn llvm_umax(vm: &mut VM<'_>, f: FnInfo) -> Result<ReturnValue> {
     Ok(ReturnValue::Value(result))
 }
 
+// -------------------------------------------------------------------------------------------------
+// Floating point intrinsics
+//
+// Floating point is not supported by the solver, so these only handle concrete `float` and `double`
+// arguments. The result is calculated in Rust and returned as a bit pattern.
+//
+// Transcendental functions have no exact representation in the solver either, so for symbolic
+// arguments they return an unconstrained value. These can be overridden with
+// [Intrinsics::replace] if a better approximation is needed.
+// -------------------------------------------------------------------------------------------------
+
+/// Returns `value` as a floating point value of the call's return type.
+fn float_result(ctx: &mut HookCtx<'_, '_>, value: f64) -> Result<ReturnValue> {
+    let ty = ctx.return_type()?;
+    let result = float_to_bv(ctx.state(), value, ty.as_ref())?;
+    Ok(ReturnValue::Value(result))
+}
+
+/// Returns an unconstrained value of the call's return type.
+fn unconstrained_result(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    let bits = ctx.bit_size(ctx.return_type()?.as_ref())?;
+    Ok(ReturnValue::Value(ctx.solver().bv_fresh(bits, "havoc")))
+}
+
+/// Applies `op` to a concrete argument, or returns an unconstrained value if it is symbolic.
+fn float_unary(ctx: &mut HookCtx<'_, '_>, op: fn(f64) -> f64) -> Result<ReturnValue> {
+    assert_eq!(ctx.args().len(), 1);
+    match get_concrete_float(ctx.state(), &ctx.args()[0].0)? {
+        Some(value) => float_result(ctx, op(value)),
+        None => unconstrained_result(ctx),
+    }
+}
+
+/// Raises the first argument to the power of the second argument, both floating point.
+pub fn llvm_pow(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    assert_eq!(ctx.args().len(), 2);
+    let base = get_concrete_float(ctx.state(), &ctx.args()[0].0)?;
+    let exponent = get_concrete_float(ctx.state(), &ctx.args()[1].0)?;
+
+    match (base, exponent) {
+        (Some(base), Some(exponent)) => float_result(ctx, base.powf(exponent)),
+        _ => unconstrained_result(ctx),
+    }
+}
+
+/// Raises the first argument to the power of the second argument, which is a signed integer.
+pub fn llvm_powi(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    assert_eq!(ctx.args().len(), 2);
+    let base = get_concrete_float(ctx.state(), &ctx.args()[0].0)?;
+    let exponent = ctx.arg(1)?;
+    let exponent = exponent
+        .to_string_binary()
+        .map(|bits| u32::from_str_radix(&bits, 2).unwrap() as i32);
+
+    match (base, exponent) {
+        (Some(base), Some(exponent)) => float_result(ctx, base.powi(exponent)),
+        _ => unconstrained_result(ctx),
+    }
+}
+
+pub fn llvm_exp(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    float_unary(ctx, f64::exp)
+}
+
+pub fn llvm_exp2(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    float_unary(ctx, f64::exp2)
+}
+
+pub fn llvm_log(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    float_unary(ctx, f64::ln)
+}
+
+pub fn llvm_log10(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    float_unary(ctx, f64::log10)
+}
+
+pub fn llvm_log2(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    float_unary(ctx, f64::log2)
+}
+
+/// Round to the nearest integer, with ties rounded to the even integer.
+fn round_ties_even(value: f64) -> f64 {
+    let rounded = value.round();
+    if (value - value.trunc()).abs() == 0.5 {
+        2.0 * (value / 2.0).round()
+    } else {
+        rounded
+    }
+}
+
+/// Convert a rounded floating point value to a signed integer of the call's return type.
+///
+/// If the value does not fit in the return type the result is unspecified, this is represented by
+/// an unconstrained value.
+fn float_round_to_int(ctx: &mut HookCtx<'_, '_>, round: fn(f64) -> f64) -> Result<ReturnValue> {
+    assert_eq!(ctx.args().len(), 1);
+    let value = get_concrete_float(ctx.state(), &ctx.args()[0].0)?
+        .ok_or_else(|| VMError::UnsupportedInstruction("Symbolic floating point".to_owned()))?;
+
+    let bits = ctx.bit_size(ctx.return_type()?.as_ref())?;
+    let result = float_to_int(ctx.state(), round(value), bits, true)
+        .unwrap_or_else(|| ctx.solver().bv_fresh(bits, "havoc"));
+
+    Ok(ReturnValue::Value(result))
+}
+
+/// Rounds to the nearest integer, with ties rounded away from zero.
+pub fn llvm_lround(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    float_round_to_int(ctx, f64::round)
+}
+
+/// Rounds to the nearest integer using the current rounding mode.
+///
+/// The rounding mode cannot be changed, so it is always round to nearest with ties to even.
+pub fn llvm_lrint(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    float_round_to_int(ctx, round_ties_even)
+}
+
+/// Saturating conversion from floating point to integer, used by Rust's `as` casts.
+fn float_to_int_sat(ctx: &mut HookCtx<'_, '_>, signed: bool) -> Result<ReturnValue> {
+    assert_eq!(ctx.args().len(), 1);
+    let value = get_concrete_float(ctx.state(), &ctx.args()[0].0)?
+        .ok_or_else(|| VMError::UnsupportedInstruction("Symbolic floating point".to_owned()))?;
+
+    let bits = ctx.bit_size(ctx.return_type()?.as_ref())?;
+    let result = float_to_int_saturating(ctx.state(), value, bits, signed);
+    Ok(ReturnValue::Value(result))
+}
+
+pub fn llvm_fptosi_sat(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    float_to_int_sat(ctx, true)
+}
+
+pub fn llvm_fptoui_sat(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    float_to_int_sat(ctx, false)
+}
+
+// -------------------------------------------------------------------------------------------------
+// Constrained floating point intrinsics
+//
+// These are used instead of the regular floating point instructions when the rounding mode or
+// floating point exceptions matter, e.g. with `-ffp-model=strict`. The last two operands are
+// metadata for the rounding mode and exception behavior. Only the default environment is
+// supported, so they are ignored and the operations are calculated as for concrete values.
+// -------------------------------------------------------------------------------------------------
+
+/// Applies `op` to the two floating point arguments, the same as the regular instruction.
+fn float_binary(ctx: &mut HookCtx<'_, '_>, op: FloatOp) -> Result<ReturnValue> {
+    assert_eq!(ctx.args().len(), 4);
+    let ty = ctx.return_type()?;
+    let (lhs, rhs) = (&ctx.args()[0].0, &ctx.args()[1].0);
+    let result = float_binop(ctx.state(), lhs, rhs, op, ty.as_ref())?;
+    Ok(ReturnValue::Value(result))
+}
+
+pub fn llvm_constrained_fadd(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    float_binary(ctx, FloatOp::Add)
+}
+
+pub fn llvm_constrained_fsub(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    float_binary(ctx, FloatOp::Sub)
+}
+
+pub fn llvm_constrained_fmul(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    float_binary(ctx, FloatOp::Mul)
+}
+
+pub fn llvm_constrained_fdiv(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    float_binary(ctx, FloatOp::Div)
+}
+
+pub fn llvm_constrained_frem(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    float_binary(ctx, FloatOp::Rem)
+}
+
+// -------------------------------------------------------------------------------------------------
+// Bit manipulation intrinsics
+// -------------------------------------------------------------------------------------------------
+
+/// Count the zeros in each lane of the first argument, from the most significant bit if `leading`
+/// is set and from the least significant bit otherwise.
+///
+/// The second argument `is_zero_poison` selects if a lane that is zero is poison. The path then
+/// forks on whether any lane can be zero, and the path where one is ends with
+/// [VMError::PoisonValue]. Otherwise the count of a zero lane is the bit width.
+fn count_zeros(ctx: &mut HookCtx<'_, '_>, leading: bool) -> Result<ReturnValue> {
+    assert_eq!(ctx.args().len(), 2);
+    let (lanes, bits) = ctx.arg_lanes(0)?;
+    let value = ctx.arg(0)?;
+    let is_zero_poison = get_u64_solution_from_operand(ctx.state(), &ctx.args()[1].0)? != 0;
+
+    let lanes: Vec<_> = (0..lanes)
+        .map(|i| value.slice(i * bits, (i + 1) * bits - 1))
+        .collect();
+
+    if is_zero_poison {
+        let any_zero = lanes
+            .iter()
+            .map(|lane| lane.redor().not())
+            .reduce(|acc, is_zero| acc.or(&is_zero))
+            .ok_or(VMError::MalformedInstruction)?;
+
+        if !ctx.fork(&any_zero.not())? {
+            return Err(VMError::PoisonValue);
+        }
+    }
+
+    // Start with the count for zero, and check the bits so the one closest to the end that is
+    // counted from is checked last, i.e. it takes precedence.
+    let counts: Vec<_> = lanes
+        .iter()
+        .map(|lane| {
+            let mut count = ctx.solver().bv_from_u64(bits as u64, bits);
+            for i in 0..bits {
+                let bit = if leading { i } else { bits - 1 - i };
+                let zeros = if leading { bits - 1 - bit } else { bit };
+                let zeros = ctx.solver().bv_from_u64(zeros as u64, bits);
+                count = lane.slice(bit, bit).ite(&zeros, &count);
+            }
+            count
+        })
+        .collect();
+
+    Ok(ReturnValue::Value(BV::concat_all(&counts)))
+}
+
+/// Count the leading zeros, i.e. the zeros before the most significant set bit.
+pub fn llvm_ctlz(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    count_zeros(ctx, true)
+}
+
+/// Count the trailing zeros, i.e. the zeros after the least significant set bit.
+pub fn llvm_cttz(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    count_zeros(ctx, false)
+}
+
 // -------------------------------------------------------------------------------------------------
 // Arithmetic with overflow intrinsics
 // -------------------------------------------------------------------------------------------------
@@ -268,16 +686,13 @@ enum BinaryOpOverflow {
 }
 
 /// Binary operations that indicate whether an overflow occurred or not.
-fn binary_op_overflow(vm: &mut VM<'_>, f: FnInfo, op: BinaryOpOverflow) -> Result<ReturnValue> {
-    assert_eq!(f.arguments.len(), 2);
-
-    let (lhs, _) = f.arguments.get(0).unwrap();
-    let (rhs, _) = f.arguments.get(1).unwrap();
+fn binary_op_overflow(ctx: &mut HookCtx<'_, '_>, op: BinaryOpOverflow) -> Result<ReturnValue> {
+    assert_eq!(ctx.args().len(), 2);
 
-    let lhs_ty = vm.state.type_of(lhs);
-    let rhs_ty = vm.state.type_of(rhs);
-    let lhs = vm.state.get_var(lhs)?;
-    let rhs = vm.state.get_var(rhs)?;
+    let (lanes, bits) = ctx.arg_lanes(0)?;
+    assert_eq!(ctx.arg_lanes(1)?, (lanes, bits));
+    let lhs = ctx.arg(0)?;
+    let rhs = ctx.arg(1)?;
 
     let operation = |lhs: BV, rhs: BV| {
         let (result, overflow) = match op {
@@ -298,103 +713,64 @@ fn binary_op_overflow(vm: &mut VM<'_>, f: FnInfo, op: BinaryOpOverflow) -> Resul
     //
     // The result type is a struct so {result, overflow} and for vectors this means {<iX res>, <i1>}
     // so the results and overflows have to be appended separately until the final return. Which the
-    // regular `binop` does not handle.
-    use Type::*;
-    let result = match (lhs_ty.as_ref(), rhs_ty.as_ref()) {
-        // For integers just perform the operation.
-        (IntegerType { .. }, IntegerType { .. }) => {
-            let (result, overflow) = operation(lhs, rhs);
-            Ok(overflow.concat(&result))
-        }
-
-        // For vectors each operation has to be done independently, and the return should be in
-        // the format of {results, overflows}.
-        (
-            VectorType {
-                element_type: lhs_inner_ty,
-                num_elements: n,
-                scalable: false,
-            },
-            VectorType {
-                element_type: rhs_inner_ty,
-                num_elements: m,
-                scalable: false,
-            },
-        ) => {
-            assert_eq!(lhs_inner_ty, rhs_inner_ty);
-            assert_eq!(
-                vm.state.project.bit_size(lhs_inner_ty),
-                vm.state.project.bit_size(rhs_inner_ty)
-            );
-            assert_eq!(*n, *m);
-
-            let bits = vm.state.project.bit_size(lhs_inner_ty)?;
-            let num_elements = *n as u32;
-
-            // Perform the operation per element and concatenate the result.
-            let (results, overflows) = (0..num_elements)
-                .map(|i| {
-                    let low = i * bits;
-                    let high = (i + 1) * bits - 1;
-                    let lhs = lhs.slice(low, high);
-                    let rhs = rhs.slice(low, high);
-                    operation(lhs, rhs)
-                })
-                .reduce(|(res_acc, overflow_acc), (res, overflow)| {
-                    (res.concat(&res_acc), overflow.concat(&overflow_acc))
-                })
-                .ok_or(VMError::MalformedInstruction)?;
-
-            trace!("results: {results:?}, overflows: {overflows:?}");
-            Ok(overflows.concat(&results))
-        }
-
-        // TODO: Check out scalable vectors.
-        (VectorType { .. }, VectorType { .. }) => {
-            todo!()
-        }
-
-        // These types should not appear in a binary operation.
-        _ => Err(VMError::MalformedInstruction),
-    }?;
-
-    Ok(ReturnValue::Value(result))
+    // regular `binop` does not handle. Scalars are treated as a vector with a single lane.
+    let (results, overflows) = (0..lanes)
+        .map(|i| {
+            let low = i * bits;
+            let high = (i + 1) * bits - 1;
+            operation(lhs.slice(low, high), rhs.slice(low, high))
+        })
+        .reduce(|(res_acc, overflow_acc), (res, overflow)| {
+            (res.concat(&res_acc), overflow.concat(&overflow_acc))
+        })
+        .ok_or(VMError::MalformedInstruction)?;
+
+    trace!("results: {results:?}, overflows: {overflows:?}");
+    let ty = ctx.return_type()?;
+    let value = layout_members(
+        &ty,
+        [Some(results), Some(overflows)],
+        ctx.solver(),
+        ctx.project(),
+    )?
+    .ok_or(VMError::MalformedInstruction)?;
+    Ok(ReturnValue::Value(value))
 }
 
 /// Signed addition on any bit width, performs a signed addition and indicates whether an overflow
 /// occurred.
-pub fn llvm_sadd_with_overflow(vm: &mut VM<'_>, f: FnInfo) -> Result<ReturnValue> {
-    binary_op_overflow(vm, f, BinaryOpOverflow::SAdd)
+pub fn llvm_sadd_with_overflow(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    binary_op_overflow(ctx, BinaryOpOverflow::SAdd)
 }
 
 /// Unsigned addition on any bit width, performs an unsigned addition and indicates whether an
 /// overflow occurred.
-pub fn llvm_uadd_with_overflow(vm: &mut VM<'_>, f: FnInfo) -> Result<ReturnValue> {
-    binary_op_overflow(vm, f, BinaryOpOverflow::UAdd)
+pub fn llvm_uadd_with_overflow(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    binary_op_overflow(ctx, BinaryOpOverflow::UAdd)
 }
 
 /// Signed subtraction on any bit width, performs a signed subtraction and indicates whether an
 /// overflow occurred.
-pub fn llvm_ssub_with_overflow(vm: &mut VM<'_>, f: FnInfo) -> Result<ReturnValue> {
-    binary_op_overflow(vm, f, BinaryOpOverflow::SSub)
+pub fn llvm_ssub_with_overflow(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    binary_op_overflow(ctx, BinaryOpOverflow::SSub)
 }
 
 /// Unsigned subtraction on any bit width, performs an unsigned subtraction and indicates whether an
 /// overflow occurred.
-pub fn llvm_usub_with_overflow(vm: &mut VM<'_>, f: FnInfo) -> Result<ReturnValue> {
-    binary_op_overflow(vm, f, BinaryOpOverflow::USub)
+pub fn llvm_usub_with_overflow(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    binary_op_overflow(ctx, BinaryOpOverflow::USub)
 }
 
 /// Signed multiplication on any bit width, performs a signed multiplication and indicates whether
 /// an overflow occurred.
-pub fn llvm_smul_with_overflow(vm: &mut VM<'_>, f: FnInfo) -> Result<ReturnValue> {
-    binary_op_overflow(vm, f, BinaryOpOverflow::SMul)
+pub fn llvm_smul_with_overflow(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    binary_op_overflow(ctx, BinaryOpOverflow::SMul)
 }
 
 /// Unsigned multiplication on any bit width, performs an unsigned multiplication and indicates
 /// whether an overflow occurred.
-pub fn llvm_umul_with_overflow(vm: &mut VM<'_>, f: FnInfo) -> Result<ReturnValue> {
-    binary_op_overflow(vm, f, BinaryOpOverflow::UMul)
+pub fn llvm_umul_with_overflow(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    binary_op_overflow(ctx, BinaryOpOverflow::UMul)
 }
 
 // -------------------------------------------------------------------------------------------------
@@ -406,58 +782,226 @@ enum BinaryOpSaturate {
     UAdd,
 }
 
-fn binary_op_saturate(vm: &mut VM<'_>, f: FnInfo, op: BinaryOpSaturate) -> Result<ReturnValue> {
-    assert_eq!(f.arguments.len(), 2);
-
-    let (lhs, _) = f.arguments.get(0).unwrap();
-    let (rhs, _) = f.arguments.get(1).unwrap();
-
-    let result = binop(&vm.state, lhs, rhs, |lhs, rhs| match op {
-        BinaryOpSaturate::UAdd => lhs.uadds(&rhs),
-        BinaryOpSaturate::SAdd => lhs.sadds(&rhs),
-    })?;
+fn binary_op_saturate(ctx: &mut HookCtx<'_, '_>, op: BinaryOpSaturate) -> Result<ReturnValue> {
+    assert_eq!(ctx.args().len(), 2);
+
+    let (lanes, bits) = ctx.arg_lanes(0)?;
+    assert_eq!(ctx.arg_lanes(1)?, (lanes, bits));
+    let lhs = ctx.arg(0)?;
+    let rhs = ctx.arg(1)?;
+
+    // Saturate each lane on its own, the first lane is in the lowest bits.
+    let result = (0..lanes)
+        .map(|i| {
+            let low = i * bits;
+            let high = (i + 1) * bits - 1;
+            let (lhs, rhs) = (lhs.slice(low, high), rhs.slice(low, high));
+            match op {
+                BinaryOpSaturate::UAdd => lhs.uadds(&rhs),
+                BinaryOpSaturate::SAdd => lhs.sadds(&rhs),
+            }
+        })
+        .reduce(|acc, result| result.concat(&acc))
+        .ok_or(VMError::MalformedInstruction)?;
 
     Ok(ReturnValue::Value(result))
 }
 
-pub fn llvm_uadd_sat(vm: &mut VM<'_>, f: FnInfo) -> Result<ReturnValue> {
-    binary_op_saturate(vm, f, BinaryOpSaturate::UAdd)
+pub fn llvm_uadd_sat(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    binary_op_saturate(ctx, BinaryOpSaturate::UAdd)
+}
+pub fn llvm_sadd_sat(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    binary_op_saturate(ctx, BinaryOpSaturate::SAdd)
+}
+
+// -------------------------------------------------------------------------------------------------
+// Variable argument handling intrinsics
+// -------------------------------------------------------------------------------------------------
+
+/// Initializes the `va_list` passed as the argument to point to the variadic arguments.
+pub fn llvm_va_start(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    assert_eq!(ctx.args().len(), 1);
+    let va_list = ctx.arg(0)?;
+    let (vm, _) = ctx.split();
+    vm.va_start(&va_list)?;
+
+    Ok(ReturnValue::Void)
 }
-pub fn llvm_sadd_sat(vm: &mut VM<'_>, f: FnInfo) -> Result<ReturnValue> {
-    binary_op_saturate(vm, f, BinaryOpSaturate::SAdd)
+
+/// Copies the `va_list` in the second argument to the first.
+pub fn llvm_va_copy(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    assert_eq!(ctx.args().len(), 2);
+    let dst = ctx.arg(0)?;
+    let src = ctx.arg(1)?;
+    let (vm, _) = ctx.split();
+    vm.va_copy(&dst, &src)?;
+
+    Ok(ReturnValue::Void)
 }
 
 // -------------------------------------------------------------------------------------------------
 // General intrinsics
 // -------------------------------------------------------------------------------------------------
 
-pub fn llvm_expect(vm: &mut VM<'_>, f: FnInfo) -> Result<ReturnValue> {
-    assert_eq!(f.arguments.len(), 2);
-    let (a0, _) = f.arguments.get(0).unwrap();
-    let val = vm.state.get_var(a0).unwrap();
+pub fn llvm_expect(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    assert_eq!(ctx.args().len(), 2);
+    let val = ctx.arg(0).unwrap();
 
     Ok(ReturnValue::Value(val))
 }
 
-pub fn llvm_assume(vm: &mut VM<'_>, info: FnInfo) -> Result<ReturnValue> {
-    assert_eq!(info.arguments.len(), 1);
+/// Annotates a pointer, the pointer itself is returned unchanged.
+pub fn llvm_ptr_annotation(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    assert_eq!(ctx.args().len(), 5);
+    let ptr = ctx.arg(0)?;
+
+    Ok(ReturnValue::Value(ptr))
+}
+
+/// Saves the stack pointer, used around variable sized `alloca`s.
+///
+/// The returned pointer is a marker for the stack allocations made so far in the function, see
+/// [State::stack_save](crate::vm::State::stack_save).
+pub fn llvm_stacksave(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    assert_eq!(ctx.args().len(), 0);
+    let marker = ctx.state().stack_save();
+    let ptr = ctx.solver().bv_from_u64(marker, ctx.ptr_size());
+
+    Ok(ReturnValue::Value(ptr))
+}
+
+/// Restores the stack pointer saved by `llvm.stacksave`.
+///
+/// The stack allocations made after the pointer was saved are freed, so later accesses to them
+/// are errors.
+pub fn llvm_stackrestore(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    assert_eq!(ctx.args().len(), 1);
+    let marker = ctx.arg(0)?;
+    let marker = ctx.concretize(&marker)?;
+
+    let (vm, _) = ctx.split();
+    vm.state.stack_restore(marker);
+
+    Ok(ReturnValue::Void)
+}
+
+/// Returns the address of a thread local global for the current thread.
+///
+/// Execution is single-threaded, so thread locals are ordinary globals and the address of the
+/// global itself is returned.
+pub fn llvm_threadlocal_address(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    assert_eq!(ctx.args().len(), 1);
+    let addr = ctx.arg(0)?;
+
+    Ok(ReturnValue::Value(addr))
+}
+
+/// Returns the number of bytes from the pointer to the end of its allocation.
+///
+/// The pointer may be symbolic, the size is then known if all addresses it can take are in the
+/// same allocation. The second argument `min` selects if the smallest or largest possible size is
+/// returned. When the size is unknown `0` is returned if `min` is set, and `-1` otherwise. The
+/// third argument `nullunknown` selects if a null pointer has size `0` or an unknown size.
+pub fn llvm_objectsize(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    assert_eq!(ctx.args().len(), 4);
+    let ptr = ctx.arg(0)?;
+    let min = get_u64_solution_from_operand(ctx.state(), &ctx.args()[1].0)? != 0;
+    let null_unknown = get_u64_solution_from_operand(ctx.state(), &ctx.args()[2].0)? != 0;
+
+    let bits = ctx.bit_size(ctx.return_type()?.as_ref())?;
+    let unknown = if min {
+        ctx.solver().bv_zero(bits)
+    } else {
+        ctx.solver().bv_from_u64(0, bits).not()
+    };
+
+    let lowest = ctx.solver().get_solution_minimum(&ptr)?;
+    let highest = ctx.solver().get_solution_maximum(&ptr)?;
+    if highest == 0 {
+        let size = if null_unknown {
+            unknown
+        } else {
+            ctx.solver().bv_zero(bits)
+        };
+        return Ok(ReturnValue::Value(size));
+    }
+
+    let memory = ctx.mem();
+    let size = match (
+        memory.allocation_containing(lowest),
+        memory.allocation_containing(highest),
+    ) {
+        (Some(first), Some(last)) if first == last => {
+            let end = first.addr + first.bytes;
+            let remaining = if min { end - highest } else { end - lowest };
+            ctx.solver().bv_from_u64(remaining, bits)
+        }
+        _ => unknown,
+    };
 
-    let (condition, _) = &info.arguments[0];
-    let condition = vm.state.get_var(condition)?;
-    vm.state.solver.assert(&condition);
+    Ok(ReturnValue::Value(size))
+}
+
+/// Reads a cycle or time counter.
+///
+/// The value depends on timing which is not modeled, so a new unconstrained symbol is returned
+/// for each read. This keeps paths that depend on timing feasible.
+pub fn llvm_readcyclecounter(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    assert_eq!(ctx.args().len(), 0);
+    let ty = ctx.return_type()?;
+    let bits = ctx.bit_size(ty.as_ref())?;
+
+    let name = ctx.solver().fresh_name("cycle_counter");
+    let value = ctx.solver().bv(bits, &name);
+    ctx.add_symbol(SolutionVariable {
+        name,
+        value: value.clone(),
+        ty: Some(ty),
+    });
+
+    Ok(ReturnValue::Value(value))
+}
+
+pub fn llvm_assume(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    assert_eq!(ctx.args().len(), 1);
+
+    let condition = ctx.arg(0)?;
+    ctx.solver().assert(&condition);
 
     Ok(ReturnValue::Void)
 }
 
 #[cfg(test)]
 mod tests {
-    use crate::{Project, ReturnValue, Solutions, VMError, VM};
+    use super::{llvm_assume, llvm_memcpy, llvm_threadlocal_address, llvm_umax, noop, Intrinsics};
+    use crate::{
+        common::binop,
+        hooks::{Hook, HookCtx},
+        vm::{ConcretizationPolicy, Result},
+        Project, ReturnValue, Solutions, VMError, VM,
+    };
+
+    /// Check that `name` resolves to `expected` in the default intrinsics.
+    fn resolves_to(name: &str, expected: Hook) -> bool {
+        let intrinsics = Intrinsics::new_with_defaults();
+        intrinsics
+            .get(name)
+            .map(|hook| *hook as usize == expected as usize)
+            .unwrap_or(false)
+    }
 
     fn run(fn_name: &str) -> Vec<Result<Option<i64>, VMError>> {
         let path = format!("./tests/unit_tests/intrinsics.bc");
         let project = Project::from_path(&path).expect("Failed to created proejct");
-        let mut vm = VM::new(fn_name, &project).expect("Failed to create VM");
+        run_project(&project, fn_name)
+    }
+
+    fn run_project(project: &Project, fn_name: &str) -> Vec<Result<Option<i64>, VMError>> {
+        let vm = VM::new(fn_name, project).expect("Failed to create VM");
+        run_vm(vm)
+    }
 
+    fn run_vm(mut vm: VM<'_>) -> Vec<Result<Option<i64>, VMError>> {
         let mut path_results = Vec::new();
         while let Some(path_result) = vm.run() {
             let path_result = match path_result {
@@ -481,6 +1025,49 @@ mod tests {
         path_results
     }
 
+    #[test]
+    fn test_lookup_sideeffect_donothing() {
+        assert!(resolves_to("llvm.sideeffect", noop));
+        assert!(resolves_to("llvm.donothing", noop));
+    }
+
+    #[test]
+    fn test_lookup_not_shadowed() {
+        assert!(resolves_to("llvm.assume", llvm_assume));
+        assert!(resolves_to("llvm.memcpy.p0i8.p0i8.i64", llvm_memcpy));
+        assert!(resolves_to("llvm.umax.i32", llvm_umax));
+        assert!(resolves_to(
+            "llvm.threadlocal.address.p0",
+            llvm_threadlocal_address
+        ));
+        assert!(!resolves_to("llvm.memcpy.p0i8.p0i8.i64", noop));
+        assert!(!resolves_to("llvm.umax.i32", noop));
+        assert!(!resolves_to("llvm.experimental.constrained.fadd.f64", noop));
+        assert!(resolves_to("llvm.experimental.noalias.scope.decl", noop));
+    }
+
+    #[test]
+    fn test_lookup_dotted_prefix() {
+        let intrinsics = Intrinsics::new_with_defaults();
+        assert!(intrinsics.get("llvm.dbg.declare").is_some());
+        assert!(intrinsics.get("llvm.lifetime.start.p0i8").is_some());
+        assert!(intrinsics.get("llvm.memcpy.p0i8.p0i8.i32").is_some());
+    }
+
+    #[test]
+    fn test_lookup_adversarial_names() {
+        let intrinsics = Intrinsics::new_with_defaults();
+        assert!(intrinsics.get("llvm.dbgfoo").is_none());
+        assert!(intrinsics.get("llvm.lifetimes.start").is_none());
+        assert!(intrinsics.get("llvm.experimentalfoo.bar").is_none());
+        assert!(intrinsics.get("llvm.experimental.guard").is_none());
+        assert!(intrinsics.get("llvm.memcpyfoo").is_none());
+        assert!(intrinsics.get("llvm.memcpy").is_none());
+        assert!(intrinsics.get("llvm.umaxx.i32").is_none());
+        assert!(intrinsics.get("llvm.assume.i1").is_none());
+        assert!(intrinsics.get("llvm.").is_none());
+    }
+
     #[test]
     fn test_memcpy() {
         let res = run("test_memcpy");
@@ -488,6 +1075,50 @@ mod tests {
         assert_eq!(res[0], Ok(Some(0x6543fe671234abcd)));
     }
 
+    #[test]
+    fn test_lookup_memcpy_inline() {
+        assert!(resolves_to(
+            "llvm.memcpy.inline.p0i8.p0i8.i32",
+            llvm_memcpy_inline
+        ));
+        assert!(resolves_to("llvm.memcpy.p0i8.p0i8.i32", llvm_memcpy));
+        assert!(!resolves_to(
+            "llvm.memcpy.inline.p0i8.p0i8.i32",
+            llvm_memcpy
+        ));
+    }
+
+    #[test]
+    fn test_memcpy_inline() {
+        let res = run("test_memcpy_inline");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(0x6543fe671234abcd)));
+    }
+
+    #[test]
+    fn test_memcpy_symbolic_size_fork() {
+        let project = Project::from_path("./tests/unit_tests/intrinsics.bc").unwrap();
+        let vm = VM::new("test_memcpy_symbolic_size", &project)
+            .unwrap()
+            .with_concretization_policy(ConcretizationPolicy::Fork(8));
+
+        let mut res = run_vm(vm);
+        assert_eq!(res.len(), 3);
+        res.sort_by_key(|r| r.clone().ok().flatten());
+        assert_eq!(res, vec![Ok(Some(0)), Ok(Some(0xab)), Ok(Some(0xcdab))]);
+    }
+
+    #[test]
+    fn test_memcpy_symbolic_size_max() {
+        let project = Project::from_path("./tests/unit_tests/intrinsics.bc").unwrap();
+        let vm = VM::new("test_memcpy_symbolic_size", &project)
+            .unwrap()
+            .with_concretization_policy(ConcretizationPolicy::ConcretizeMax);
+
+        let res = run_vm(vm);
+        assert_eq!(res, vec![Ok(Some(0xcdab))]);
+    }
+
     #[test]
     fn test_memset() {
         let res = run("test_memset");
@@ -502,6 +1133,112 @@ mod tests {
         assert_eq!(res[0], Ok(Some(0xbcef)));
     }
 
+    /// Custom hook used to replace `llvm.umax.*`, returns the minimum instead.
+    fn custom_umax(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+        let lhs = &ctx.args()[0].0;
+        let rhs = &ctx.args()[1].0;
+
+        let result = binop(ctx.state(), lhs, rhs, |lhs, rhs| lhs.ult(rhs).ite(lhs, rhs))?;
+        Ok(ReturnValue::Value(result))
+    }
+
+    #[test]
+    fn test_umax_replaced() {
+        let path = "./tests/unit_tests/intrinsics.bc";
+        let mut project = Project::from_path(path).expect("Failed to created proejct");
+        let previous = project
+            .hooks_mut()
+            .intrinsics_mut()
+            .replace("llvm.umax.", custom_umax);
+        assert!(previous.is_some());
+
+        let res = run_project(&project, "test_umax");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(0xabcd)));
+    }
+
+    /// Custom hook used to replace `llvm.umax.*`, returns if the first argument is the largest.
+    fn forking_umax(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+        let lhs = ctx.arg(0)?;
+        let rhs = ctx.arg(1)?;
+        let bits = ctx.bit_size(ctx.return_type()?.as_ref())?;
+
+        let greater = ctx.fork(&lhs.ugt(&rhs))?;
+        let result = ctx.solver().bv_from_u64(greater as u64, bits);
+        Ok(ReturnValue::Value(result))
+    }
+
+    #[test]
+    fn test_umax_replaced_forking() {
+        let path = "./tests/unit_tests/intrinsics.bc";
+        let mut project = Project::from_path(path).expect("Failed to created proejct");
+        project
+            .hooks_mut()
+            .intrinsics_mut()
+            .replace("llvm.umax.", forking_umax);
+
+        // The hook is called again on the saved path, where the argument is not the largest.
+        let res = run_project(&project, "test_umax_symbolic");
+        assert_eq!(res, vec![Ok(Some(1)), Ok(Some(0))]);
+    }
+
+    /// Custom hook used to replace `llvm.umax.*`, returns the number of lanes and the lane width of
+    /// the first argument.
+    fn umax_lanes(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+        let (lanes, bits) = ctx.arg_lanes(0)?;
+        let size = ctx.bit_size(ctx.return_type()?.as_ref())?;
+
+        let result = ((lanes as u64) << 16) | bits as u64;
+        Ok(ReturnValue::Value(ctx.solver().bv_from_u64(result, size)))
+    }
+
+    #[test]
+    fn test_arg_lanes() {
+        let path = "./tests/unit_tests/intrinsics.bc";
+        let mut project = Project::from_path(path).expect("Failed to created proejct");
+        project
+            .hooks_mut()
+            .intrinsics_mut()
+            .replace("llvm.umax.", umax_lanes);
+
+        // Scalars are a single lane.
+        let res = run_project(&project, "test_umax");
+        assert_eq!(res, vec![Ok(Some(0x1_0020))]);
+
+        // Lanes of `<4 x i16>`.
+        let res = run_project(&project, "test_umax_vec4");
+        assert_eq!(res, vec![Ok(Some(0x4_0010))]);
+    }
+
+    #[test]
+    fn test_umax_replaced_in_vm() {
+        let path = "./tests/unit_tests/intrinsics.bc";
+        let project = Project::from_path(path).expect("Failed to created proejct");
+        let mut vm = VM::new("test_umax", &project).expect("Failed to create VM");
+        vm.intrinsics_mut().replace("llvm.umax.", custom_umax);
+        assert_eq!(run_vm(vm), vec![Ok(Some(0xabcd))]);
+
+        // The project still has the default hook.
+        let res = run_project(&project, "test_umax");
+        assert_ne!(res, vec![Ok(Some(0xabcd))]);
+    }
+
+    #[test]
+    fn test_umax_removed() {
+        let path = "./tests/unit_tests/intrinsics.bc";
+        let mut project = Project::from_path(path).expect("Failed to created proejct");
+        let removed = project.hooks_mut().intrinsics_mut().remove("llvm.umax.");
+        assert!(removed.is_some());
+        assert!(project.hooks().intrinsics().get("llvm.umax.i32").is_none());
+
+        let res = run_project(&project, "test_umax");
+        assert_eq!(res.len(), 1);
+        assert_eq!(
+            res[0],
+            Err(VMError::UnsupportedIntrinsic("llvm.umax.i32".to_owned()))
+        );
+    }
+
     #[test]
     fn test_umax_vec() {
         let res = run("test_umax_vec");
@@ -509,6 +1246,229 @@ mod tests {
         assert_eq!(res[0], Ok(Some(0x000043210000bcef)));
     }
 
+    #[test]
+    fn test_umax_vec4() {
+        let res = run("test_umax_vec4");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(0x80000005ffff0002u64 as i64)));
+    }
+
+    #[test]
+    fn test_smax() {
+        let res = run("test_smax");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(3)));
+    }
+
+    #[test]
+    fn test_smax_vec4() {
+        let res = run("test_smax_vec4");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(0x7fff000500030002)));
+    }
+
+    #[test]
+    fn test_powi() {
+        let res = run("test_powi");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(1024f64.to_bits() as i64)));
+    }
+
+    #[test]
+    fn test_powi_f32() {
+        let res = run("test_powi_f32");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(0.25f32.to_bits() as i64)));
+    }
+
+    #[test]
+    fn test_powi_replaced() {
+        let path = "./tests/unit_tests/intrinsics.bc";
+        let mut project = Project::from_path(path).expect("Failed to created proejct");
+        let replaced = project
+            .hooks_mut()
+            .intrinsics_mut()
+            .replace("llvm.powi.", noop);
+        assert!(replaced.is_some());
+
+        // The call expects a return value.
+        let res = run_project(&project, "test_powi");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Err(VMError::MalformedInstruction));
+    }
+
+    #[test]
+    fn test_lround() {
+        let res = run("test_lround");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(3)));
+    }
+
+    #[test]
+    fn test_llround_negative() {
+        let res = run("test_llround_negative");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(-3)));
+    }
+
+    #[test]
+    fn test_lrint() {
+        let res = run("test_lrint");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(2)));
+    }
+
+    #[test]
+    fn test_llrint_f32() {
+        let res = run("test_llrint_f32");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(4)));
+    }
+
+    #[test]
+    fn test_constrained_fadd() {
+        let res = run("test_constrained_fadd");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(3.75f64.to_bits() as i64)));
+    }
+
+    #[test]
+    fn test_constrained_fsub() {
+        let res = run("test_constrained_fsub");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some((-3f64).to_bits() as i64)));
+    }
+
+    #[test]
+    fn test_constrained_fmul_f32() {
+        let res = run("test_constrained_fmul_f32");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some((-6f32).to_bits() as i64)));
+    }
+
+    #[test]
+    fn test_constrained_fdiv() {
+        let res = run("test_constrained_fdiv");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(0.125f64.to_bits() as i64)));
+    }
+
+    #[test]
+    fn test_constrained_frem() {
+        let res = run("test_constrained_frem");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(1.5f64.to_bits() as i64)));
+    }
+
+    #[test]
+    fn test_constrained_unsupported() {
+        let res = run("test_constrained_unsupported");
+        assert_eq!(res.len(), 1);
+        assert_eq!(
+            res[0],
+            Err(VMError::UnsupportedIntrinsic(
+                "llvm.experimental.constrained.sqrt.f64".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_unregistered() {
+        let res = run("test_unregistered");
+        assert_eq!(res.len(), 1);
+        assert_eq!(
+            res[0],
+            Err(VMError::UnsupportedIntrinsic("llvm.cos.f64".to_owned()))
+        );
+    }
+
+    #[test]
+    fn test_experimental_unsupported() {
+        let res = run("test_experimental_unsupported");
+        assert_eq!(res.len(), 1);
+        assert_eq!(
+            res[0],
+            Err(VMError::UnsupportedIntrinsic(
+                "llvm.experimental.vector.reverse.v4i32".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_objectsize() {
+        let res = run("test_objectsize");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(16)));
+    }
+
+    #[test]
+    fn test_objectsize_offset() {
+        let res = run("test_objectsize_offset");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(12)));
+    }
+
+    #[test]
+    fn test_objectsize_unknown() {
+        let res = run("test_objectsize_unknown");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(-1)));
+    }
+
+    #[test]
+    fn test_prefetch() {
+        assert!(resolves_to("llvm.prefetch", noop));
+        assert!(resolves_to("llvm.prefetch.p0i8", noop));
+
+        let res = run("test_prefetch");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(0)));
+    }
+
+    #[test]
+    fn test_readcyclecounter() {
+        let mut res = run("test_readcyclecounter");
+        assert_eq!(res.len(), 2, "expected both branches to be feasible");
+        res.sort_by_key(|r| *r.as_ref().unwrap());
+        assert_eq!(res, vec![Ok(Some(0)), Ok(Some(1))]);
+    }
+
+    #[test]
+    fn test_localescape() {
+        assert!(resolves_to("llvm.localescape", noop));
+
+        let res = run("test_localescape");
+        assert_eq!(res, vec![Ok(Some(1))]);
+    }
+
+    #[test]
+    fn test_localrecover() {
+        let res = run("test_localrecover");
+        let expected = VMError::UnsupportedIntrinsic("llvm.localrecover".to_owned());
+        assert_eq!(res, vec![Err(expected)]);
+    }
+
+    #[test]
+    fn test_fptosi_sat() {
+        let res = run("test_fptosi_sat");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(i32::MAX as i64)));
+    }
+
+    #[test]
+    fn test_fptosi_sat_negative() {
+        let res = run("test_fptosi_sat_negative");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(i32::MIN as u32 as i64)));
+    }
+
+    #[test]
+    fn test_fptoui_sat_nan() {
+        let res = run("test_fptoui_sat_nan");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(0)));
+    }
+
     #[test]
     fn test_sadd_sat0() {
         let res = run("test_sadd_sat0");
@@ -565,6 +1525,25 @@ mod tests {
         assert_eq!(res[0], Ok(Some(0xfb3)));
     }
 
+    #[test]
+    fn test_ctlz() {
+        assert_eq!(run("test_ctlz"), vec![Ok(Some(8))]);
+        assert_eq!(run("test_ctlz_zero"), vec![Ok(Some(32))]);
+        assert_eq!(run("test_ctlz_vec"), vec![Ok(Some(0x0007000f))]);
+    }
+
+    #[test]
+    fn test_ctlz_zero_poison() {
+        let res = run("test_ctlz_zero_poison");
+        assert_eq!(res, vec![Ok(Some(1)), Err(VMError::PoisonValue)]);
+    }
+
+    #[test]
+    fn test_cttz() {
+        assert_eq!(run("test_cttz"), vec![Ok(Some(20))]);
+        assert_eq!(run("test_cttz_zero"), vec![Ok(Some(32))]);
+    }
+
     #[test]
     fn test_sadd_with_overflow0() {
         let res = run("test_sadd_with_overflow0");
@@ -613,4 +1592,18 @@ mod tests {
         assert_eq!(res.len(), 1);
         assert_eq!(res[0], Ok(Some(5)));
     }
+
+    #[test]
+    fn test_var_annotation() {
+        let res = run("test_var_annotation");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(5)));
+    }
+
+    #[test]
+    fn test_ptr_annotation() {
+        let res = run("test_ptr_annotation");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(7)));
+    }
 }
@@ -3,23 +3,29 @@ use llvm_ir::{
     function::{FunctionAttribute, ParameterAttribute},
     instruction::Call,
     terminator::Invoke,
-    Name, Operand, Type,
+    Instruction, Name, Operand, Terminator, Type, TypeRef,
 };
 use log::trace;
 use std::collections::HashMap;
 
 use crate::{
-    common::SolutionVariable,
-    vm::{Result, ReturnValue, VM},
+    common::{get_u64_solution_from_operand, SolutionVariable},
+    memory::BITS_IN_BYTE,
+    vm::{Result, ReturnValue, State, VM},
     Solutions, VMError,
 };
 
+mod context;
 mod intrinsics;
+mod stdio;
 
-use intrinsics::{is_intrinsic, Intrinsics};
+pub use context::HookCtx;
+pub(crate) use intrinsics::is_intrinsic;
+pub use intrinsics::Intrinsics;
+pub use stdio::{printf, puts};
 
-/// Hook type
-pub type Hook = fn(&mut VM<'_>, f: FnInfo) -> Result<ReturnValue>;
+/// Hook type, the hook gets the call it replaces through the [HookCtx].
+pub type Hook = fn(&mut HookCtx<'_, '_>) -> Result<ReturnValue>;
 
 /// Arg type
 pub type Argument = (Operand, Vec<ParameterAttribute>);
@@ -27,28 +33,52 @@ pub type Argument = (Operand, Vec<ParameterAttribute>);
 #[derive(Debug)]
 pub struct FnInfo {
     pub arguments: Vec<Argument>,
+
+    /// Types of the arguments, in the same order as `arguments`.
+    pub argument_types: Vec<TypeRef>,
+
     pub return_attrs: Vec<ParameterAttribute>,
     pub fn_attrs: Vec<FunctionAttribute>,
 }
 
 impl FnInfo {
-    pub fn from_call(call: &Call) -> Self {
+    pub fn from_call(call: &Call, state: &State<'_>) -> Self {
         Self {
             arguments: call.arguments.clone(),
+            argument_types: argument_types(&call.arguments, state),
             return_attrs: call.return_attributes.clone(),
             fn_attrs: call.function_attributes.clone(),
         }
     }
 
-    pub fn from_invoke(invoke: &Invoke) -> Self {
+    pub fn from_invoke(invoke: &Invoke, state: &State<'_>) -> Self {
         Self {
             arguments: invoke.arguments.clone(),
+            argument_types: argument_types(&invoke.arguments, state),
             return_attrs: invoke.return_attributes.clone(),
             fn_attrs: invoke.function_attributes.clone(),
         }
     }
 }
 
+/// Returns the types of `arguments` in the module that is currently executed.
+fn argument_types(arguments: &[Argument], state: &State<'_>) -> Vec<TypeRef> {
+    arguments.iter().map(|(op, _)| state.type_of(op)).collect()
+}
+
+/// Returns the return type of the call to the hook that is currently executing.
+pub(crate) fn call_return_type(vm: &VM<'_>) -> Result<TypeRef> {
+    let location = &vm.state.current_loc;
+    match location.block.instrs.get(location.get_instruction_offset()) {
+        Some(Instruction::Call(call)) => Ok(vm.state.type_of(call)),
+        Some(_) => Err(VMError::InternalError("Hook called outside of a call")),
+        None => match &location.block.term {
+            Terminator::Invoke(invoke) => Ok(vm.state.type_of(invoke)),
+            _ => Err(VMError::InternalError("Hook called outside of a call")),
+        },
+    }
+}
+
 pub struct Hooks {
     hooks: HashMap<String, Hook>,
 
@@ -72,6 +102,9 @@ impl Hooks {
         hooks.add("x0001e_lib::symbolic", symbolic);
         hooks.add("assume", assume);
         hooks.add("symbolic", symbolic_no_type);
+        hooks.add("getenv", getenv);
+        hooks.add("puts", puts);
+        hooks.add("printf", printf);
 
         hooks
     }
@@ -80,6 +113,16 @@ impl Hooks {
         self.hooks.insert(name.into(), hook);
     }
 
+    /// Returns the intrinsic hooks.
+    pub fn intrinsics(&self) -> &Intrinsics {
+        &self.intrinsics
+    }
+
+    /// Returns the intrinsic hooks, these can be used to replace or remove the default intrinsics.
+    pub fn intrinsics_mut(&mut self) -> &mut Intrinsics {
+        &mut self.intrinsics
+    }
+
     pub fn get(&self, name: &str) -> Option<Hook> {
         trace!("hooks: get {}", name);
         if is_intrinsic(name) {
@@ -90,68 +133,64 @@ impl Hooks {
     }
 }
 
-pub fn assume(vm: &mut VM<'_>, info: FnInfo) -> Result<ReturnValue> {
-    trace!("assume info: {:?}", info);
+pub fn assume(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    trace!("assume info: {:?}", ctx.info());
 
-    let (condition, _) = info.arguments.get(0).unwrap();
-    let condition = vm.state.get_var(condition)?;
+    let condition = ctx.arg(0)?;
 
     match condition.len() {
-        1 => vm.state.solver.assert(&condition),
+        1 => ctx.solver().assert(&condition),
         _ => {
-            let zero = vm.solver.bv_zero(condition.len());
-            vm.state.solver.assert(&condition.ne(&zero))
+            let zero = ctx.solver().bv_zero(condition.len());
+            ctx.solver().assert(&condition.ne(&zero))
         }
     }
 
-    if vm.solver.is_sat()? {
+    if ctx.solver().is_sat()? {
         Ok(ReturnValue::Void)
     } else {
         Err(VMError::Unsat)
     }
 }
 
-pub fn symbolic_no_type(vm: &mut VM<'_>, info: FnInfo) -> Result<ReturnValue> {
-    trace!("symbolic fninfo: {:?}", info);
-
-    let addr = &info.arguments[0].0;
-    let size = &info.arguments[1].0;
+pub fn symbolic_no_type(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    trace!("symbolic fninfo: {:?}", ctx.info());
 
-    let ty = vm.state.type_of(addr);
+    let ty = ctx.arg_type(0);
     if !matches!(ty.as_ref(), Type::PointerType { .. }) {
         panic!("Expected pointer type");
     }
 
-    let size = vm.state.get_var(size)?;
-    let size = match vm.solver.get_solutions_for_bv(&size, 1)? {
+    let size = ctx.arg(1)?;
+    let size = match ctx.solver().get_solutions_for_bv(&size, 1)? {
         Solutions::None => Err(VMError::Unsat),
         Solutions::Exactly(s) => Ok(s[0].as_u64().unwrap()),
         Solutions::AtLeast(_) => panic!("Found multiple solutions for size"),
     }?;
 
-    let name = addr.to_string();
-    let name = format!("{}-{}", name, rand::random::<u32>());
-    let value = vm.solver.bv(size as u32, &name);
+    let addr = get_u64_solution_from_operand(ctx.state(), &ctx.args()[0].0)?;
+    let name = ctx.solver().fresh_name(&format!("mem_{addr:#x}"));
+    let value = ctx.solver().bv(size as u32, &name);
 
-    let addr = vm.state.get_var(addr)?;
-    vm.state.mem.borrow_mut().write(&addr, value.clone())?;
+    let addr = ctx.arg(0)?;
+    ctx.mem().write(&addr, value.clone())?;
 
     let solution_var = SolutionVariable {
         name,
         value,
         ty: None,
     };
-    vm.state.symbols.push(solution_var);
+    ctx.add_symbol(solution_var);
 
     Ok(ReturnValue::Void)
 }
 
-pub fn symbolic(vm: &mut VM<'_>, info: FnInfo) -> Result<ReturnValue> {
-    trace!("symbolic fninfo: {:?}", info);
+pub fn symbolic(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    trace!("symbolic fninfo: {:?}", ctx.info());
 
-    let addr = &info.arguments[0].0;
+    let addr = &ctx.args()[0].0;
 
-    let ty = vm.state.type_of(addr);
+    let ty = ctx.arg_type(0);
     if let Type::PointerType {
         pointee_type: inner_ty,
         ..
@@ -165,23 +204,71 @@ pub fn symbolic(vm: &mut VM<'_>, info: FnInfo) -> Result<ReturnValue> {
             Operand::ConstantOperand(_) => todo!(),
             Operand::MetadataOperand => todo!(),
         };
-        let name = format!("{}-{}", name, rand::random::<u32>());
-        //println!("Symbolic {name}");
+        let name = ctx.solver().fresh_name(&name);
 
-        let size = vm.project.bit_size(inner_ty.as_ref())?;
-        let new_symbol = vm.solver.bv(size, &name);
+        let size = ctx.bit_size(inner_ty.as_ref())?;
+        let new_symbol = ctx.solver().bv(size, &name);
         let solution_var = SolutionVariable {
             name,
             value: new_symbol.clone(),
             ty: Some(inner_ty.clone()),
         };
-        vm.state.symbols.push(solution_var);
+        ctx.add_symbol(solution_var);
 
-        let addr = vm.state.get_var(addr)?;
-        vm.state.mem.borrow_mut().write(&addr, new_symbol)?;
+        let addr = ctx.arg(0)?;
+        ctx.mem().write(&addr, new_symbol)?;
 
         Ok(ReturnValue::Void)
     } else {
         panic!("not a pointer type");
     }
 }
+
+/// Hook for `getenv`.
+///
+/// Environment variables that have been marked as symbolic with [VM::set_symbolic_env_var] may
+/// either be unset or set to an unconstrained string, other variables are always unset. The
+/// returned pointer is null when unset, so branching on it forks the path.
+pub fn getenv(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    let (vm, info) = ctx.split();
+    trace!("getenv info: {:?}", info);
+
+    let name = vm.state.get_var(&info.arguments[0].0)?;
+    let name = vm.state.mem.borrow().read_cstring(&name)?;
+
+    let ptr_size = vm.project.ptr_size;
+    let null = vm.solver.bv_zero(ptr_size);
+
+    let len = match vm.symbolic_env_var(&name) {
+        Some(len) => len,
+        None => return Ok(ReturnValue::Value(null)),
+    };
+
+    // Null terminated string with `len` unconstrained bytes.
+    let var_name = vm.solver.fresh_name(&format!("getenv({name})"));
+    let terminator = vm.solver.bv_zero(BITS_IN_BYTE);
+    let value = if len > 0 {
+        let value = vm.solver.bv(len * BITS_IN_BYTE, &var_name);
+        vm.add_symbol(SolutionVariable {
+            name: var_name.clone(),
+            value: value.clone(),
+            ty: None,
+        });
+        terminator.concat(&value)
+    } else {
+        terminator
+    };
+
+    let addr = vm.state.allocate(value.len() as u64, 1)?;
+    let addr = vm.solver.bv_from_u64(addr, ptr_size);
+    vm.state.mem.borrow_mut().write(&addr, value)?;
+
+    let is_set = vm.solver.bv(1, &format!("{var_name}.is_set"));
+    vm.add_symbol(SolutionVariable {
+        name: format!("{var_name}.is_set"),
+        value: is_set.clone(),
+        ty: None,
+    });
+
+    Ok(ReturnValue::Value(is_set.ite(&addr, &null)))
+}
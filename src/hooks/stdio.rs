@@ -0,0 +1,144 @@
+//! Hooks for printing with `puts` and `printf`.
+//!
+//! The output is appended to [State::output](crate::vm::State::output) instead of being printed,
+//! so each path has its own output. Values that are symbolic are written as `<symbolic>`.
+use log::trace;
+
+use super::{call_return_type, HookCtx};
+use crate::{
+    memory::MemoryError,
+    vm::{Result, ReturnValue, VM},
+    Solutions, BV,
+};
+
+/// Written in place of values that do not have a single possible value.
+const SYMBOLIC: &str = "<symbolic>";
+
+/// Hook for `puts`, writes the string followed by a newline.
+pub fn puts(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    let (vm, info) = ctx.split();
+    trace!("puts info: {:?}", info);
+
+    let string = vm.state.get_var(&info.arguments[0].0)?;
+    let mut output = read_string(vm, &string)?;
+    output.push('\n');
+
+    write_output(vm, &output)
+}
+
+/// Hook for `printf`.
+///
+/// Supports the conversions `%d`, `%i`, `%u`, `%x`, `%c`, `%s` and `%%`. Length modifiers are
+/// skipped, the width of the argument is used instead. Flags, field widths and precision are not
+/// supported, such conversions are written as they are.
+pub fn printf(ctx: &mut HookCtx<'_, '_>) -> Result<ReturnValue> {
+    let (vm, info) = ctx.split();
+    trace!("printf info: {:?}", info);
+
+    let format = vm.state.get_var(&info.arguments[0].0)?;
+    let format = vm.state.mem.borrow().read_cstring(&format)?;
+
+    let mut arguments = info.arguments[1..].iter();
+    let mut output = String::new();
+    let mut chars = format.chars().peekable();
+    while let Some(c) = chars.next() {
+        if c != '%' {
+            output.push(c);
+            continue;
+        }
+
+        let mut length = String::new();
+        while let Some(modifier) = chars.next_if(|c| matches!(c, 'h' | 'l' | 'j' | 'z' | 't')) {
+            length.push(modifier);
+        }
+
+        let conversion = match chars.next() {
+            Some('%') => {
+                output.push('%');
+                continue;
+            }
+            Some(conversion @ ('d' | 'i' | 'u' | 'x' | 'c' | 's')) => conversion,
+            Some(other) => {
+                output.push('%');
+                output.push_str(&length);
+                output.push(other);
+                continue;
+            }
+            None => {
+                output.push('%');
+                output.push_str(&length);
+                break;
+            }
+        };
+
+        // Missing arguments are undefined behavior, write the conversion as is.
+        let argument = match arguments.next() {
+            Some((argument, _)) => vm.state.get_var(argument)?,
+            None => {
+                output.push('%');
+                output.push_str(&length);
+                output.push(conversion);
+                continue;
+            }
+        };
+
+        let formatted = match conversion {
+            's' => read_string(vm, &argument)?,
+            _ => match concrete_value(vm, &argument)? {
+                Some(value) => format_integer(conversion, value, argument.len()),
+                None => SYMBOLIC.to_owned(),
+            },
+        };
+        output.push_str(&formatted);
+    }
+
+    write_output(vm, &output)
+}
+
+/// Append `output` to the output of the current path, and return the number of bytes written.
+fn write_output(vm: &mut VM<'_>, output: &str) -> Result<ReturnValue> {
+    vm.state.output.push_str(output);
+
+    let bits = vm.project.bit_size(call_return_type(vm)?.as_ref())?;
+    let written = vm.solver.bv_from_u64(output.len() as u64, bits);
+    Ok(ReturnValue::Value(written))
+}
+
+/// Read the string at `addr`, strings with symbolic contents are written as `<symbolic>`.
+fn read_string(vm: &VM<'_>, addr: &BV) -> Result<String> {
+    let addr = match concrete_value(vm, addr)? {
+        Some(addr) => vm.solver.bv_from_u64(addr, vm.project.ptr_size),
+        None => return Ok(SYMBOLIC.to_owned()),
+    };
+
+    match vm.state.mem.borrow().read_cstring(&addr) {
+        Ok(string) => Ok(string),
+        Err(MemoryError::SymbolicString) => Ok(SYMBOLIC.to_owned()),
+        Err(err) => Err(err.into()),
+    }
+}
+
+/// Returns the value of `value` if it only has a single possible value.
+fn concrete_value(vm: &VM<'_>, value: &BV) -> Result<Option<u64>> {
+    if let Some(value) = value.as_concrete() {
+        return Ok(Some(value));
+    }
+
+    match vm.solver.get_solutions_for_bv(value, 1)? {
+        Solutions::Exactly(solutions) => Ok(solutions[0].as_u64()),
+        Solutions::AtLeast(_) | Solutions::None => Ok(None),
+    }
+}
+
+/// Format an integer `bits` wide for the printf `conversion`.
+fn format_integer(conversion: char, value: u64, bits: u32) -> String {
+    match conversion {
+        'd' | 'i' => {
+            let unused = 64 - bits.min(64);
+            (((value << unused) as i64) >> unused).to_string()
+        }
+        'x' => format!("{value:x}"),
+        'c' => char::from(value as u8).to_string(),
+        _ => value.to_string(),
+    }
+}
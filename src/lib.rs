@@ -23,7 +23,7 @@
 //! Code to analyze the function can be written as
 //!
 //! ```rust
-//! use x0001e::{Project, ReturnValue, VM};
+//! use x0001e::prelude::*;
 //!
 //! fn main() -> Result<(), Box<dyn std::error::Error>> {
 //!     let project = Project::from_path("tests/doc_tests/test.bc")?;
@@ -90,8 +90,9 @@
 //! ## Hooks
 //!
 //! Hooks can be added for any function the IR calls. They are added onto a project, and when a call
-//! matches the name the VM first check if a hook is available for the function call. Each hook has
-//! full access to the [VM] so they can re-implement most of the functionality.
+//! matches the name the VM first check if a hook is available for the function call. Each hook gets
+//! a [HookCtx](hooks::HookCtx) with the arguments of the call, and access to the solver and memory
+//! of the current path.
 //!
 #![warn(rust_2018_idioms, rust_2021_compatibility)]
 //#![warn(missing_docs)]
@@ -111,6 +112,13 @@ pub use crate::{
     vm::{Result, ReturnValue, VMError, VM},
 };
 
+/// The commonly used types, so they can all be imported with `use x0001e::prelude::*`.
+pub mod prelude {
+    pub use crate::{
+        memory::MemoryError, Project, ReturnValue, Solutions, Solver, VMError, BV, VM,
+    };
+}
+
 pub mod ir {
     ///! Re-exports of `llvm-ir` types.
     pub use llvm_ir::types::NamedStructDef;
@@ -6,21 +6,38 @@
 //! Memory should first be allocated, this allocates a certain amount of bits and returns an address
 //! to an uninitialized piece of memory. Read/Write operations to those addresses can then be done.
 //!
-//! On allocations, the allocation is given a unique ID. This ID is used to ensure reads to not
-//! cross allocation boundaries, and if they do a `MemoryError::OutOfBounds` is returned.
+//! With bounds checks enabled, see [Memory::set_bounds_checks], each allocation is given a unique
+//! ID. This ID is used to ensure reads to not cross allocation boundaries, and if they do a
+//! `MemoryError::OutOfBounds` is returned.
 //!
 //! The system also provides null pointer checking, this is enabled with `null_detection` in
 //! [Memory]. This checks that the address cannot be null when both reading and writing.
 //!
 //! It does not currently check that reads are not performed from uninitialized memory.
+//!
+//! Values written to memory keep their taint, see [BV::taint]. Taint is tracked per byte for
+//! concrete addresses. A tainted value written to a symbolic address can end up anywhere, so after
+//! that all reads are tainted.
 use log::{debug, trace};
+use std::collections::HashSet;
 use thiserror::Error;
 
-use crate::solver::{Array, Solver, SolverError, BV};
+use crate::solver::{Array, Solutions, Solver, SolverError, BV};
+
+/// Largest size in bytes an allocation with a symbolic size can have.
+///
+/// Allocations with a symbolic size always reserve this many bytes, and sizes above it are
+/// treated as errors.
+pub const MAX_SYMBOLIC_ALLOCATION: u64 = 4096;
+
+/// Size in bytes of the memory reserved at address zero.
+///
+/// No allocations are made in this range, so accesses to it are treated as null pointer
+/// dereferences.
+pub const NULL_PAGE_SIZE: u64 = 0x1000;
 
-/// Feature flag that enables checking when reading that the first byte read matches the last byte
-/// read's allocation id.
-pub const CHECK_OUT_OF_BOUNDS: bool = false;
+/// Longest string in bytes, excluding the null terminator, that [Memory::read_cstring] reads.
+pub const MAX_CSTRING_LENGTH: u64 = 4096;
 
 /// Error representing an issue when performing memory operations.
 #[derive(Debug, Error, PartialEq, Eq)]
@@ -51,6 +68,19 @@ pub enum MemoryError {
     #[error("Out of bounds")]
     OutOfBounds,
 
+    /// The size of an allocation with a symbolic size can exceed [MAX_SYMBOLIC_ALLOCATION].
+    #[error("Symbolic allocation size can exceed {0} bytes")]
+    SymbolicAllocationTooLarge(u64),
+
+    /// A string read with [Memory::read_cstring] does not have a concrete value.
+    #[error("Expected a string with a concrete value")]
+    SymbolicString,
+
+    /// A string read with [Memory::read_cstring] is not null terminated within the given number
+    /// of bytes.
+    #[error("String is not null terminated within {0} bytes")]
+    UnterminatedString(u64),
+
     /// Errors passed on from the solver.
     #[error(transparent)]
     Solver(#[from] SolverError),
@@ -69,6 +99,19 @@ pub fn to_bytes(size: u64) -> Result<u64, MemoryError> {
     }
 }
 
+/// Information about a single allocation, see [Memory::allocations].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AllocationInfo {
+    /// Address where the allocation starts.
+    pub addr: u64,
+
+    /// Size of the allocation in bytes.
+    pub bytes: u64,
+
+    /// If the allocation has not been freed.
+    pub live: bool,
+}
+
 /// Allocations and backing memory store.
 ///
 /// Memory keeps track of all allocations and provides the backing memory store. All allocations
@@ -93,6 +136,9 @@ pub struct Memory {
     /// Enable to check if addresses passed to read or write can be null.
     null_detection: bool,
 
+    /// Enable to tag allocations with their ID and check that reads stay in one allocation.
+    bounds_checks: bool,
+
     /// Symbol that is a null pointer, used for comparisons if `null_detection` is enabled.
     nullptr: BV,
 
@@ -104,6 +150,12 @@ pub struct Memory {
 
     /// The next allocation ID to store in `allocations`.
     next_allocation_id: usize,
+
+    /// All allocations in the order they were made.
+    allocated: Vec<AllocationInfo>,
+
+    /// Bytes that hold tainted values.
+    tainted: TaintedBytes,
 }
 
 impl Memory {
@@ -133,8 +185,11 @@ impl Memory {
             allocations,
             ptr_size,
             null_detection: false,
+            bounds_checks: false,
             nullptr,
             next_allocation_id: 0,
+            allocated: Vec::new(),
+            tainted: TaintedBytes::default(),
         }
     }
 
@@ -166,18 +221,163 @@ impl Memory {
             self.next_allocation_id
         );
 
-        if CHECK_OUT_OF_BOUNDS {
-            let alloc_id = self.solver.bv_from_u64(self.next_allocation_id as u64, 8);
-            for i in 0..bytes {
-                let addr = self.solver.bv_from_u64(addr + i, self.ptr_size);
-                self.allocations = self.allocations.write(&addr, &alloc_id);
-            }
-            self.next_allocation_id += 1;
+        if self.bounds_checks {
+            self.tag_allocation(addr, bytes, None);
+        }
+
+        self.allocated.push(AllocationInfo {
+            addr,
+            bytes,
+            live: true,
+        });
+
+        Ok(addr)
+    }
+
+    /// Allocate `bits` in memory aligned to `align`, where only the first `size` bytes are in
+    /// bounds.
+    ///
+    /// `size` is a symbolic size in bytes that must not exceed `bits`. The full `bits` are
+    /// reserved, but with bounds checks enabled the bytes from `size` and onwards are
+    /// tagged as a separate allocation. So reads that cross the symbolic end are out of bounds.
+    pub fn allocate_symbolic(
+        &mut self,
+        bits: u64,
+        align: u64,
+        size: &BV,
+    ) -> Result<u64, MemoryError> {
+        let (addr, bytes) = self.allocator.get_address(bits, align)?;
+
+        debug!(
+            "allocate symbolic addr: {addr:x}, max bytes: {bytes}, allocation_id: {}",
+            self.next_allocation_id
+        );
+
+        if self.bounds_checks {
+            self.tag_allocation(addr, bytes, Some(size));
         }
 
+        self.allocated.push(AllocationInfo {
+            addr,
+            bytes,
+            live: true,
+        });
+
         Ok(addr)
     }
 
+    /// Enable or disable checking that reads do not cross allocation boundaries.
+    ///
+    /// When enabled, reads that start in one allocation and end in another return
+    /// [MemoryError::OutOfBounds], and [Memory::in_bounds_condition] can be used. Tagging each
+    /// byte with its allocation is expensive, so this is disabled by default. Allocations made
+    /// before enabling are tagged with their full size.
+    pub fn set_bounds_checks(&mut self, enabled: bool) {
+        if enabled && !self.bounds_checks {
+            let allocated = self.allocated.clone();
+            for allocation in allocated {
+                self.tag_allocation(allocation.addr, allocation.bytes, None);
+            }
+        }
+        self.bounds_checks = enabled;
+    }
+
+    /// Returns if reads are checked to not cross allocation boundaries.
+    pub fn bounds_checks(&self) -> bool {
+        self.bounds_checks
+    }
+
+    /// Tag the `bytes` starting at `addr` with a new allocation id.
+    ///
+    /// If `size` is given only the first `size` bytes are tagged with the id, the rest are tagged
+    /// with a separate guard id.
+    fn tag_allocation(&mut self, addr: u64, bytes: u64, size: Option<&BV>) {
+        let alloc_id = self.solver.bv_from_u64(self.next_allocation_id as u64, 8);
+        let guard_id = self
+            .solver
+            .bv_from_u64(self.next_allocation_id as u64 + 1, 8);
+
+        for i in 0..bytes {
+            let id = match size {
+                Some(size) => {
+                    let offset = self.solver.bv_from_u64(i, self.ptr_size);
+                    offset.ult(size).ite(&alloc_id, &guard_id)
+                }
+                None => alloc_id.clone(),
+            };
+
+            let addr = self.solver.bv_from_u64(addr + i, self.ptr_size);
+            self.allocations = self.allocations.write(&addr, &id);
+        }
+
+        self.next_allocation_id += if size.is_some() { 2 } else { 1 };
+    }
+
+    /// Mark the allocation starting at `addr` as freed.
+    ///
+    /// The address space is not reused, and accesses to the allocation are not checked. This
+    /// only updates what [Memory::allocations] reports. Returns `false` if there is no live
+    /// allocation at `addr`.
+    pub fn free(&mut self, addr: u64) -> bool {
+        match self
+            .allocated
+            .iter_mut()
+            .find(|allocation| allocation.addr == addr && allocation.live)
+        {
+            Some(allocation) => {
+                allocation.live = false;
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Merge the contents of `other` into `self`, keeping the values of `self` where `cond` is
+    /// true and the values of `other` where it is false.
+    ///
+    /// Both must have made the same allocations, otherwise `false` is returned and nothing is
+    /// changed.
+    pub fn merge(&mut self, other: &Memory, cond: &BV) -> bool {
+        if self.allocated != other.allocated {
+            return false;
+        }
+
+        self.store.memory = cond.ite_array(&self.store.memory, &other.store.memory);
+        self.tainted.merge(&other.tainted);
+        true
+    }
+
+    /// Returns all allocations, in the order they were made.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use x0001e::memory::Memory;
+    /// # use x0001e::solver::Solver;
+    /// #
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// #   let solver = Solver::new();
+    /// #   let ptr_size = 64;
+    ///     let mut memory = Memory::new(solver, ptr_size);
+    ///
+    ///     let address = memory.allocate(32, 4)?;
+    ///     let allocation = memory.allocations().last().unwrap();
+    ///     assert_eq!(allocation.addr, address);
+    ///     assert_eq!(allocation.bytes, 4);
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn allocations(&self) -> impl Iterator<Item = AllocationInfo> + '_ {
+        self.allocated.iter().copied()
+    }
+
+    /// Returns the live allocation that contains the address `addr`, if any.
+    pub fn allocation_containing(&self, addr: u64) -> Option<AllocationInfo> {
+        self.allocations()
+            .filter(|allocation| allocation.live)
+            .find(|allocation| addr >= allocation.addr && addr - allocation.addr < allocation.bytes)
+    }
+
     /// Read `bits` starting from `addr`.
     ///
     /// # Errors
@@ -213,11 +413,16 @@ impl Memory {
 
         // If we try to read more than a single byte, check that the read does not cross into
         // another allocation.
-        if CHECK_OUT_OF_BOUNDS && bits > BITS_IN_BYTE {
+        if self.bounds_checks && bits > BITS_IN_BYTE {
             self.check_out_of_bounds_by_size(addr, bits)?;
         }
 
         let val = self.store.read(addr, bits, &self.solver, self.ptr_size)?;
+        let val = if self.tainted.contains(addr, bits) {
+            val.taint()
+        } else {
+            val
+        };
         trace!("Read value: {val:?}");
         Ok(val)
     }
@@ -257,9 +462,92 @@ impl Memory {
         );
         assert_eq!(addr.len(), self.ptr_size, "passed wrong sized address");
         self.check_null_ptr(addr)?;
+        self.tainted.write(addr, value.len(), value.is_tainted());
         self.store.write(addr, value, &self.solver, self.ptr_size)
     }
 
+    /// Read a null terminated string starting at `addr`.
+    ///
+    /// Each byte must have a single possible value, otherwise [MemoryError::SymbolicString] is
+    /// returned, and if the constraints are unsatisfiable [SolverError::Unsat] is. At most
+    /// [MAX_CSTRING_LENGTH] bytes are read before the terminator. Invalid UTF-8 is replaced with
+    /// the replacement character.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use x0001e::memory::Memory;
+    /// # use x0001e::solver::Solver;
+    /// #
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// #   let solver = Solver::new();
+    /// #   let ptr_size = 64;
+    /// #
+    ///     let mut memory = Memory::new(solver.clone(), ptr_size);
+    ///     let address = memory.allocate(6 * 8, 1)?;
+    ///     let address = solver.bv_from_u64(address, ptr_size);
+    ///
+    ///     memory.write_cstring(&address, "hello")?;
+    ///     assert_eq!(memory.read_cstring(&address)?, "hello");
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn read_cstring(&self, addr: &BV) -> Result<String, MemoryError> {
+        let one = self.solver.bv_from_u64(1, self.ptr_size);
+        let mut addr = addr.clone();
+        let mut bytes = Vec::new();
+
+        while bytes.len() as u64 <= MAX_CSTRING_LENGTH {
+            let byte = self.read(&addr, BITS_IN_BYTE)?;
+            let byte = match byte.as_concrete() {
+                Some(byte) => byte as u8,
+                None => match self.solver.get_solutions_for_bv(&byte, 1)? {
+                    Solutions::Exactly(s) => s[0].as_u64().unwrap() as u8,
+                    Solutions::AtLeast(_) => return Err(MemoryError::SymbolicString),
+                    Solutions::None => return Err(SolverError::Unsat.into()),
+                },
+            };
+
+            if byte == 0 {
+                return Ok(String::from_utf8_lossy(&bytes).into_owned());
+            }
+            bytes.push(byte);
+            addr = addr.add(&one);
+        }
+
+        Err(MemoryError::UnterminatedString(MAX_CSTRING_LENGTH))
+    }
+
+    /// Write `value` as a null terminated string starting at `addr`.
+    ///
+    /// This writes the bytes of `value` followed by a null byte, so `value.len() + 1` bytes must
+    /// be available at `addr`.
+    pub fn write_cstring(&mut self, addr: &BV, value: &str) -> Result<(), MemoryError> {
+        // The first byte is stored in the lowest bits, so the terminator ends up last.
+        let string = value
+            .bytes()
+            .chain(std::iter::once(0))
+            .map(|byte| self.solver.bv_from_u64(byte as u64, BITS_IN_BYTE))
+            .reduce(|acc, byte| byte.concat(&acc))
+            .unwrap();
+
+        self.write(addr, string)
+    }
+
+    /// Returns a condition that is true if `addr` is in the same allocation as `base`, or points to
+    /// one byte past the end of it.
+    ///
+    /// Allocations are only tracked when bounds checks are enabled, see
+    /// [Memory::set_bounds_checks].
+    pub fn in_bounds_condition(&self, base: &BV, addr: &BV) -> BV {
+        let one = self.solver.bv_from_u64(1, self.ptr_size);
+        let base_id = self.allocations.read(base);
+
+        let in_allocation = self.allocations.read(addr).eq(&base_id);
+        let one_past_end = self.allocations.read(&addr.sub(&one)).eq(&base_id);
+        in_allocation.or(&one_past_end)
+    }
+
     /// Check that the address cannot be null. Returns `MemoryError::NullPointer` if it is possible
     /// for the address to be zero.
     fn check_null_ptr(&self, addr: &BV) -> Result<(), MemoryError> {
@@ -318,6 +606,63 @@ impl Memory {
     }
 }
 
+/// Bytes of memory that hold tainted values.
+#[derive(Debug, Clone, Default)]
+struct TaintedBytes {
+    /// Concrete addresses of the tainted bytes.
+    bytes: HashSet<u64>,
+
+    /// Set when a tainted value has been written to a symbolic address.
+    anywhere: bool,
+}
+
+impl TaintedBytes {
+    /// Update the taint of the `bits` written to `addr`.
+    ///
+    /// Untainted writes to concrete addresses clear the taint of the bytes they overwrite.
+    fn write(&mut self, addr: &BV, bits: u32, tainted: bool) {
+        match addr.as_concrete() {
+            Some(addr) => {
+                for byte in addr..addr + num_bytes(bits) {
+                    if tainted {
+                        self.bytes.insert(byte);
+                    } else {
+                        self.bytes.remove(&byte);
+                    }
+                }
+            }
+            None => self.anywhere |= tainted,
+        }
+    }
+
+    /// Returns true if any of the `bits` read from `addr` can be tainted.
+    fn contains(&self, addr: &BV, bits: u32) -> bool {
+        if self.anywhere {
+            return true;
+        }
+        if self.bytes.is_empty() {
+            return false;
+        }
+
+        match addr.as_concrete() {
+            Some(addr) => (addr..addr + num_bytes(bits)).any(|byte| self.bytes.contains(&byte)),
+            // The address can point to any of the tainted bytes.
+            None => true,
+        }
+    }
+
+    /// Merge the taint of `other` into `self`, bytes tainted in either are tainted.
+    fn merge(&mut self, other: &TaintedBytes) {
+        self.bytes.extend(other.bytes.iter().copied());
+        self.anywhere |= other.anywhere;
+    }
+}
+
+/// Returns the number of bytes `bits` occupy in memory.
+fn num_bytes(bits: u32) -> u64 {
+    (bits as u64).div_ceil(BITS_IN_BYTE as u64)
+}
+
 /// Simple bump allocator that starts allocating addresses at `BumpAllocator::ALLOC_START`
 #[derive(Debug, Clone, Default)]
 struct BumpAllocator {
@@ -477,6 +822,26 @@ impl MemoryStore {
 mod tests {
     use super::*;
 
+    #[test]
+    fn taint_kept_in_memory() {
+        let solver = Solver::new();
+        let mut memory = Memory::new(solver.clone(), 64);
+        let addr = memory.allocate(64, 4).unwrap();
+        let addr = solver.bv_from_u64(addr, 64);
+        let next = addr.add(&solver.bv_from_u64(4, 64));
+
+        let tainted = solver.bv_from_u64(1, 32).taint();
+        memory.write(&addr, tainted).unwrap();
+        memory.write(&next, solver.bv_from_u64(2, 32)).unwrap();
+        assert!(memory.read(&addr, 32).unwrap().is_tainted());
+        assert!(memory.read(&addr, 64).unwrap().is_tainted());
+        assert!(!memory.read(&next, 32).unwrap().is_tainted());
+
+        // Overwriting with an untainted value removes the taint.
+        memory.write(&addr, solver.bv_from_u64(3, 32)).unwrap();
+        assert!(!memory.read(&addr, 64).unwrap().is_tainted());
+    }
+
     #[test]
     fn allocates_in_sequence() {
         let mut alloc = BumpAllocator::new();
@@ -515,6 +880,73 @@ mod tests {
         );
     }
 
+    #[test]
+    fn free_marks_allocation() {
+        let mut memory = Memory::new(Solver::new(), 64);
+        let addr = memory.allocate(32, 4).unwrap();
+        let addr2 = memory.allocate(64, 8).unwrap();
+
+        assert!(memory.free(addr));
+        assert!(!memory.free(addr));
+
+        let allocations: Vec<_> = memory.allocations().collect();
+        assert_eq!(
+            allocations,
+            vec![
+                AllocationInfo {
+                    addr,
+                    bytes: 4,
+                    live: false
+                },
+                AllocationInfo {
+                    addr: addr2,
+                    bytes: 8,
+                    live: true
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn cstring_roundtrip() {
+        let solver = Solver::new();
+        let mut memory = Memory::new(solver.clone(), 64);
+        let addr = memory.allocate(16 * 8, 1).unwrap();
+        let addr = solver.bv_from_u64(addr, 64);
+
+        memory.write_cstring(&addr, "hello world").unwrap();
+        assert_eq!(memory.read_cstring(&addr), Ok("hello world".to_owned()));
+
+        // Reading from the middle gives the rest of the string.
+        let middle = addr.add(&solver.bv_from_u64(6, 64));
+        assert_eq!(memory.read_cstring(&middle), Ok("world".to_owned()));
+
+        memory.write_cstring(&addr, "").unwrap();
+        assert_eq!(memory.read_cstring(&addr), Ok(String::new()));
+    }
+
+    #[test]
+    fn cstring_symbolic() {
+        let solver = Solver::new();
+        let mut memory = Memory::new(solver.clone(), 64);
+        let addr = memory.allocate(4 * 8, 1).unwrap();
+        let addr = solver.bv_from_u64(addr, 64);
+
+        memory.write_cstring(&addr, "abc").unwrap();
+        let one = solver.bv_from_u64(1, 64);
+        memory
+            .write(&addr.add(&one), solver.bv(8, "symbolic"))
+            .unwrap();
+        assert_eq!(memory.read_cstring(&addr), Err(MemoryError::SymbolicString));
+
+        // Without any solutions the string has no value at all.
+        solver.assert(&solver.bv_from_bool(false));
+        assert_eq!(
+            memory.read_cstring(&addr),
+            Err(MemoryError::Solver(SolverError::Unsat))
+        );
+    }
+
     #[test]
     fn handles_overflow() {
         let mut alloc = BumpAllocator::new();
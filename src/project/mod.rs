@@ -1,20 +1,20 @@
 use anyhow::anyhow;
 use llvm_ir::{
-    module::{GlobalVariable, Linkage},
+    module::{DataLayout, Endianness, GlobalVariable, Linkage},
     types::{NamedStructDef, Typed},
-    Function, Module, Name, Type, TypeRef,
+    Constant, Function, Module, Name, Terminator, Type, TypeRef,
 };
 use log::warn;
 use rustc_demangle::demangle;
-use std::{collections::HashMap, fs, path::Path};
+use std::{collections::HashMap, fmt::Write, fs, path::Path};
 
 use crate::{
     common::{
-        get_bit_offset_concrete, get_bit_offset_symbol, get_byte_offset_concrete,
-        get_byte_offset_symbol, size_in_bits,
+        abi_alignment_in_bits, alloc_size_in_bits, get_bit_offset_concrete, get_bit_offset_symbol,
+        get_byte_offset_concrete, get_byte_offset_symbol, size_in_bits,
     },
     custom_modules::{CustomModule, RustModule},
-    hooks::{Hook, Hooks},
+    hooks::{is_intrinsic, Hook, Hooks},
     memory::to_bytes,
     VMError, BV,
 };
@@ -69,6 +69,12 @@ pub struct Project {
     /// Global variables that are private to this module.
     private_global_variables: HashMap<ModuleHandle, HashMap<Name, GlobalVariableHandle>>,
 
+    /// Aliases that are visible to other modules, maps the alias to the aliasee in its module.
+    aliases: HashMap<Name, (ModuleHandle, Name)>,
+
+    /// Aliases that are private to the module, maps the alias to the aliasee.
+    private_aliases: HashMap<ModuleHandle, HashMap<Name, Name>>,
+
     /// Public functions from user-defined modules.
     custom_module_functions: HashMap<&'static str, Hook>,
 
@@ -121,9 +127,10 @@ impl Project {
         Self::from_modules(modules)
     }
 
-    /// Creates a project from a path to a `.bc` file.
+    /// Creates a project from a path to a `.bc` or `.ll` file.
     ///
-    /// Sets up a new project with all LLVM bitcode module passed in the path.
+    /// Sets up a new project with the LLVM module passed in the path. Files with the extension
+    /// `.ll` are parsed as textual IR, all other files are parsed as bitcode.
     ///
     /// # Example
     ///
@@ -136,7 +143,34 @@ impl Project {
     /// # }
     /// ```
     pub fn from_path(path: impl AsRef<Path>) -> Result<Self, std::io::Error> {
-        let module = Module::from_bc_path(path).unwrap();
+        let path = path.as_ref();
+        let module = match path.extension() {
+            Some(extension) if extension == "ll" => Module::from_ir_path(path),
+            _ => Module::from_bc_path(path),
+        }
+        .unwrap();
+        Self::from_modules(vec![module])
+    }
+
+    /// Creates a project from LLVM bitcode in memory.
+    ///
+    /// The bitcode is the same as the contents of a `.bc` file. It is parsed directly from memory,
+    /// so the filesystem is not used.
+    ///
+    /// # Example
+    ///
+    /// ```rust
+    /// # use x0001e::Project;
+    /// #
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let bitcode = std::fs::read("tests/doc_tests/test.bc")?;
+    /// let project = Project::from_bytes(&bitcode)?;
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn from_bytes(bitcode: &[u8]) -> Result<Self, std::io::Error> {
+        let module = parse_bitcode(bitcode)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
         Self::from_modules(vec![module])
     }
 
@@ -229,6 +263,40 @@ impl Project {
             }
         }
 
+        let mut aliases = HashMap::new();
+        let mut private_aliases: HashMap<_, HashMap<_, _>> = HashMap::new();
+
+        for (i, module) in modules.iter().enumerate() {
+            let module_handle = ModuleHandle(i);
+            let private_aliases = private_aliases.entry(module_handle).or_default();
+
+            for alias in module.global_aliases.iter() {
+                let aliasee = match aliasee_name(&alias.aliasee) {
+                    Some(aliasee) => aliasee.clone(),
+                    None => {
+                        warn!("Unsupported aliasee for alias {}", alias.name);
+                        continue;
+                    }
+                };
+
+                let name = alias.name.clone();
+                match internal_get_privacy(&alias.linkage) {
+                    Some(Privacy::Internal) => {
+                        private_aliases.insert(name, aliasee);
+                    }
+                    Some(Privacy::External) => {
+                        if aliases.insert(name, (module_handle, aliasee)).is_some() {
+                            warn!("Multiple public aliases with name {} exist", alias.name);
+                        }
+                    }
+                    Some(Privacy::ExternalWeak) => {
+                        aliases.entry(name).or_insert((module_handle, aliasee));
+                    }
+                    Some(Privacy::ExternalAppend) | None => {}
+                }
+            }
+        }
+
         let mut project = Project {
             modules,
             ptr_size,
@@ -237,6 +305,8 @@ impl Project {
             global_variables,
             private_functions,
             private_global_variables,
+            aliases,
+            private_aliases,
             custom_module_functions: HashMap::new(),
             hooks: Hooks::new(),
         };
@@ -264,6 +334,18 @@ impl Project {
         }
     }
 
+    /// Returns the [Hooks] used by the project.
+    pub fn hooks(&self) -> &Hooks {
+        &self.hooks
+    }
+
+    /// Returns the [Hooks] used by the project.
+    ///
+    /// Changes to the hooks should be done before creating a [crate::VM] from the project.
+    pub fn hooks_mut(&mut self) -> &mut Hooks {
+        &mut self.hooks
+    }
+
     /// Locate an entry point.
     ///
     /// Searches all functions in all modules for `name`. It will also check demangled names for all
@@ -305,7 +387,9 @@ impl Project {
     /// Get a function by name.
     ///
     /// It will first check if `name` matches any user-defined hooks. Followed by module private
-    /// definitions, and finally check against public functions.
+    /// definitions, and finally check against public functions. If none of these match
+    /// [VMError::UndefinedFunction] is returned with the demangled name, or
+    /// [VMError::UnsupportedIntrinsic] if it is an intrinsic.
     ///
     /// This can be used when creating user-defined hooks. The name is the mangled name of the
     /// function, and requires a [ModuleHandle].
@@ -352,8 +436,84 @@ impl Project {
             return Ok(FunctionType::Function { function, module });
         }
 
-        let f = format!("{name} ({demangled_name})");
-        Err(VMError::FunctionNotFound(f))
+        // Calls to an alias are calls to the aliasee.
+        if let Some((module, Name::Name(aliasee))) =
+            self.resolve_alias(&Name::from(name), module_handle)
+        {
+            return self.get_function(aliasee, module);
+        }
+
+        // Intrinsics are never defined, so report which one is missing a hook.
+        if is_intrinsic(name) {
+            return Err(VMError::UnsupportedIntrinsic(name.to_owned()));
+        }
+
+        // The function is only declared, and no hook has been registered for it.
+        Err(VMError::UndefinedFunction(demangled_name_no_hash))
+    }
+
+    /// Resolve the alias `name` to the function or global variable it refers to.
+    ///
+    /// Aliases to other aliases are followed until a name that is not an alias is found. Returns
+    /// the module of the aliasee together with its name, or `None` if `name` is not an alias.
+    pub fn resolve_alias(
+        &self,
+        name: &Name,
+        module_handle: ModuleHandle,
+    ) -> Option<(ModuleHandle, &Name)> {
+        let num_aliases = self.aliases.len()
+            + self
+                .private_aliases
+                .values()
+                .map(|aliases| aliases.len())
+                .sum::<usize>();
+
+        let mut resolved = None;
+        let mut module_handle = module_handle;
+        let mut name = name;
+
+        // Cycles are not valid, but guard against them by only following each alias once.
+        for _ in 0..=num_aliases {
+            let private = self
+                .private_aliases
+                .get(&module_handle)
+                .and_then(|aliases| aliases.get(name));
+
+            let (next_module, next_name) = match private {
+                Some(aliasee) => (module_handle, aliasee),
+                None => match self.aliases.get(name) {
+                    Some((module, aliasee)) => (*module, aliasee),
+                    None => return resolved,
+                },
+            };
+
+            resolved = Some((next_module, next_name));
+            module_handle = next_module;
+            name = next_name;
+        }
+
+        warn!("Alias cycle found for {name}");
+        None
+    }
+
+    /// Returns the target triple of the modules, if it is set.
+    ///
+    /// All modules are expected to be compiled for the same target, so the first module that has
+    /// a target triple is used.
+    pub fn target_triple(&self) -> Option<&str> {
+        self.modules
+            .iter()
+            .find_map(|module| module.target_triple.as_deref())
+    }
+
+    /// Returns the byte order of the target, from the data layout of the first module.
+    pub fn endianness(&self) -> Endianness {
+        self.modules[0].data_layout.endianness
+    }
+
+    /// Returns the data layout of the target, from the first module.
+    pub fn data_layout(&self) -> &DataLayout {
+        &self.modules[0].data_layout
     }
 
     /// Get the definition of a named struct.
@@ -423,6 +583,128 @@ impl Project {
             })
     }
 
+    /// Get the global constructors of all modules, sorted by priority.
+    ///
+    /// The constructors are read from the `llvm.global_ctors` variable in each module. Constructors
+    /// with the same priority are kept in the order they are listed.
+    pub fn get_global_ctors(&self) -> Vec<(ModuleHandle, &Function)> {
+        let ctors_name = Name::from("llvm.global_ctors");
+
+        let mut ctors = Vec::new();
+        for (i, module) in self.modules.iter().enumerate() {
+            let module_handle = ModuleHandle(i);
+            let initializer = module
+                .global_vars
+                .iter()
+                .find(|var| var.name == ctors_name)
+                .and_then(|var| var.initializer.as_ref());
+
+            let elements = match initializer.map(|c| c.as_ref()) {
+                Some(Constant::Array { elements, .. }) => elements,
+                _ => continue,
+            };
+
+            // Each element is a `{ i32 priority, void ()* ctor, i8* data }`.
+            for element in elements {
+                let values = match element.as_ref() {
+                    Constant::Struct { values, .. } if values.len() >= 2 => values,
+                    _ => continue,
+                };
+
+                let priority = match values[0].as_ref() {
+                    Constant::Int { value, .. } => *value,
+                    _ => continue,
+                };
+
+                // Older LLVM versions may have the constructor wrapped in a bitcast.
+                let ctor = match values[1].as_ref() {
+                    Constant::BitCast(cast) => cast.operand.as_ref(),
+                    ctor => ctor,
+                };
+                let name = match ctor {
+                    Constant::GlobalReference {
+                        name: Name::Name(name),
+                        ..
+                    } => name,
+                    _ => continue,
+                };
+
+                match self.find_function(name, module_handle) {
+                    Some((module, function)) => ctors.push((priority, module, function)),
+                    None => warn!("Global constructor {name} not found"),
+                }
+            }
+        }
+
+        ctors.sort_by_key(|(priority, ..)| *priority);
+        ctors
+            .into_iter()
+            .map(|(_, module, function)| (module, function))
+            .collect()
+    }
+
+    /// Returns the control-flow graph of the function `name` in Graphviz DOT format.
+    ///
+    /// Each basic block is a node, and each edge goes from a block to a possible successor of its
+    /// terminator. The edges of conditional branches are labeled `true` and `false`, and the edges
+    /// to the indirect labels of `callbr` are labeled `indirect`.
+    pub fn function_cfg(&self, name: &str) -> Result<String, VMError> {
+        let (_, function) = self.find_entry_function(name)?;
+        let quote = |id: String| format!("\"{}\"", id.replace('"', "\\\""));
+        let node = |name: &Name| quote(name.to_string());
+
+        let mut dot = format!("digraph {} {{\n", quote(function.name.clone()));
+        for block in &function.basic_blocks {
+            writeln!(dot, "    {};", node(&block.name)).unwrap();
+        }
+
+        for block in &function.basic_blocks {
+            let edges: Vec<(&Name, Option<&str>)> = match &block.term {
+                Terminator::Br(br) => vec![(&br.dest, None)],
+                Terminator::CondBr(br) => vec![
+                    (&br.true_dest, Some("true")),
+                    (&br.false_dest, Some("false")),
+                ],
+                Terminator::Switch(switch) => switch
+                    .dests
+                    .iter()
+                    .map(|(_, dest)| (dest, None))
+                    .chain(std::iter::once((&switch.default_dest, Some("default"))))
+                    .collect(),
+                Terminator::IndirectBr(br) => br.possible_dests.iter().map(|d| (d, None)).collect(),
+                Terminator::Invoke(invoke) => vec![
+                    (&invoke.return_label, None),
+                    (&invoke.exception_label, Some("unwind")),
+                ],
+                Terminator::CleanupRet(ret) => ret.unwind_dest.iter().map(|d| (d, None)).collect(),
+                Terminator::CatchRet(ret) => vec![(&ret.successor, None)],
+                Terminator::CatchSwitch(switch) => switch
+                    .catch_handlers
+                    .iter()
+                    .chain(switch.default_unwind_dest.iter())
+                    .map(|d| (d, None))
+                    .collect(),
+                Terminator::CallBr(br) => std::iter::once((&br.return_label, None))
+                    .chain(br.other_labels.iter().map(|d| (d, Some("indirect"))))
+                    .collect(),
+                Terminator::Ret(_) | Terminator::Resume(_) | Terminator::Unreachable(_) => {
+                    Vec::new()
+                }
+            };
+
+            for (dest, label) in edges {
+                write!(dot, "    {} -> {}", node(&block.name), node(dest)).unwrap();
+                match label {
+                    Some(label) => writeln!(dot, " [label=\"{label}\"];").unwrap(),
+                    None => writeln!(dot, ";").unwrap(),
+                }
+            }
+        }
+
+        dot.push_str("}\n");
+        Ok(dot)
+    }
+
     /// Get the size in bits of type `ty`.
     pub fn bit_size(&self, ty: &Type) -> Result<u32, VMError> {
         let size = size_in_bits(ty, self)
@@ -437,6 +719,23 @@ impl Project {
         Ok(size)
     }
 
+    /// Get the size in bytes of type `ty` in memory, including padding.
+    ///
+    /// The layout follows the data layout of the target, so this is the same as `sizeof` or
+    /// `std::mem::size_of` of the type that was lowered to `ty`.
+    pub fn size_of(&self, ty: &Type) -> Result<u64, VMError> {
+        let size = alloc_size_in_bits(ty, self)
+            .ok_or_else(|| VMError::Other(anyhow!("Cannot take size of type")))?;
+        Ok(to_bytes(size)?)
+    }
+
+    /// Get the ABI alignment in bytes of type `ty`, from the data layout of the target.
+    pub fn align_of(&self, ty: &Type) -> Result<u64, VMError> {
+        let align = abi_alignment_in_bits(ty, self)
+            .ok_or_else(|| VMError::Other(anyhow!("Cannot take alignment of type")))?;
+        Ok(to_bytes(align)?)
+    }
+
     /// Get the offset to the index in bits for type `ty`.
     pub fn bit_offset_concrete(&self, ty: &Type, index: u64) -> Result<(u64, TypeRef), VMError> {
         get_bit_offset_concrete(ty, index, self)
@@ -486,6 +785,67 @@ impl Project {
     }
 }
 
+/// Parses a module from LLVM bitcode in memory.
+///
+/// [llvm_ir] can only parse modules from a path, so the bitcode is parsed with LLVM into a
+/// temporary context and then converted.
+fn parse_bitcode(bitcode: &[u8]) -> Result<Module, String> {
+    use llvm_sys::{bit_reader::LLVMParseBitcodeInContext2, core::*, prelude::*};
+    use std::ffi::{c_void, CStr};
+
+    // Without a handler LLVM exits the process on parse errors, so they are collected instead.
+    extern "C" fn handle_diagnostic(info: LLVMDiagnosticInfoRef, errors: *mut c_void) {
+        // SAFETY: `errors` is the vector passed when the handler is set, which outlives the
+        // context, and the description is a valid C string until it is disposed of.
+        unsafe {
+            let errors = &mut *(errors as *mut Vec<String>);
+            let description = LLVMGetDiagInfoDescription(info);
+            errors.push(CStr::from_ptr(description).to_string_lossy().into_owned());
+            LLVMDisposeMessage(description);
+        }
+    }
+
+    let mut errors: Vec<String> = Vec::new();
+
+    // SAFETY: The buffer is a copy of `bitcode`, and the parsed module does not take ownership of
+    // it. The module and context are only used here and are disposed of after the conversion.
+    unsafe {
+        let context = LLVMContextCreate();
+        let errors_ptr = &mut errors as *mut Vec<String> as *mut c_void;
+        LLVMContextSetDiagnosticHandler(context, Some(handle_diagnostic), errors_ptr);
+
+        let buffer = LLVMCreateMemoryBufferWithMemoryRangeCopy(
+            bitcode.as_ptr().cast(),
+            bitcode.len(),
+            b"bitcode\0".as_ptr().cast(),
+        );
+
+        let mut module_ref = std::ptr::null_mut();
+        let failed = LLVMParseBitcodeInContext2(context, buffer, &mut module_ref) != 0;
+        LLVMDisposeMemoryBuffer(buffer);
+
+        let module = if failed {
+            Err(format!("Failed to parse bitcode: {}", errors.join(", ")))
+        } else {
+            let module = Module::from_llvm_ref(module_ref);
+            LLVMDisposeModule(module_ref);
+            Ok(module)
+        };
+        LLVMContextDispose(context);
+        module
+    }
+}
+
+/// Returns the name of the global an alias refers to, looking through casts.
+fn aliasee_name(aliasee: &Constant) -> Option<&Name> {
+    match aliasee {
+        Constant::GlobalReference { name, .. } => Some(name),
+        Constant::BitCast(cast) => aliasee_name(&cast.operand),
+        Constant::AddrSpaceCast(cast) => aliasee_name(&cast.operand),
+        _ => None,
+    }
+}
+
 enum Privacy {
     // Internal privacy means that the item is only available in the module it is defined.
     Internal,
@@ -8,7 +8,7 @@ pub struct Array(pub(super) boolector::Array<Rc<Btor>>);
 
 impl Array {
     pub fn read(&self, addr: &BV) -> BV {
-        BV(self.0.read(&addr.0))
+        BV(self.0.read(&addr.0), false)
     }
 
     pub fn write(&self, addr: &BV, value: &BV) -> Array {
@@ -1,11 +1,37 @@
 #![allow(clippy::len_without_is_empty)]
 use boolector::{BVSolution, Btor};
 use std::{cmp::Ordering, rc::Rc};
+use thiserror::Error;
 
+use super::Array;
 use crate::Solver;
 
-#[derive(Debug, Clone, PartialEq, Eq)]
-pub struct BV(pub(crate) boolector::BV<Rc<Btor>>);
+/// Error returned by the checked operations on [BV], e.g. [BV::try_add].
+#[derive(Debug, Error, PartialEq, Eq)]
+pub enum BVError {
+    /// The operands have different bit widths.
+    #[error("Bit widths {0} and {1} do not match")]
+    WidthMismatch(u32, u32),
+}
+
+/// Bit-vector symbol.
+///
+/// Apart from the underlying solver bit-vector, each [BV] carries a taint flag. Values derived from
+/// a tainted [BV] through the operations on [BV] are also tainted, so it is possible to tell if
+/// e.g. a return value depends on a tainted input. Memory keeps the taint of the bytes written to
+/// it, so a value read from memory is tainted if any of the bytes it is read from are, see
+/// [memory](crate::memory).
+#[derive(Debug, Clone)]
+pub struct BV(pub(crate) boolector::BV<Rc<Btor>>, pub(crate) bool);
+
+impl PartialEq for BV {
+    fn eq(&self, other: &Self) -> bool {
+        // The taint is only metadata, two [BV]s are equal if they refer to the same node.
+        self.0 == other.0
+    }
+}
+
+impl Eq for BV {}
 
 impl BV {
     /// Returns the bit width of the [BV].
@@ -14,20 +40,28 @@ impl BV {
     }
 
     /// Zero-extend the current [BV] to the passed bit width and return the resulting [BV].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is smaller than the current bit width.
     pub fn zero_ext(&self, width: u32) -> BV {
         match self.len().cmp(&width) {
-            Ordering::Less => BV(self.0.uext(width - self.len())),
+            Ordering::Less => BV(self.0.uext(width - self.len()), self.1),
             Ordering::Equal => self.clone(),
-            Ordering::Greater => todo!(),
+            Ordering::Greater => panic!("cannot zero extend {} bits to {width}", self.len()),
         }
     }
 
     /// Sign-extend the current [BV] to the passed bit width and return the resulting [BV].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `width` is smaller than the current bit width.
     pub fn sign_ext(&self, width: u32) -> BV {
         match self.len().cmp(&width) {
-            Ordering::Less => BV(self.0.sext(width - self.len())),
+            Ordering::Less => BV(self.0.sext(width - self.len()), self.1),
             Ordering::Equal => self.clone(),
-            Ordering::Greater => todo!(),
+            Ordering::Greater => panic!("cannot sign extend {} bits to {width}", self.len()),
         }
     }
 
@@ -46,74 +80,85 @@ impl BV {
         }
     }
 
+    /// Resize the current [BV] to the passed bit width and return the resulting [BV].
+    ///
+    /// Same as [BV::resize_unsigned], except that smaller symbols are sign extended.
+    pub fn resize_signed(self, width: u32) -> BV {
+        match self.len().cmp(&width) {
+            Ordering::Equal => self,
+            Ordering::Less => self.sign_ext(width),
+            Ordering::Greater => self.slice(0, width - 1),
+        }
+    }
+
     /// [BV] equality check. Both [BV]s must have the same bit width, the result is returned as a
     /// [BV] of width `1`.
     pub fn eq(&self, other: &BV) -> BV {
         assert_eq!(self.len(), other.len());
-        BV(self.0._eq(&other.0))
+        BV(self.0._eq(&other.0), self.1 || other.1)
     }
 
     /// [BV] inequality check. Both [BV]s must have the same bit width, the result is returned as a
     /// [BV] of width `1`.
     pub fn ne(&self, other: &BV) -> BV {
         assert_eq!(self.len(), other.len());
-        BV(self.0._ne(&other.0))
+        BV(self.0._ne(&other.0), self.1 || other.1)
     }
 
     /// [BV] unsigned greater than. Both [BV]s must have the same bit width, the result is returned
     /// as a [BV] of width `1`.
     pub fn ugt(&self, other: &BV) -> BV {
         assert_eq!(self.len(), other.len());
-        BV(self.0.ugt(&other.0))
+        BV(self.0.ugt(&other.0), self.1 || other.1)
     }
 
     /// [BV] unsigned greater than or equal. Both [BV]s must have the same bit width, the result is
     /// returned as a [BV] of width `1`.
     pub fn ugte(&self, other: &BV) -> BV {
         assert_eq!(self.len(), other.len());
-        BV(self.0.ugte(&other.0))
+        BV(self.0.ugte(&other.0), self.1 || other.1)
     }
 
     /// [BV] unsigned less than. Both [BV]s must have the same bit width, the result is returned as
     /// a [BV] of width `1`.
     pub fn ult(&self, other: &BV) -> BV {
         assert_eq!(self.len(), other.len());
-        BV(self.0.ult(&other.0))
+        BV(self.0.ult(&other.0), self.1 || other.1)
     }
 
     /// [BV] unsigned less than or equal. Both [BV]s must have the same bit width, the result is
     /// returned as a [BV] of width `1`.
     pub fn ulte(&self, other: &BV) -> BV {
         assert_eq!(self.len(), other.len());
-        BV(self.0.ulte(&other.0))
+        BV(self.0.ulte(&other.0), self.1 || other.1)
     }
 
     /// [BV] signed greater than. Both [BV]s must have the same bit width, the result is returned as
     /// a [BV] of width `1`.
     pub fn sgt(&self, other: &BV) -> BV {
         assert_eq!(self.len(), other.len());
-        BV(self.0.sgt(&other.0))
+        BV(self.0.sgt(&other.0), self.1 || other.1)
     }
 
     /// [BV] signed greater or equal than. Both [BV]s must have the same bit width, the result is
     /// returned as a [BV] of width `1`.
     pub fn sgte(&self, other: &BV) -> BV {
         assert_eq!(self.len(), other.len());
-        BV(self.0.sgte(&other.0))
+        BV(self.0.sgte(&other.0), self.1 || other.1)
     }
 
     /// [BV] signed less than. Both [BV]s must have the same bit width, the result is returned as a
     /// [BV] of width `1`.
     pub fn slt(&self, other: &BV) -> BV {
         assert_eq!(self.len(), other.len());
-        BV(self.0.slt(&other.0))
+        BV(self.0.slt(&other.0), self.1 || other.1)
     }
 
     /// [BV] signed less than or equal. Both [BV]s must have the same bit width, the result is
     /// returned as a [BV] of width `1`.
     pub fn slte(&self, other: &BV) -> BV {
         assert_eq!(self.len(), other.len());
-        BV(self.0.slte(&other.0))
+        BV(self.0.slte(&other.0), self.1 || other.1)
     }
 
     // ---------------------------------------------------------------------------------------------
@@ -122,37 +167,37 @@ impl BV {
 
     pub fn add(&self, other: &BV) -> BV {
         assert_eq!(self.len(), other.len());
-        BV(self.0.add(&other.0))
+        BV(self.0.add(&other.0), self.1 || other.1)
     }
 
     pub fn sub(&self, other: &BV) -> BV {
         assert_eq!(self.len(), other.len());
-        BV(self.0.sub(&other.0))
+        BV(self.0.sub(&other.0), self.1 || other.1)
     }
 
     pub fn mul(&self, other: &BV) -> BV {
         assert_eq!(self.len(), other.len());
-        BV(self.0.mul(&other.0))
+        BV(self.0.mul(&other.0), self.1 || other.1)
     }
 
     pub fn udiv(&self, other: &BV) -> BV {
         assert_eq!(self.len(), other.len());
-        BV(self.0.udiv(&other.0))
+        BV(self.0.udiv(&other.0), self.1 || other.1)
     }
 
     pub fn sdiv(&self, other: &BV) -> BV {
         assert_eq!(self.len(), other.len());
-        BV(self.0.sdiv(&other.0))
+        BV(self.0.sdiv(&other.0), self.1 || other.1)
     }
 
     pub fn urem(&self, other: &BV) -> BV {
         assert_eq!(self.len(), other.len());
-        BV(self.0.urem(&other.0))
+        BV(self.0.urem(&other.0), self.1 || other.1)
     }
 
     pub fn srem(&self, other: &BV) -> BV {
         assert_eq!(self.len(), other.len());
-        BV(self.0.srem(&other.0))
+        BV(self.0.srem(&other.0), self.1 || other.1)
     }
 
     // ---------------------------------------------------------------------------------------------
@@ -161,32 +206,32 @@ impl BV {
 
     pub fn uaddo(&self, other: &BV) -> BV {
         assert_eq!(self.len(), other.len());
-        BV(self.0.uaddo(&other.0))
+        BV(self.0.uaddo(&other.0), self.1 || other.1)
     }
 
     pub fn saddo(&self, other: &BV) -> BV {
         assert_eq!(self.len(), other.len());
-        BV(self.0.saddo(&other.0))
+        BV(self.0.saddo(&other.0), self.1 || other.1)
     }
 
     pub fn usubo(&self, other: &BV) -> BV {
         assert_eq!(self.len(), other.len());
-        BV(self.0.usubo(&other.0))
+        BV(self.0.usubo(&other.0), self.1 || other.1)
     }
 
     pub fn ssubo(&self, other: &BV) -> BV {
         assert_eq!(self.len(), other.len());
-        BV(self.0.ssubo(&other.0))
+        BV(self.0.ssubo(&other.0), self.1 || other.1)
     }
 
     pub fn umulo(&self, other: &BV) -> BV {
         assert_eq!(self.len(), other.len());
-        BV(self.0.umulo(&other.0))
+        BV(self.0.umulo(&other.0), self.1 || other.1)
     }
 
     pub fn smulo(&self, other: &BV) -> BV {
         assert_eq!(self.len(), other.len());
-        BV(self.0.smulo(&other.0))
+        BV(self.0.smulo(&other.0), self.1 || other.1)
     }
 
     // ---------------------------------------------------------------------------------------------
@@ -228,10 +273,7 @@ impl BV {
         let min = solver.bv_signed_min(width);
         let max = solver.bv_signed_max(width);
 
-        // Check the sign bit.
-        let is_negative = self.slice(self.len() - 1, self.len() - 1);
-
-        overflow.ite(&is_negative.ite(&min, &max), &result)
+        overflow.ite(&self.sign_bit().ite(&min, &max), &result)
     }
 
     // ---------------------------------------------------------------------------------------------
@@ -239,19 +281,33 @@ impl BV {
     // ---------------------------------------------------------------------------------------------
 
     pub fn not(&self) -> BV {
-        BV(self.0.not())
+        BV(self.0.not(), self.1)
     }
 
     pub fn and(&self, other: &BV) -> BV {
-        BV(self.0.and(&other.0))
+        BV(self.0.and(&other.0), self.1 || other.1)
     }
 
     pub fn or(&self, other: &BV) -> BV {
-        BV(self.0.or(&other.0))
+        BV(self.0.or(&other.0), self.1 || other.1)
     }
 
     pub fn xor(&self, other: &BV) -> BV {
-        BV(self.0.xor(&other.0))
+        BV(self.0.xor(&other.0), self.1 || other.1)
+    }
+
+    // ---------------------------------------------------------------------------------------------
+    // Reductions
+    // ---------------------------------------------------------------------------------------------
+
+    /// Or-reduction, returns a one bit `BV` that is set if any bit is set.
+    pub fn redor(&self) -> BV {
+        BV(self.0.redor(), self.1)
+    }
+
+    /// And-reduction, returns a one bit `BV` that is set if all bits are set.
+    pub fn redand(&self) -> BV {
+        BV(self.0.redand(), self.1)
     }
 
     // ---------------------------------------------------------------------------------------------
@@ -260,17 +316,17 @@ impl BV {
 
     /// Shift left logical
     pub fn sll(&self, other: &BV) -> BV {
-        BV(self.0.sll(&other.0))
+        BV(self.0.sll(&other.0), self.1 || other.1)
     }
 
     /// Shift right logical
     pub fn srl(&self, other: &BV) -> BV {
-        BV(self.0.srl(&other.0))
+        BV(self.0.srl(&other.0), self.1 || other.1)
     }
 
     /// Shift right arithmetic
     pub fn sra(&self, other: &BV) -> BV {
-        BV(self.0.sra(&other.0))
+        BV(self.0.sra(&other.0), self.1 || other.1)
     }
 
     // ---------------------------------------------------------------------------------------------
@@ -278,13 +334,43 @@ impl BV {
     // ---------------------------------------------------------------------------------------------
 
     pub fn concat(&self, other: &BV) -> BV {
-        BV(self.0.concat(&other.0))
+        BV(self.0.concat(&other.0), self.1 || other.1)
+    }
+
+    /// Concatenate all `parts` into a single `BV`.
+    ///
+    /// The first part becomes the least significant bits, and the last part the most significant
+    /// bits. This is the same order that vector and array elements are laid out in, so
+    /// `concat_all(&[a, b])` is the same as `b.concat(&a)`.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `parts` is empty.
+    pub fn concat_all(parts: &[BV]) -> BV {
+        let (first, rest) = parts.split_first().expect("cannot concatenate zero parts");
+        rest.iter()
+            .fold(first.clone(), |acc, part| part.concat(&acc))
+    }
+
+    /// Concatenate `count` copies of the value, the result is `count * self.len()` bits wide.
+    ///
+    /// # Panics
+    ///
+    /// Will panic if `count` is zero.
+    pub fn repeat(&self, count: u32) -> BV {
+        assert!(count > 0, "cannot repeat zero times");
+        (1..count).fold(self.clone(), |acc, _| acc.concat(self))
     }
 
     pub fn slice(&self, low: u32, high: u32) -> BV {
         assert!(low <= high);
         assert!(high <= self.len());
-        BV(self.0.slice(high, low))
+        BV(self.0.slice(high, low), self.1)
+    }
+
+    /// Returns the most significant bit as a one bit `BV`, which is set if the value is negative.
+    pub fn sign_bit(&self) -> BV {
+        self.slice(self.len() - 1, self.len() - 1)
     }
 
     /// Replaces part of the BV starting at `start_idx` with `replace_with`
@@ -319,19 +405,287 @@ impl BV {
 
     pub fn ite(&self, then_bv: &BV, else_bv: &BV) -> BV {
         assert_eq!(self.len(), 1);
-        BV(self.0.cond_bv(&then_bv.0, &else_bv.0))
+        BV(
+            self.0.cond_bv(&then_bv.0, &else_bv.0),
+            self.1 || then_bv.1 || else_bv.1,
+        )
+    }
+
+    /// Returns `then_array` if `self` is true, otherwise `else_array`.
+    ///
+    /// `self` must be a single bit.
+    pub fn ite_array(&self, then_array: &Array, else_array: &Array) -> Array {
+        assert_eq!(self.len(), 1);
+        Array(self.0.cond_array(&then_array.0, &else_array.0))
+    }
+
+    // ---------------------------------------------------------------------------------------------
+    // Taint
+    // ---------------------------------------------------------------------------------------------
+
+    /// Returns `true` if the [BV] is tainted, i.e. it is derived from a tainted value.
+    pub fn is_tainted(&self) -> bool {
+        self.1
+    }
+
+    /// Returns a tainted copy of the [BV].
+    ///
+    /// All values derived from the returned [BV] will also be tainted.
+    pub fn taint(&self) -> BV {
+        BV(self.0.clone(), true)
     }
 
     // ---------------------------------------------------------------------------------------------
     // Misc
     // ---------------------------------------------------------------------------------------------
 
+    /// Returns the solver the [BV] belongs to.
+    ///
+    /// The returned solver does not know which names [Solver::bv_fresh] has handed out, so it
+    /// should only be used to create constants.
     pub fn get_solver(&self) -> Solver {
         let btor = self.0.get_btor();
-        Solver(btor)
+        Solver(
+            btor,
+            Default::default(),
+            Default::default(),
+            Default::default(),
+            Default::default(),
+        )
     }
 
     pub(super) fn get_solution(&self) -> BVSolution {
         self.0.get_a_solution()
     }
+
+    /// Returns the bit pattern of the [BV] with the most significant bit first, if it is constant.
+    ///
+    /// Symbolic values return `None`. Intended for debugging, to get a value from a symbolic [BV]
+    /// use the [Solver] instead.
+    pub fn to_string_binary(&self) -> Option<String> {
+        self.0.as_binary_str()
+    }
+
+    /// Returns the value of the [BV] if it is constant and at most 64 bits wide.
+    pub fn as_concrete(&self) -> Option<u64> {
+        self.0.as_u64()
+    }
+
+    /// Returns [BVError::WidthMismatch] if `self` and `other` have different bit widths.
+    pub fn check_same_width(&self, other: &BV) -> Result<(), BVError> {
+        if self.len() == other.len() {
+            Ok(())
+        } else {
+            Err(BVError::WidthMismatch(self.len(), other.len()))
+        }
+    }
+}
+
+/// Generates checked variants of binary operations on [BV].
+///
+/// The checked variants return [BVError::WidthMismatch] if the operands have different bit widths,
+/// instead of panicking. Use these where the widths come from the analyzed code, and the unchecked
+/// variants where the widths are known to match.
+macro_rules! checked_binary_ops {
+    ($($checked:ident => $op:ident),* $(,)?) => {
+        impl BV {
+            $(
+                #[doc = concat!("Same as [BV::", stringify!($op), "], but returns an error if the ")]
+                #[doc = "bit widths do not match."]
+                pub fn $checked(&self, other: &BV) -> Result<BV, BVError> {
+                    self.check_same_width(other)?;
+                    Ok(self.$op(other))
+                }
+            )*
+        }
+    };
+}
+
+checked_binary_ops! {
+    try_eq => eq,
+    try_ne => ne,
+    try_ugt => ugt,
+    try_ugte => ugte,
+    try_ult => ult,
+    try_ulte => ulte,
+    try_sgt => sgt,
+    try_sgte => sgte,
+    try_slt => slt,
+    try_slte => slte,
+    try_add => add,
+    try_sub => sub,
+    try_mul => mul,
+    try_udiv => udiv,
+    try_sdiv => sdiv,
+    try_urem => urem,
+    try_srem => srem,
+    try_uaddo => uaddo,
+    try_saddo => saddo,
+    try_usubo => usubo,
+    try_ssubo => ssubo,
+    try_umulo => umulo,
+    try_smulo => smulo,
+    try_uadds => uadds,
+    try_sadds => sadds,
+    try_and => and,
+    try_or => or,
+    try_xor => xor,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::BVError;
+    use crate::{Solver, BV};
+
+    #[test]
+    fn checked_ops_width_mismatch() {
+        let solver = Solver::new();
+        let a = solver.bv_from_u64(1, 8);
+        let b = solver.bv_from_u64(1, 16);
+
+        assert_eq!(a.try_add(&b), Err(BVError::WidthMismatch(8, 16)));
+        assert_eq!(b.try_sub(&a), Err(BVError::WidthMismatch(16, 8)));
+        assert_eq!(a.try_eq(&b), Err(BVError::WidthMismatch(8, 16)));
+        assert_eq!(a.try_ult(&b), Err(BVError::WidthMismatch(8, 16)));
+        assert_eq!(a.try_xor(&b), Err(BVError::WidthMismatch(8, 16)));
+    }
+
+    #[test]
+    fn checked_ops_same_width() {
+        let solver = Solver::new();
+        let a = solver.bv_from_u64(3, 8);
+        let b = solver.bv_from_u64(2, 8);
+
+        let sum = a.try_add(&b).unwrap();
+        assert_eq!(sum.to_string_binary(), Some("00000101".to_owned()));
+        let lt = b.try_ult(&a).unwrap();
+        assert_eq!(lt.to_string_binary(), Some("1".to_owned()));
+    }
+
+    #[test]
+    fn to_string_binary_constant() {
+        let solver = Solver::new();
+        let bv = solver.bv_from_u64(0b1010_0101, 8);
+        assert_eq!(bv.to_string_binary(), Some("10100101".to_owned()));
+    }
+
+    #[test]
+    fn to_string_binary_keeps_leading_zeros() {
+        let solver = Solver::new();
+        let bv = solver.bv_from_u64(1, 12);
+        assert_eq!(bv.to_string_binary(), Some("000000000001".to_owned()));
+    }
+
+    #[test]
+    fn to_string_binary_symbolic() {
+        let solver = Solver::new();
+        let bv = solver.bv(8, "x");
+        assert_eq!(bv.to_string_binary(), None);
+    }
+
+    #[test]
+    fn resize_unsigned() {
+        let solver = Solver::new();
+        let bv = solver.bv_from_u64(0x80, 8);
+        assert_eq!(
+            bv.clone().resize_unsigned(16).to_string_binary(),
+            Some("0000000010000000".to_owned())
+        );
+        assert_eq!(bv.clone().resize_unsigned(8), bv);
+        assert_eq!(
+            bv.resize_unsigned(4).to_string_binary(),
+            Some("0000".to_owned())
+        );
+    }
+
+    #[test]
+    fn resize_signed() {
+        let solver = Solver::new();
+        let bv = solver.bv_from_u64(0x80, 8);
+        assert_eq!(
+            bv.clone().resize_signed(16).to_string_binary(),
+            Some("1111111110000000".to_owned())
+        );
+        assert_eq!(bv.clone().resize_signed(8), bv);
+        assert_eq!(
+            bv.resize_signed(4).to_string_binary(),
+            Some("0000".to_owned())
+        );
+    }
+
+    #[test]
+    fn concat_all_order() {
+        let solver = Solver::new();
+        let parts = [
+            solver.bv_from_u64(0x12, 8),
+            solver.bv_from_u64(0x34, 8),
+            solver.bv_from_u64(0x5, 4),
+        ];
+        let value = BV::concat_all(&parts);
+        assert_eq!(value.len(), 20);
+        assert_eq!(value.as_concrete(), Some(0x5_34_12));
+
+        // The first part is in the lowest bits.
+        assert_eq!(value.slice(0, 7).as_concrete(), Some(0x12));
+        assert_eq!(value.slice(8, 15).as_concrete(), Some(0x34));
+        assert_eq!(value.slice(16, 19).as_concrete(), Some(0x5));
+    }
+
+    #[test]
+    fn concat_all_single() {
+        let solver = Solver::new();
+        let part = solver.bv(8, "x");
+        assert_eq!(BV::concat_all(&[part.clone()]), part);
+    }
+
+    #[test]
+    fn repeat_byte() {
+        let solver = Solver::new();
+        let byte = solver.bv_from_u64(0xab, 8);
+        let value = byte.repeat(4);
+        assert_eq!(value.len(), 32);
+        assert_eq!(value.as_concrete(), Some(0xabab_abab));
+    }
+
+    #[test]
+    fn repeat_once() {
+        let solver = Solver::new();
+        let value = solver.bv(8, "x");
+        assert_eq!(value.repeat(1), value);
+    }
+
+    #[test]
+    fn sign_bit_constant() {
+        let solver = Solver::new();
+        let positive = solver.bv_from_u64(0x7f, 8);
+        let negative = solver.bv_from_u64(0x80, 8);
+        let all_ones = solver.bv_from_u64(u64::MAX, 64);
+        let bit = solver.bv_from_u64(1, 1);
+        assert_eq!(positive.sign_bit().to_string_binary(), Some("0".to_owned()));
+        assert_eq!(negative.sign_bit().to_string_binary(), Some("1".to_owned()));
+        assert_eq!(all_ones.sign_bit().to_string_binary(), Some("1".to_owned()));
+        assert_eq!(bit.sign_bit().to_string_binary(), Some("1".to_owned()));
+    }
+
+    #[test]
+    fn redor_constant() {
+        let solver = Solver::new();
+        let zero = solver.bv_from_u64(0, 8);
+        let some = solver.bv_from_u64(0b0001_0000, 8);
+        let ones = solver.bv_from_u64(0xff, 8);
+        assert_eq!(zero.redor().to_string_binary(), Some("0".to_owned()));
+        assert_eq!(some.redor().to_string_binary(), Some("1".to_owned()));
+        assert_eq!(ones.redor().to_string_binary(), Some("1".to_owned()));
+    }
+
+    #[test]
+    fn redand_constant() {
+        let solver = Solver::new();
+        let zero = solver.bv_from_u64(0, 8);
+        let some = solver.bv_from_u64(0b0111_1111, 8);
+        let ones = solver.bv_from_u64(0xff, 8);
+        assert_eq!(zero.redand().to_string_binary(), Some("0".to_owned()));
+        assert_eq!(some.redand().to_string_binary(), Some("0".to_owned()));
+        assert_eq!(ones.redand().to_string_binary(), Some("1".to_owned()));
+    }
 }
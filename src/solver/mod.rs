@@ -1,8 +1,14 @@
 use boolector::{
     option::{BtorOption, ModelGen, NumberFormat, RewriteLevel},
-    BVSolution, Btor, SolverResult,
+    BVSolution, Btor, SolverResult as BtorResult,
+};
+use llvm_ir::module::Endianness;
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    rc::Rc,
+    time::Duration,
 };
-use std::{collections::HashMap, rc::Rc};
 use thiserror::Error;
 
 mod array;
@@ -10,12 +16,37 @@ mod bv;
 
 use crate::VMError;
 
-pub use self::{array::Array, bv::BV};
+pub use self::{
+    array::Array,
+    bv::{BVError, BV},
+};
 
 #[derive(Debug, Error, PartialEq, Eq)]
 pub enum SolverError {
     #[error("Solver state unknown")]
     Unknown,
+
+    /// The constraints have no solutions.
+    #[error("Constraints are unsatisfiable")]
+    Unsat,
+
+    /// The query was stopped after reaching the timeout set by [Solver::set_query_timeout].
+    #[error("Solver query timed out")]
+    Timeout,
+}
+
+/// Result of a satisfiability check, see [Solver::check_with_constraint].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SolverResult {
+    /// The constraints are satisfiable.
+    Sat,
+
+    /// The constraints are unsatisfiable.
+    Unsat,
+
+    /// The solver could not decide, e.g. because the query reached the timeout set by
+    /// [Solver::set_query_timeout].
+    Unknown,
 }
 
 #[derive(Debug)]
@@ -30,6 +61,32 @@ pub enum Solutions {
     AtLeast(Vec<BVSolution>),
 }
 
+/// Convert a solution to bytes in the byte order `endianness`.
+///
+/// Solutions can be wider than 64 bits, e.g. for buffers. If the width is not a multiple of bytes
+/// the most significant byte is zero extended. Bits that can take any value are set to zero.
+pub fn solution_to_bytes(solution: &BVSolution, endianness: Endianness) -> Vec<u8> {
+    let solution = solution.disambiguate();
+    let binary_str = solution.as_01x_str();
+
+    let padding = (8 - binary_str.len() % 8) % 8;
+    let bits: Vec<u8> = std::iter::repeat(0)
+        .take(padding)
+        .chain(binary_str.bytes().map(|bit| (bit == b'1') as u8))
+        .collect();
+
+    // The binary string starts with the most significant bit, so the bytes are big endian.
+    let mut bytes: Vec<u8> = bits
+        .chunks(8)
+        .map(|byte| byte.iter().fold(0, |acc, bit| acc << 1 | bit))
+        .collect();
+
+    if let Endianness::LittleEndian = endianness {
+        bytes.reverse();
+    }
+    bytes
+}
+
 /// Helper used to generate solutions for symbols.
 ///
 /// Solutions can be generated using methods on the solver. Using this instead provides a cache
@@ -71,7 +128,7 @@ impl SolutionGenerator {
         }
 
         // Setup before checking for solutions.
-        if !self.solver.is_sat()? {
+        if !self.solver.check_sat()? {
             return Err(VMError::Unsat);
         }
 
@@ -85,8 +142,99 @@ impl SolutionGenerator {
     }
 }
 
+/// Number of times each name has been used by [Solver::bv_fresh].
+type SymbolNames = Rc<RefCell<HashMap<String, usize>>>;
+
+/// Constants created by [Solver::bv_from_u64] and [Solver::bv_zero], by value and width.
+///
+/// Terms in boolector are immutable and structurally equal terms are shared, so handing out the
+/// same term for a constant is the same as creating it again. The cache only avoids creating new
+/// references to the term for frequently used constants, such as zero and pointer sizes.
+type Constants = Rc<RefCell<HashMap<(u64, u32), BV>>>;
+
+/// Maximum number of constants kept in [Constants], later constants are created each time.
+const MAX_CONSTANTS: usize = 4096;
+
+/// Counts of satisfiability checks, see [Solver::stats].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct SolverStats {
+    /// Number of times satisfiability was requested.
+    pub queries: usize,
+
+    /// Number of the queries that had to be solved by the underlying solver.
+    pub checks: usize,
+}
+
+/// Cached result of the last satisfiability check of the solver state.
+///
+/// Adding constraints cannot make an unsatisfiable state satisfiable, and removing constraints
+/// cannot make a satisfiable state unsatisfiable. So the cached result is only cleared when the
+/// constraints change in a way that can change the result.
+#[derive(Debug, Default)]
+struct SatCache {
+    /// Result for the current constraints without any assumptions, if known.
+    sat: Cell<Option<bool>>,
+
+    /// Constraints known to be satisfiable together with the current constraints.
+    sat_with: RefCell<Vec<BV>>,
+
+    stats: Cell<SolverStats>,
+
+    /// Timeout for each query, see [Solver::set_query_timeout].
+    timeout: Cell<Option<Duration>>,
+}
+
+impl SatCache {
+    /// Called when the constraints may have changed so the results are no longer known.
+    fn invalidate(&self, sat: Option<bool>) {
+        self.sat.set(sat);
+        self.sat_with.borrow_mut().clear();
+    }
+}
+
+/// A context level of the solver, see [Solver::push].
 #[derive(Debug, Clone)]
-pub struct Solver(pub(crate) Rc<Btor>);
+struct Level {
+    /// Unique id of the level, used to find which levels two [SolverSnapshot]s have in common.
+    id: usize,
+
+    /// Constraints asserted while this was the top level.
+    asserted: Vec<BV>,
+}
+
+/// Context levels of the solver, with the constraints asserted in each.
+#[derive(Debug, Clone)]
+struct Levels {
+    levels: Vec<Level>,
+
+    next_id: usize,
+}
+
+impl Default for Levels {
+    fn default() -> Self {
+        // The base level cannot be popped.
+        Self {
+            levels: vec![Level {
+                id: 0,
+                asserted: Vec::new(),
+            }],
+            next_id: 1,
+        }
+    }
+}
+
+/// The asserted constraints of a [Solver] at some point, see [Solver::snapshot].
+#[derive(Debug, Clone)]
+pub struct SolverSnapshot(Vec<Level>);
+
+#[derive(Debug, Clone)]
+pub struct Solver(
+    pub(crate) Rc<Btor>,
+    SymbolNames,
+    Rc<SatCache>,
+    Rc<RefCell<Levels>>,
+    Constants,
+);
 
 impl Default for Solver {
     fn default() -> Self {
@@ -112,41 +260,129 @@ impl Solver {
         btor.set_opt(BtorOption::Normalize(true));
         btor.set_opt(BtorOption::NormalizeAdd(true));
 
-        Self(Rc::new(btor))
+        Self(
+            Rc::new(btor),
+            SymbolNames::default(),
+            Rc::default(),
+            Rc::default(),
+            Constants::default(),
+        )
     }
 
     /// A regular clone will only clone the reference, a duplication will instead create a new
     /// instance of the underlying solver.
     pub fn duplicate(&self) -> Self {
-        Self(Rc::new(self.0.duplicate()))
+        let names = self.1.borrow().clone();
+        let cache = SatCache {
+            sat: Cell::new(self.2.sat.get()),
+            sat_with: RefCell::new(Vec::new()),
+            stats: Cell::new(self.2.stats.get()),
+            timeout: Cell::new(self.2.timeout.get()),
+        };
+        let levels = self.3.borrow().clone();
+        let solver = Self(
+            Rc::new(self.0.duplicate()),
+            Rc::new(RefCell::new(names)),
+            Rc::new(cache),
+            Rc::new(RefCell::new(levels)),
+            // Terms belong to a single boolector instance, so constants cannot be shared.
+            Constants::default(),
+        );
+        solver.set_query_timeout(self.2.timeout.get());
+        solver
+    }
+
+    /// Limit the time the underlying solver may spend on each query, or `None` to remove the
+    /// timeout.
+    ///
+    /// Queries that reach the timeout return [SolverError::Timeout].
+    pub fn set_query_timeout(&self, timeout: Option<Duration>) {
+        self.0.set_opt(BtorOption::SolverTimeout(timeout));
+        self.2.timeout.set(timeout);
+    }
+
+    /// Returns how many satisfiability checks have been requested, and how many of those had to be
+    /// solved by the underlying solver.
+    pub fn stats(&self) -> SolverStats {
+        self.2.stats.get()
     }
 
     /// Solve for the current solver state, and returns if the result is satisfiable.
     ///
     /// All asserts and assumes are implicitly combined with a boolean and. Returns true or false,
-    /// and [SolverError::Unknown] if the result cannot be determined.
+    /// and [SolverError::Unknown] if the result cannot be determined, or
+    /// [SolverError::Timeout] if the query reached the timeout set by
+    /// [Solver::set_query_timeout].
+    ///
+    /// The result is cached until the constraints change, so repeated calls are cheap.
     pub fn is_sat(&self) -> Result<bool, SolverError> {
-        let is_sat = self.0.sat();
-        match is_sat {
-            SolverResult::Sat => Ok(true),
-            SolverResult::Unsat => Ok(false),
-            SolverResult::Unknown => Err(SolverError::Unknown),
+        match self.2.sat.get() {
+            Some(sat) => {
+                self.count_query(false);
+                Ok(sat)
+            }
+            None => self.check_sat(),
+        }
+    }
+
+    /// Check if the solver state is satisfiable together with `constraint`.
+    ///
+    /// Unlike [Solver::is_sat_with_constraint], results that cannot be determined are returned as
+    /// [SolverResult::Unknown] instead of as errors.
+    pub fn check_with_constraint(&self, constraint: &BV) -> SolverResult {
+        match self.is_sat_with_constraint(constraint) {
+            Ok(true) => SolverResult::Sat,
+            Ok(false) => SolverResult::Unsat,
+            Err(_) => SolverResult::Unknown,
         }
     }
 
     /// Solve for the solver state with the assumption of the passed constraint.
     pub fn is_sat_with_constraint(&self, constraint: &BV) -> Result<bool, SolverError> {
-        // Assume the constraint, will be forgotten after the next call to `is_sat`.
-        constraint.0.assume();
-        self.is_sat()
+        self.is_sat_with_constraints(&[constraint])
     }
 
     /// Solve for the solver state with the assumption of the passed constraints.
     pub fn is_sat_with_constraints(&self, constraints: &[&BV]) -> Result<bool, SolverError> {
+        // Constant constraints do not need the solver, they either make the state unsatisfiable or
+        // do not change it.
+        let mut symbolic = Vec::new();
         for constraint in constraints {
+            match constraint.0.as_binary_str().as_deref() {
+                Some("0") => {
+                    self.count_query(false);
+                    return Ok(false);
+                }
+                Some(_) => {}
+                None => symbolic.push(*constraint),
+            }
+        }
+        if symbolic.is_empty() {
+            return self.is_sat();
+        }
+
+        // Nothing is satisfiable together with an unsatisfiable state.
+        if self.2.sat.get() == Some(false) {
+            self.count_query(false);
+            return Ok(false);
+        }
+
+        // Assume the constraints, will be forgotten after the next call to `sat`.
+        for constraint in symbolic.iter() {
             constraint.0.assume();
         }
-        self.is_sat()
+        let sat = self.known(self.solve())?;
+        self.count_query(true);
+
+        if sat {
+            // If the state is satisfiable with extra constraints, it is satisfiable without them.
+            self.2.sat.set(Some(true));
+            if let [constraint] = symbolic.as_slice() {
+                self.2.sat_with.borrow_mut().push((*constraint).clone());
+            }
+        }
+
+        Ok(sat)
     }
 
     /// Add the constraint to the solver.
@@ -154,9 +390,64 @@ impl Solver {
     /// The passed constraint will be implicitly combined with the current state in a boolean `and`.
     /// Asserted constraints cannot be removed.
     pub fn assert(&self, bv: &BV) {
+        let cache = &self.2;
+        match bv.0.as_binary_str().as_deref() {
+            // Does not change the constraints.
+            Some("1") => {}
+            Some(_) => cache.invalidate(Some(false)),
+            None => {
+                let known_sat = cache.sat_with.borrow().contains(bv);
+                match cache.sat.get() {
+                    Some(false) => {}
+                    _ if known_sat => cache.invalidate(Some(true)),
+                    _ => cache.invalidate(None),
+                }
+            }
+        }
+
+        if let Some(level) = self.3.borrow_mut().levels.last_mut() {
+            level.asserted.push(bv.clone());
+        }
         bv.0.assert();
     }
 
+    /// Check satisfiability with the underlying solver, without using the cached result.
+    ///
+    /// Used when a model is generated, since that requires the solver to run.
+    fn check_sat(&self) -> Result<bool, SolverError> {
+        let sat = self.known(self.solve())?;
+        self.count_query(true);
+        self.2.invalidate(Some(sat));
+        Ok(sat)
+    }
+
+    fn solve(&self) -> SolverResult {
+        match self.0.sat() {
+            BtorResult::Sat => SolverResult::Sat,
+            BtorResult::Unsat => SolverResult::Unsat,
+            BtorResult::Unknown => SolverResult::Unknown,
+        }
+    }
+
+    /// Returns if `result` is satisfiable, results that are unknown are returned as errors.
+    fn known(&self, result: SolverResult) -> Result<bool, SolverError> {
+        match result {
+            SolverResult::Sat => Ok(true),
+            SolverResult::Unsat => Ok(false),
+            SolverResult::Unknown if self.2.timeout.get().is_some() => Err(SolverError::Timeout),
+            SolverResult::Unknown => Err(SolverError::Unknown),
+        }
+    }
+
+    fn count_query(&self, checked: bool) {
+        let mut stats = self.2.stats.get();
+        stats.queries += 1;
+        if checked {
+            stats.checks += 1;
+        }
+        self.2.stats.set(stats);
+    }
+
     /// Returns `true` if `lhs` and `rhs` must be equal under the current constraints.
     pub fn must_be_equal(&self, lhs: &BV, rhs: &BV) -> Result<bool, SolverError> {
         // Add the constraint lhs != rhs and invert the results. The only way
@@ -196,6 +487,8 @@ impl Solver {
     }
 
     /// Returns the highest value a solution can have for the given bit-vector.
+    ///
+    /// Returns [SolverError::Unsat] if the constraints have no solutions.
     pub fn get_solution_maximum(&self, bv: &BV) -> Result<u64, SolverError> {
         // Check if bit-vector is a constant.
         if let Some(str) = bv.0.as_binary_str() {
@@ -214,11 +507,59 @@ impl Solver {
         result
     }
 
+    /// Returns the lowest value a solution can have for the given bit-vector.
+    ///
+    /// Returns [SolverError::Unsat] if the constraints have no solutions.
+    pub fn get_solution_minimum(&self, bv: &BV) -> Result<u64, SolverError> {
+        // Check if bit-vector is a constant.
+        if let Some(str) = bv.0.as_binary_str() {
+            let solution = u64::from_str_radix(&str, 2).unwrap();
+            return Ok(solution);
+        }
+
+        self.push();
+        self.0.set_opt(BtorOption::ModelGen(ModelGen::All));
+
+        let result = self.internal_get_min_solution(bv);
+
+        // Restore solver to initial state.
+        self.0.set_opt(BtorOption::ModelGen(ModelGen::Disabled));
+        self.pop();
+        result
+    }
+
+    /// Returns a readable dump of a model satisfying the current constraints.
+    ///
+    /// The model contains all symbols and their solution, named symbols are listed by their name.
+    /// This is only intended for diagnostics, returns [VMError::Unsat] if there are no solutions.
+    pub fn model_string(&self) -> Result<String, VMError> {
+        self.push();
+        self.0.set_opt(BtorOption::ModelGen(ModelGen::All));
+
+        let result = match self.check_sat() {
+            Ok(true) => Ok(self.0.print_model()),
+            Ok(false) => Err(VMError::Unsat),
+            Err(e) => Err(e.into()),
+        };
+
+        // Restore solver to initial state.
+        self.0.set_opt(BtorOption::ModelGen(ModelGen::Disabled));
+        self.pop();
+        result
+    }
+
     /// Add a context level to the solver.
     ///
     /// Adding a context level to the solver allows for adding constraints that can be forgotten
     /// later with a call to `pop`.
     pub fn push(&self) {
+        let mut levels = self.3.borrow_mut();
+        let id = levels.next_id;
+        levels.next_id += 1;
+        levels.levels.push(Level {
+            id,
+            asserted: Vec::new(),
+        });
         self.0.push(1)
     }
 
@@ -226,9 +567,61 @@ impl Solver {
     ///
     /// Removing a context level will remove all asserted constraints from that level.
     pub fn pop(&self) {
+        // Removing constraints can only make an unsatisfiable state satisfiable.
+        let sat = self.2.sat.get().filter(|sat| *sat);
+        self.2.invalidate(sat);
+        let mut levels = self.3.borrow_mut();
+        if levels.levels.len() > 1 {
+            levels.levels.pop();
+        }
         self.0.pop(1)
     }
 
+    /// Returns the context levels and the constraints asserted in them.
+    ///
+    /// The solver can later be returned to this state with [Solver::restore].
+    pub fn snapshot(&self) -> SolverSnapshot {
+        SolverSnapshot(self.3.borrow().levels.clone())
+    }
+
+    /// Return the solver to the state in `snapshot`.
+    ///
+    /// Levels that the current state shares with the snapshot are kept, the rest are popped. The
+    /// levels in the snapshot that are missing are then pushed again and their constraints are
+    /// asserted. If the snapshot was taken on the current path this is only a few pops, otherwise
+    /// the constraints of the snapshot are asserted from where the two diverged.
+    pub fn restore(&self, snapshot: &SolverSnapshot) {
+        let common = {
+            let levels = self.3.borrow();
+            levels
+                .levels
+                .iter()
+                .zip(snapshot.0.iter())
+                .take_while(|(current, saved)| {
+                    current.id == saved.id && current.asserted == saved.asserted
+                })
+                .count()
+                // The base level is never popped.
+                .max(1)
+        };
+
+        while self.3.borrow().levels.len() > common {
+            self.pop();
+        }
+
+        for level in snapshot.0.iter().skip(common) {
+            self.push();
+            {
+                // Keep the id so later snapshots can share the level.
+                let mut levels = self.3.borrow_mut();
+                levels.levels.last_mut().unwrap().id = level.id;
+            }
+            for constraint in level.asserted.iter() {
+                self.assert(constraint);
+            }
+        }
+    }
+
     /// Create a new uninitialized bitvector array.
     ///
     /// The array will be indexed with bitvectors of size `index_width`, and each element in the
@@ -244,37 +637,87 @@ impl Solver {
 
     /// Create a new uninitialized bitvector of size `bits`.
     pub fn bv(&self, bits: u32, name: &str) -> BV {
-        BV(boolector::BV::new(self.0.clone(), bits, Some(name)))
+        BV(boolector::BV::new(self.0.clone(), bits, Some(name)), false)
+    }
+
+    /// Returns a unique symbol name based on `name`.
+    ///
+    /// Symbol names must be unique, so if `name` has already been used a counter is appended, e.g.
+    /// `mem_0x1000`, `mem_0x1000_1`, and so on. Since execution is deterministic the same symbol
+    /// gets the same name across runs.
+    ///
+    /// Names follow the scheme `input_<fn>_<index>` for entry function parameters, `mem_<addr>`
+    /// for symbolic memory, `argv_<index>` for symbolic `argv` entries, and `havoc` for
+    /// unconstrained results.
+    pub fn fresh_name(&self, name: &str) -> String {
+        let mut names = self.1.borrow_mut();
+        let count = names.entry(name.to_owned()).or_insert(0);
+        let name = match *count {
+            0 => name.to_owned(),
+            n => format!("{name}_{n}"),
+        };
+        *count += 1;
+        name
+    }
+
+    /// Create a new uninitialized bitvector of size `bits` with a unique name based on `name`.
+    ///
+    /// See [Solver::fresh_name] for how the name is picked.
+    pub fn bv_fresh(&self, bits: u32, name: &str) -> BV {
+        self.bv(bits, &self.fresh_name(name))
     }
 
     /// Create a new unnamed uninitialized bitvector of size `bits`.
     pub fn bv_unnamed(&self, bits: u32) -> BV {
-        BV(boolector::BV::new(self.0.clone(), bits, None))
+        BV(boolector::BV::new(self.0.clone(), bits, None), false)
     }
 
     /// Create a new symbol from a boolean value.
     pub fn bv_from_bool(&self, value: bool) -> BV {
-        BV(boolector::BV::from_bool(self.0.clone(), value))
+        BV(boolector::BV::from_bool(self.0.clone(), value), false)
     }
 
     /// Create a new symbol from an `u64` value of size `bits`.
+    ///
+    /// Constants are interned, so creating the same constant again returns the same term.
     pub fn bv_from_u64(&self, value: u64, bits: u32) -> BV {
-        BV(boolector::BV::from_u64(self.0.clone(), value, bits))
+        self.constant(value, bits, || {
+            boolector::BV::from_u64(self.0.clone(), value, bits)
+        })
     }
 
     /// Create a new symbol set to zero of size `bits.
     pub fn bv_zero(&self, bits: u32) -> BV {
-        BV(boolector::BV::zero(self.0.clone(), bits))
+        self.constant(0, bits, || boolector::BV::zero(self.0.clone(), bits))
+    }
+
+    /// Returns the interned constant `value` of size `bits`, or creates it with `create`.
+    fn constant(
+        &self,
+        value: u64,
+        bits: u32,
+        create: impl FnOnce() -> boolector::BV<Rc<Btor>>,
+    ) -> BV {
+        let mut constants = self.4.borrow_mut();
+        if let Some(constant) = constants.get(&(value, bits)) {
+            return constant.clone();
+        }
+
+        let constant = BV(create(), false);
+        if constants.len() < MAX_CONSTANTS {
+            constants.insert((value, bits), constant.clone());
+        }
+        constant
     }
 
     /// Create a bitvector of size `bits` from a binary string.
     pub fn from_binary_string(&self, bits: &str) -> BV {
-        BV(boolector::BV::from_binary_str(self.0.clone(), bits))
+        BV(boolector::BV::from_binary_str(self.0.clone(), bits), false)
     }
 
     /// Creates a big-vector of size `bits` containing the maximum unsigned value.
     pub fn bv_unsigned_max(&self, bits: u32) -> BV {
-        BV(boolector::BV::ones(self.0.clone(), bits))
+        BV(boolector::BV::ones(self.0.clone(), bits), false)
     }
 
     /// Create a bit-vector of size `bits` containing the maximum signed value.
@@ -284,7 +727,7 @@ impl Solver {
         // Maximum value: 0111...1
         let leading_zero = boolector::BV::zero(self.0.clone(), 1);
         let ones = boolector::BV::ones(self.0.clone(), bits - 1);
-        BV(leading_zero.concat(&ones))
+        BV(leading_zero.concat(&ones), false)
     }
 
     /// Create a bit-vector of size `bits` containing the minimum signed value.
@@ -294,7 +737,7 @@ impl Solver {
         // Minimum value: 1000...0
         let leading_one = boolector::BV::one(self.0.clone(), 1);
         let zeroes = boolector::BV::zero(self.0.clone(), bits - 1);
-        BV(leading_one.concat(&zeroes))
+        BV(leading_one.concat(&zeroes), false)
     }
 
     /// Helper to ensure we always set `ModelGen::Disabled` for all paths in this function.
@@ -304,7 +747,7 @@ impl Solver {
         max_solutions: usize,
     ) -> Result<Solutions, SolverError> {
         let mut solutions = Vec::new();
-        while solutions.len() < max_solutions && self.is_sat()? {
+        while solutions.len() < max_solutions && self.check_sat()? {
             let solution = bv.get_solution().disambiguate();
 
             // Constrain the next value to not be an already found solution.
@@ -317,7 +760,7 @@ impl Solver {
         if solutions.is_empty() {
             Ok(Solutions::None)
         } else {
-            let exists_more_solutions = self.is_sat()?;
+            let exists_more_solutions = self.check_sat()?;
             match exists_more_solutions {
                 false => Ok(Solutions::Exactly(solutions)),
                 true => Ok(Solutions::AtLeast(solutions)),
@@ -328,7 +771,7 @@ impl Solver {
     /// Helper to ensure we always set `ModelGen::Disabled` for all paths in this function.
     fn internal_get_max_solution(&self, bv: &BV) -> Result<u64, SolverError> {
         let mut highest = None;
-        while self.is_sat()? {
+        while self.check_sat()? {
             let solution = bv.get_solution();
             let solution = solution.disambiguate();
 
@@ -340,10 +783,151 @@ impl Solver {
         }
 
         match highest {
-            // None => Ok(Solutions::None),
-            None => panic!("unsat"),
+            None => Err(SolverError::Unsat),
+            Some(value) => Ok(value.as_u64().unwrap()),
+        }
+    }
+
+    /// Helper to ensure we always set `ModelGen::Disabled` for all paths in this function.
+    fn internal_get_min_solution(&self, bv: &BV) -> Result<u64, SolverError> {
+        let mut lowest = None;
+        while self.check_sat()? {
+            let solution = bv.get_solution();
+            let solution = solution.disambiguate();
+
+            // Constrain the next value to be lower than the already found solution.
+            let solution_bv = self.from_binary_string(solution.as_01x_str());
+            lowest = Some(solution);
+
+            self.assert(&bv.ult(&solution_bv));
+        }
+
+        match lowest {
+            None => Err(SolverError::Unsat),
             Some(value) => Ok(value.as_u64().unwrap()),
-            // Some(value) => Ok(Solutions::Exactly(vec![value])),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use llvm_ir::module::Endianness;
+    use std::time::Duration;
+
+    use super::{solution_to_bytes, Solutions, Solver, SolverError, SolverResult, BV};
+    use crate::VMError;
+
+    #[test]
+    fn solution_to_bytes_wide() {
+        let solver = Solver::new();
+        let buffer = solver.bv(128, "buffer");
+        let pattern = solver.from_binary_string(&format!(
+            "{:0128b}",
+            0x00112233_44556677_8899aabb_ccddeeffu128
+        ));
+        solver.assert(&buffer.eq(&pattern));
+
+        let solution = match solver.get_solutions_for_bv(&buffer, 1).unwrap() {
+            Solutions::Exactly(solutions) => solutions[0].clone(),
+            solutions => panic!("expected one solution, got {solutions:?}"),
+        };
+
+        let big_endian: Vec<u8> = (0..16).map(|i| i * 0x11).collect();
+        assert_eq!(
+            solution_to_bytes(&solution, Endianness::BigEndian),
+            big_endian
+        );
+
+        let mut little_endian = big_endian;
+        little_endian.reverse();
+        assert_eq!(
+            solution_to_bytes(&solution, Endianness::LittleEndian),
+            little_endian
+        );
+    }
+
+    /// Returns a constraint that requires factoring a product of two 31-bit primes, which cannot
+    /// be decided by rewriting alone and always reaches the SAT solver.
+    fn factor_constraint(solver: &Solver) -> BV {
+        let x = solver.bv(64, "x");
+        let y = solver.bv(64, "y");
+        let one = solver.bv_from_u64(1, 64);
+        let max = solver.bv_from_u64(u32::MAX as u64, 64);
+        let product = solver.bv_from_u64(2147483647 * 2147483629, 64);
+        x.ugt(&one)
+            .and(&y.ugt(&one))
+            .and(&x.ulte(&max))
+            .and(&y.ulte(&max))
+            .and(&x.mul(&y).eq(&product))
+    }
+
+    #[test]
+    fn query_timeout() {
+        let solver = Solver::new();
+        solver.assert(&factor_constraint(&solver));
+
+        // A zero timeout has always expired once the SAT solver runs, so the result does not
+        // depend on how fast the machine is.
+        solver.set_query_timeout(Some(Duration::ZERO));
+        let result = solver.is_sat();
+        assert_eq!(result, Err(SolverError::Timeout));
+
+        let error: VMError = result.unwrap_err().into();
+        assert_eq!(error, VMError::SolverTimeout);
+    }
+
+    #[test]
+    fn check_with_constraint_unknown() {
+        let solver = Solver::new();
+        let constraint = factor_constraint(&solver);
+
+        solver.set_query_timeout(Some(Duration::ZERO));
+        assert_eq!(
+            solver.check_with_constraint(&constraint),
+            SolverResult::Unknown
+        );
+
+        // Easy queries are still decided.
+        let unsat = solver.bv_from_bool(false);
+        assert_eq!(solver.check_with_constraint(&unsat), SolverResult::Unsat);
+    }
+
+    #[test]
+    fn min_max_unsat() {
+        let solver = Solver::new();
+        let x = solver.bv(32, "x");
+        let ten = solver.bv_from_u64(10, 32);
+        solver.assert(&x.ugt(&ten));
+        solver.assert(&x.ult(&ten));
+
+        assert_eq!(solver.get_solution_minimum(&x), Err(SolverError::Unsat));
+        assert_eq!(solver.get_solution_maximum(&x), Err(SolverError::Unsat));
+
+        let error: VMError = SolverError::Unsat.into();
+        assert_eq!(error, VMError::Unsat);
+    }
+
+    #[test]
+    fn constants_interned() {
+        let solver = Solver::new();
+        let zero = solver.bv_from_u64(0, 64);
+        assert_eq!(solver.bv_from_u64(0, 64), zero);
+        assert_eq!(solver.bv_zero(64), zero);
+        assert_eq!(zero.0.get_id(), solver.bv_from_u64(0, 64).0.get_id());
+
+        // Constants of other values or widths are different.
+        assert_eq!(solver.bv_from_u64(0, 32).len(), 32);
+        assert_eq!(solver.bv_from_u64(1, 64).as_concrete(), Some(1));
+
+        // Results are the same as with separately created constants.
+        let x = solver.bv(64, "x");
+        let one = solver.bv_from_u64(1, 64);
+        solver.assert(&x.add(&one).eq(&zero));
+        let max = solver.bv_unsigned_max(64);
+        assert_eq!(solver.must_be_equal(&x, &max), Ok(true));
+
+        // Duplicated solvers create their own constants.
+        let duplicate = solver.duplicate();
+        assert_eq!(duplicate.bv_from_u64(0, 64).as_concrete(), Some(0));
+    }
+}
@@ -0,0 +1,130 @@
+//! Model of the `argc`/`argv` arguments passed to `main`-style entry points.
+//!
+//! The arguments are materialized into memory as null terminated C strings, together with a null
+//! terminated array of pointers to them. The entry function is then called with `argc` set to the
+//! number of arguments and `argv` set to the pointer array.
+use anyhow::anyhow;
+use log::debug;
+
+use super::{Result, VMError, VM};
+use crate::{common::SolutionVariable, memory::BITS_IN_BYTE, BV};
+
+/// A single entry in `argv`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Argv {
+    /// Argument with a concrete value.
+    Concrete(String),
+
+    /// Symbolic argument of the given length in bytes, excluding the null terminator.
+    ///
+    /// The bytes are unconstrained, so the argument may also contain null bytes. This lets it
+    /// represent all strings up to the given length.
+    Symbolic(u32),
+}
+
+impl<'a> VM<'a> {
+    /// Pass `args` as the `argc` and `argv` arguments to the entry function.
+    ///
+    /// The entry function must take `argc` as the first parameter and `argv` as the second, where
+    /// `argv` is a pointer. The symbolic arguments are added to the symbols in the state, named
+    /// `argv_<n>` after their index, see [Solver::fresh_name](crate::Solver::fresh_name).
+    ///
+    /// # Example
+    ///
+    /// ```ignore
+    /// # use x0001e::{Project, VM, vm::Argv};
+    /// # fn main() -> Result<(), Box<dyn std::error::Error>> {
+    /// let project = Project::from_path("tests/samples/argv.bc")?;
+    /// let vm = VM::new("main", &project)?
+    ///     .with_argv(&[Argv::Concrete("argv".to_owned()), Argv::Symbolic(4)])?;
+    /// #   Ok(())
+    /// # }
+    /// ```
+    pub fn with_argv(mut self, args: &[Argv]) -> Result<Self> {
+        if self.parameters.len() < 2 {
+            return Err(VMError::Other(anyhow!(
+                "Entry function must take argc and argv as parameters"
+            )));
+        }
+
+        self.modify_initial_path(|vm| {
+            let ptr_size = vm.project.ptr_size;
+
+            // Write each argument as a null terminated string.
+            let mut pointers = Vec::new();
+            for (n, arg) in args.iter().enumerate() {
+                let value = match arg {
+                    Argv::Concrete(s) => {
+                        let bytes = s
+                            .bytes()
+                            .map(|b| vm.solver.bv_from_u64(b as u64, BITS_IN_BYTE));
+                        bytes.reduce(|acc, v| v.concat(&acc))
+                    }
+                    Argv::Symbolic(0) => None,
+                    Argv::Symbolic(len) => {
+                        let name = vm.solver.fresh_name(&format!("argv_{n}"));
+                        let value = vm.solver.bv(len * BITS_IN_BYTE, &name);
+                        vm.add_symbol(SolutionVariable {
+                            name,
+                            value: value.clone(),
+                            ty: None,
+                        });
+                        Some(value)
+                    }
+                };
+
+                let terminator = vm.solver.bv_zero(BITS_IN_BYTE);
+                let value = match value {
+                    Some(value) => terminator.concat(&value),
+                    None => terminator,
+                };
+
+                let addr = vm.state.allocate(value.len() as u64, 1)?;
+                let addr = vm.solver.bv_from_u64(addr, ptr_size);
+                vm.state.mem.borrow_mut().write(&addr, value)?;
+                debug!("argv[{n}] written to {addr:?}");
+
+                pointers.push(addr);
+            }
+
+            // The pointer array is terminated by a null pointer.
+            pointers.push(vm.solver.bv_zero(ptr_size));
+            let argv = pointers
+                .into_iter()
+                .reduce(|acc, v| v.concat(&acc))
+                .ok_or(VMError::InternalError("Empty argv"))?;
+
+            let align = (ptr_size / BITS_IN_BYTE) as u64;
+            let argv_addr = vm.state.allocate(argv.len() as u64, align)?;
+            let argv_addr = vm.solver.bv_from_u64(argv_addr, ptr_size);
+            vm.state.mem.borrow_mut().write(&argv_addr, argv)?;
+
+            let argc_size = vm.parameters[0].value.len();
+            let argc = vm.solver.bv_from_u64(args.len() as u64, argc_size);
+
+            vm.bind_parameter(0, argc)?;
+            vm.bind_parameter(1, argv_addr)
+        })?;
+
+        Ok(self)
+    }
+
+    /// Bind the parameter at `index` of the entry function to `value`.
+    fn bind_parameter(&mut self, index: usize, value: BV) -> Result<()> {
+        let parameter = self
+            .parameters
+            .get_mut(index)
+            .ok_or(VMError::InternalError("Parameter index out of bounds"))?;
+        if parameter.value.len() != value.len() {
+            return Err(VMError::Other(anyhow!(
+                "Parameter {index} of the entry function has {} bits, expected {}",
+                parameter.value.len(),
+                value.len()
+            )));
+        }
+        parameter.value = value.clone();
+
+        let name = self.state.current_loc.func.parameters[index].name.clone();
+        self.state.vars.insert(name, value)
+    }
+}
@@ -0,0 +1,129 @@
+//! Resource limits for each path and for the whole exploration.
+use std::time::{Duration, Instant};
+
+use super::{Result, ReturnValue, VMError, VM};
+
+/// Resource limits for a single path.
+///
+/// The limits apply to each path separately, the counters are reset when a path starts executing.
+/// A path that exceeds either limit is terminated with [VMError::BudgetExhausted]. The wall time is
+/// checked periodically while executing instructions, and after forks and concretizations which
+/// query the solver.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Budget {
+    /// Maximum number of instructions to execute, including terminators.
+    pub max_instructions: Option<u64>,
+
+    /// Maximum time to spend executing.
+    pub max_wall_time: Option<Duration>,
+}
+
+/// Resource usage of the currently executing path.
+#[derive(Debug, Clone, Copy)]
+pub(super) struct BudgetUsage {
+    instructions: u64,
+    start: Instant,
+}
+
+impl BudgetUsage {
+    pub(super) fn new() -> Self {
+        Self {
+            instructions: 0,
+            start: Instant::now(),
+        }
+    }
+}
+
+/// How many instructions to execute between each check of the wall time.
+const WALL_TIME_CHECK_INTERVAL: u64 = 1024;
+
+impl<'a> VM<'a> {
+    /// Limit the resources each path may use.
+    pub fn with_budget(mut self, budget: Budget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Count an executed instruction and check that the budget has not been exhausted.
+    ///
+    /// This is called before each instruction is executed. If the deadline has passed the path is
+    /// saved so it resumes at the instruction, and [VMError::Timeout] is returned.
+    pub(super) fn consume_budget(&mut self) -> Result<()> {
+        if self.deadline_passed() {
+            self.save_backtracking_path_at_current(None)?;
+            return Err(VMError::Timeout);
+        }
+
+        self.budget_usage.instructions += 1;
+        let instructions = self.budget_usage.instructions;
+
+        if let Some(max_instructions) = self.budget.max_instructions {
+            if instructions > max_instructions {
+                return Err(VMError::BudgetExhausted);
+            }
+        }
+
+        if instructions % WALL_TIME_CHECK_INTERVAL == 0 {
+            self.check_wall_time()?;
+        }
+
+        Ok(())
+    }
+
+    /// Check that the wall time of the path has not been exceeded.
+    ///
+    /// Called after steps that query the solver, since a single query can take longer than many
+    /// instructions.
+    pub(super) fn check_wall_time(&self) -> Result<()> {
+        if let Some(max_wall_time) = self.budget.max_wall_time {
+            if self.budget_usage.start.elapsed() > max_wall_time {
+                return Err(VMError::BudgetExhausted);
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Start counting the resources used by a new path.
+    pub(super) fn reset_budget_usage(&mut self) {
+        self.budget_usage = BudgetUsage::new();
+    }
+
+    /// Explore paths until all have been explored or `deadline` has passed.
+    ///
+    /// `on_path` is called as each path finishes, while the state of the path can still be
+    /// inspected, and the values it returns are collected. The returned flag is `true` if the
+    /// deadline was reached before all paths were explored, the remaining paths are kept and can
+    /// be explored by a later call.
+    ///
+    /// The deadline is checked before each instruction, so a single instruction and the solver
+    /// queries it makes may run past it. The path that is executing when the deadline passes is
+    /// saved so that a later call resumes it at the instruction where it was stopped, its
+    /// [Budget] then starts over.
+    pub fn run_all_with_deadline<T>(
+        &mut self,
+        deadline: Duration,
+        mut on_path: impl FnMut(&VM<'a>, Result<ReturnValue>) -> T,
+    ) -> (Vec<T>, bool) {
+        self.deadline = Some(Instant::now() + deadline);
+
+        let mut results = Vec::new();
+        let timed_out = loop {
+            match self.run() {
+                Some(Err(VMError::Timeout)) => break true,
+                Some(result) => results.push(on_path(self, result)),
+                None => break false,
+            }
+        };
+
+        self.deadline = None;
+        (results, timed_out)
+    }
+
+    fn deadline_passed(&self) -> bool {
+        match self.deadline {
+            Some(deadline) => Instant::now() >= deadline,
+            None => false,
+        }
+    }
+}
@@ -0,0 +1,64 @@
+//! Policies for concretizing symbolic values.
+//!
+//! Some operations, such as the size passed to `llvm.memcpy`, require a concrete value. When the
+//! value is symbolic the [ConcretizationPolicy] decides which concrete value(s) to use.
+use super::{Result, VMError, VM};
+use crate::{Solutions, BV};
+
+/// Decides how symbolic values that must be concrete are resolved.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum ConcretizationPolicy {
+    /// Use any one of the possible values, this is the default.
+    #[default]
+    ConcretizeAny,
+
+    /// Use the lowest possible value.
+    ConcretizeMin,
+
+    /// Use the highest possible value.
+    ConcretizeMax,
+
+    /// Explore up to `n` of the possible values, one path for each.
+    Fork(usize),
+}
+
+impl<'a> VM<'a> {
+    /// Set the policy used to concretize symbolic values.
+    pub fn with_concretization_policy(mut self, policy: ConcretizationPolicy) -> Self {
+        self.concretization_policy = policy;
+        self
+    }
+
+    /// Get a concrete value for `bv` according to the [ConcretizationPolicy].
+    ///
+    /// The current path is constrained to the returned value. With [ConcretizationPolicy::Fork]
+    /// the other values are saved as paths that resume at the current instruction.
+    pub fn concretize(&mut self, bv: &BV) -> Result<u64> {
+        let value = match self.concretization_policy {
+            ConcretizationPolicy::ConcretizeAny => match self.solver.get_solutions_for_bv(bv, 1)? {
+                Solutions::None => return Err(VMError::Unsat),
+                Solutions::Exactly(s) | Solutions::AtLeast(s) => s[0].as_u64().unwrap(),
+            },
+            ConcretizationPolicy::ConcretizeMin => self.solver.get_solution_minimum(bv)?,
+            ConcretizationPolicy::ConcretizeMax => self.solver.get_solution_maximum(bv)?,
+            ConcretizationPolicy::Fork(n) => {
+                let values = match self.solver.get_solutions_for_bv(bv, n.max(1))? {
+                    Solutions::None => return Err(VMError::Unsat),
+                    Solutions::Exactly(s) | Solutions::AtLeast(s) => s,
+                };
+
+                for value in values.iter().skip(1) {
+                    let value = self.solver.from_binary_string(value.as_01x_str());
+                    self.save_backtracking_path_at_current(Some(bv.eq(&value)))?;
+                }
+                values[0].as_u64().unwrap()
+            }
+        };
+        self.check_wall_time()?;
+
+        let concrete = self.solver.bv_from_u64(value, bv.len());
+        self.solver.assert(&bv.eq(&concrete));
+
+        Ok(value)
+    }
+}
@@ -0,0 +1,40 @@
+//! Execution of global constructors before the entry function.
+//!
+//! Global constructors are listed in `llvm.global_ctors`, and are used by e.g. C++ static
+//! initializers and `__attribute__((constructor))` in C. These normally run before `main`.
+use anyhow::anyhow;
+use log::debug;
+
+use super::{Location, Result, VMError, VM};
+
+impl<'a> VM<'a> {
+    /// Run all global constructors before the entry function.
+    ///
+    /// The constructors are executed in priority order on the initial path, so all paths start
+    /// with the globals they initialize. The constructors must not depend on symbolic values in a
+    /// way that forks the path, in that case [VMError::Other] is returned.
+    pub fn with_global_ctors(mut self) -> Result<Self> {
+        self.modify_initial_path(|vm| {
+            let entry = vm.state.current_loc.clone();
+
+            for (module, function) in vm.project.get_global_ctors() {
+                debug!("Running global constructor {}", function.name);
+                vm.state.current_loc = Location::new(module, function);
+                vm.state.vars.enter_scope();
+                vm.resume_execution()?;
+
+                if !vm.backtracking_paths.is_empty() {
+                    return Err(VMError::Other(anyhow!(
+                        "Global constructor {} has multiple paths",
+                        function.name
+                    )));
+                }
+            }
+
+            vm.state.current_loc = entry;
+            Ok(())
+        })?;
+
+        Ok(self)
+    }
+}
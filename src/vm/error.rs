@@ -1,6 +1,9 @@
 use thiserror::Error;
 
-use crate::{memory::MemoryError, solver::SolverError};
+use crate::{
+    memory::MemoryError,
+    solver::{BVError, SolverError},
+};
 
 pub type Result<T> = std::result::Result<T, VMError>;
 
@@ -18,9 +21,53 @@ pub enum VMError {
     #[error("Throw")]
     Throw,
 
+    /// Panic in the analyzed code, with the reason if it is known.
+    #[error("Panic: {0}")]
+    Panic(PanicReason),
+
     #[error("Unsat")]
     Unsat,
 
+    /// Load or store through a pointer into the null page, see [crate::memory::NULL_PAGE_SIZE].
+    #[error("Null pointer dereference")]
+    NullPointerDereference,
+
+    /// Load or store through a pointer to the stack allocation of a function that has returned,
+    /// or to a stack allocation freed by `llvm.stackrestore`.
+    #[error("Use of stack memory after return")]
+    UseAfterReturn,
+
+    /// A `getelementptr inbounds` computed an address outside the allocation of its base pointer,
+    /// the result of which is poison.
+    #[error("Inbounds getelementptr outside of its allocation")]
+    InboundsViolation,
+
+    /// Load or store through an address that does not have the alignment in bytes required by the
+    /// instruction.
+    #[error("Misaligned access, required alignment is {0}")]
+    MisalignedAccess(u32),
+
+    /// Shift by an amount larger than or equal to the bit width.
+    ///
+    /// LLVM defines the result as poison, this is treated as an error since it is usually a bug.
+    #[error("Shift amount exceeds the bit width")]
+    InvalidShift,
+
+    /// An `add`, `sub` or `mul` overflowed while treated as `nsw` or `nuw`, the result of which is
+    /// poison. See [VM::with_nsw_checks](crate::vm::VM::with_nsw_checks).
+    #[error("Arithmetic overflow of a no wrap operation")]
+    PoisonOverflow,
+
+    /// A `udiv` or `sdiv` had a remainder while treated as `exact`, the result of which is poison.
+    /// See [VM::with_exact_checks](crate::vm::VM::with_exact_checks).
+    #[error("Inexact division of an exact operation")]
+    InexactDivision,
+
+    /// An operation produced poison that is undefined behavior to use, e.g. `llvm.ctlz` of zero
+    /// when zero is marked as poison.
+    #[error("Use of a poison value")]
+    PoisonValue,
+
     // -------------------------------------------------------------------------
     // Errors in IR
     // -------------------------------------------------------------------------
@@ -28,6 +75,10 @@ pub enum VMError {
     #[error("Function not found: {0}")]
     FunctionNotFound(String),
 
+    /// Call to a function without a definition or hook, e.g. an external function.
+    #[error("Call to undefined function: {0}")]
+    UndefinedFunction(String),
+
     #[error("Local not found: {0}")]
     LocalNotFound(String),
 
@@ -42,10 +93,35 @@ pub enum VMError {
     #[error("UnsupportedInstruction {0}")]
     UnsupportedInstruction(String),
 
+    /// Call to an intrinsic with semantics that are not modeled.
+    #[error("Unsupported intrinsic: {0}")]
+    UnsupportedIntrinsic(String),
+
+    /// Call to inline assembly, which cannot be executed.
+    ///
+    /// The parsed IR does not include the assembly template, so this holds the name of the
+    /// function that contains the call.
+    #[error("Inline assembly is not supported, called from {0}")]
+    InlineAsmUnsupported(String),
+
     /// UnreachableInstruction
     #[error("UnreachableInstruction")]
     UnreachableInstruction,
 
+    /// The path exceeded its [super::Budget].
+    #[error("Budget exhausted")]
+    BudgetExhausted,
+
+    /// The exploration reached its deadline, see [super::VM::run_all_with_deadline]. The path was
+    /// saved and is resumed by the next run.
+    #[error("Deadline reached")]
+    Timeout,
+
+    /// A summary was requested for a function with more than one path, see
+    /// [super::VM::symbolic_return_of].
+    #[error("Function has more than one path")]
+    MultiplePaths,
+
     #[error("Expected type to be non-zero sized")]
     UnexpectedZeroSize,
 
@@ -58,16 +134,114 @@ pub enum VMError {
     #[error(transparent)]
     Other(#[from] anyhow::Error),
 
+    /// A solver query on this path reached its timeout, see [crate::Solver::set_query_timeout].
+    #[error("Solver query timed out")]
+    SolverTimeout,
+
+    #[error(transparent)]
+    Solver(SolverError),
+
+    /// Operands of an operation have different bit widths.
     #[error(transparent)]
-    Solver(#[from] SolverError),
+    BVError(#[from] BVError),
+}
+
+/// Reason for a [VMError::Panic].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PanicReason {
+    /// Index out of bounds, with the index and the length of the indexed slice.
+    BoundsCheck { index: u64, len: u64 },
+
+    /// Panic with a static message, e.g. "attempt to add with overflow" from overflow checks.
+    Message(String),
+}
+
+impl std::fmt::Display for PanicReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PanicReason::BoundsCheck { index, len } => write!(
+                f,
+                "index out of bounds: the len is {len} but the index is {index}"
+            ),
+            PanicReason::Message(message) => write!(f, "{message}"),
+        }
+    }
 }
 
 impl PartialEq for VMError {
     fn eq(&self, other: &Self) -> bool {
         match (self, other) {
             (Self::FunctionNotFound(l0), Self::FunctionNotFound(r0)) => l0 == r0,
+            (Self::UndefinedFunction(l0), Self::UndefinedFunction(r0)) => l0 == r0,
+            (Self::UnsupportedIntrinsic(l0), Self::UnsupportedIntrinsic(r0)) => l0 == r0,
+            (Self::InlineAsmUnsupported(l0), Self::InlineAsmUnsupported(r0)) => l0 == r0,
+            (Self::Panic(l0), Self::Panic(r0)) => l0 == r0,
             (Self::Other(l0), Self::Other(r0)) => l0.to_string() == r0.to_string(),
             _ => core::mem::discriminant(self) == core::mem::discriminant(other),
         }
     }
 }
+
+impl From<SolverError> for VMError {
+    fn from(error: SolverError) -> Self {
+        match error {
+            SolverError::Timeout => VMError::SolverTimeout,
+            SolverError::Unsat => VMError::Unsat,
+            error => VMError::Solver(error),
+        }
+    }
+}
+
+impl VMError {
+    /// Returns the name of the error variant, without any of the data it holds.
+    pub fn kind(&self) -> &'static str {
+        match self {
+            VMError::Abort(_) => "Abort",
+            VMError::Throw => "Throw",
+            VMError::Panic(_) => "Panic",
+            VMError::Unsat => "Unsat",
+            VMError::NullPointerDereference => "NullPointerDereference",
+            VMError::UseAfterReturn => "UseAfterReturn",
+            VMError::InboundsViolation => "InboundsViolation",
+            VMError::MisalignedAccess(_) => "MisalignedAccess",
+            VMError::InvalidShift => "InvalidShift",
+            VMError::PoisonOverflow => "PoisonOverflow",
+            VMError::InexactDivision => "InexactDivision",
+            VMError::PoisonValue => "PoisonValue",
+            VMError::FunctionNotFound(_) => "FunctionNotFound",
+            VMError::UndefinedFunction(_) => "UndefinedFunction",
+            VMError::LocalNotFound(_) => "LocalNotFound",
+            VMError::MalformedInstruction => "MalformedInstruction",
+            VMError::UnsupportedInstruction(_) => "UnsupportedInstruction",
+            VMError::UnsupportedIntrinsic(_) => "UnsupportedIntrinsic",
+            VMError::InlineAsmUnsupported(_) => "InlineAsmUnsupported",
+            VMError::UnreachableInstruction => "UnreachableInstruction",
+            VMError::BudgetExhausted => "BudgetExhausted",
+            VMError::Timeout => "Timeout",
+            VMError::MultiplePaths => "MultiplePaths",
+            VMError::UnexpectedZeroSize => "UnexpectedZeroSize",
+            VMError::InternalError(_) => "InternalError",
+            VMError::MemoryError(_) => "MemoryError",
+            VMError::Other(_) => "Other",
+            VMError::SolverTimeout => "SolverTimeout",
+            VMError::Solver(_) => "Solver",
+            VMError::BVError(_) => "BVError",
+        }
+    }
+}
+
+/// Errors are serialized as their kind together with the error message.
+#[cfg(feature = "serde")]
+impl serde::Serialize for VMError {
+    fn serialize<S>(&self, serializer: S) -> std::result::Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        use serde::ser::SerializeStruct;
+
+        let mut state = serializer.serialize_struct("VMError", 2)?;
+        state.serialize_field("kind", self.kind())?;
+        state.serialize_field("message", &self.to_string())?;
+        state.end()
+    }
+}
@@ -1,14 +1,17 @@
 //! Map of global references to their addresses.
 //!
 //! Keeps track of both external and internal global references ([Function]s and [GlobalVariable]s).
+use anyhow::anyhow;
 use llvm_ir::{module::GlobalVariable, Function, Name, Type};
 use log::{debug, trace};
+use rustc_demangle::demangle;
 use std::{cell::Cell, collections::HashMap};
 
 use crate::{
+    common::SolutionVariable,
     memory::Memory,
     project::{ModuleHandle, Project},
-    VMError,
+    VMError, VM,
 };
 
 /// A global [Function] or [GlobalVariable].
@@ -53,6 +56,9 @@ pub struct GlobalReferences<'p> {
 
     /// Provides lookup from an address to a function name.
     private_function_name_lookup: HashMap<ModuleHandle, HashMap<u64, String>>,
+
+    /// Project the references are from, used to resolve aliases.
+    project: &'p Project,
 }
 
 impl<'p> GlobalReferences<'p> {
@@ -66,6 +72,7 @@ impl<'p> GlobalReferences<'p> {
             private_global_references: HashMap::new(),
             function_name_lookup: HashMap::new(),
             private_function_name_lookup: HashMap::new(),
+            project,
         };
 
         // Add functions.
@@ -111,43 +118,12 @@ impl<'p> GlobalReferences<'p> {
 
         // Add global variables.
         let mut create_var_global_ref =
-            |var: &'p GlobalVariable| -> Result<GlobalReference<'p>, VMError> {
-                // All GlobalVariable's should be pointers. Allocation size is based on the
-                // underlying type.
-                let size = match var.ty.as_ref() {
-                    Type::PointerType { pointee_type, .. } => project.bit_size(pointee_type)?,
-                    _ => panic!("Expected pointer type"),
-                };
-
-                // If the global is zero sized, just allocate a small amount for it.
-                let size = if size == 0 { 4 } else { size };
-
-                // If no specific alignment is specified, use the project default.
-                let align = if var.alignment == 0 {
-                    project.default_alignment
-                } else {
-                    var.alignment
-                };
-
-                let addr = memory.allocate(size as u64, align as u64)?;
-
-                trace!(
-                    "Global {} allocated at: {addr:x} with size: {size} bits, {} bytes",
-                    var.name,
-                    size / 8
-                );
-                Ok(GlobalReference {
-                    addr,
-                    kind: GlobalReferenceKind::GlobalVariable {
-                        var,
-                        initialized: Cell::new(false),
-                    },
-                })
-            };
+            |var: &'p GlobalVariable| allocate_variable(project, memory, var);
 
         for (module_handle, var) in project.get_private_global_variables() {
-            // All declaration have initializers, so skip over definitions.
-            if var.initializer.is_none() {
+            // All declaration have initializers, so skip over definitions. Thread locals are
+            // the exception, they are given uninitialized memory since there is only one thread.
+            if var.initializer.is_none() && !var.thread_local {
                 continue;
             }
 
@@ -161,8 +137,9 @@ impl<'p> GlobalReferences<'p> {
         }
 
         for (_, var) in project.get_public_global_variables() {
-            // All declaration have initializers, so skip over definitions.
-            if var.initializer.is_none() {
+            // All declaration have initializers, so skip over definitions. Thread locals are
+            // the exception, they are given uninitialized memory since there is only one thread.
+            if var.initializer.is_none() && !var.thread_local {
                 continue;
             }
 
@@ -178,10 +155,42 @@ impl<'p> GlobalReferences<'p> {
         Ok(s)
     }
 
+    /// Allocate the public global variables that are declared but not defined in any module.
+    ///
+    /// Returns the references that were added, nothing is written to their memory.
+    fn add_declarations(
+        &mut self,
+        memory: &mut Memory,
+    ) -> Result<Vec<GlobalReference<'p>>, VMError> {
+        let project = self.project;
+
+        let mut added = Vec::new();
+        for (_, var) in project.get_public_global_variables() {
+            if var.initializer.is_some()
+                || var.thread_local
+                || self.global_references.contains_key(&var.name)
+            {
+                continue;
+            }
+
+            let global_ref = allocate_variable(project, memory, var)?;
+            debug!(
+                "Declared global variable {} allocated at address: {}",
+                var.name, global_ref.addr
+            );
+            self.global_references
+                .insert(var.name.clone(), global_ref.clone());
+            added.push(global_ref);
+        }
+
+        Ok(added)
+    }
+
     /// Get a global reference by [Name].
     ///
     /// First it checks if the reference exist as a private symbol in the current module. If it does
-    /// not it will check the module private definitions.
+    /// not it will check the module private definitions. Aliases resolve to the reference of their
+    /// aliasee.
     pub fn get(&self, name: &Name, module_handle: ModuleHandle) -> Option<&GlobalReference<'p>> {
         if let Some(reference) = self
             .private_global_references
@@ -189,11 +198,59 @@ impl<'p> GlobalReferences<'p> {
             .and_then(|module_globals| module_globals.get(name))
         {
             Some(reference)
+        } else if let Some(reference) = self.global_references.get(name) {
+            Some(reference)
         } else {
-            self.global_references.get(name)
+            let (module_handle, aliasee) = self.project.resolve_alias(name, module_handle)?;
+            self.get(aliasee, module_handle)
         }
     }
 
+    /// Find a [GlobalVariable] by name in any module.
+    ///
+    /// As for entry functions, demangled names both with and without hashes are checked.
+    pub fn find_variable(&self, name: &str) -> Option<&GlobalReference<'p>> {
+        let private = self
+            .private_global_references
+            .values()
+            .flat_map(|g| g.values());
+        let mut globals = self.global_references.values().chain(private);
+
+        globals.find(|global| match &global.kind {
+            GlobalReferenceKind::GlobalVariable { var, .. } => match &var.name {
+                Name::Name(var_name) => {
+                    let demangled = demangle(var_name);
+                    var_name.as_str() == name
+                        || demangled.to_string() == name
+                        || format!("{:#}", demangled) == name
+                }
+                Name::Number(_) => false,
+            },
+            GlobalReferenceKind::Function(_) => false,
+        })
+    }
+
+    /// Returns the addresses of all global variables that have been initialized, sorted.
+    pub fn initialized_variables(&self) -> Vec<u64> {
+        let private = self
+            .private_global_references
+            .values()
+            .flat_map(|g| g.values());
+        let mut addresses: Vec<_> = self
+            .global_references
+            .values()
+            .chain(private)
+            .filter(|global| match &global.kind {
+                GlobalReferenceKind::GlobalVariable { initialized, .. } => initialized.get(),
+                GlobalReferenceKind::Function(_) => false,
+            })
+            .map(|global| global.addr)
+            .collect();
+
+        addresses.sort_unstable();
+        addresses
+    }
+
     /// Get the name of a function from an address.
     ///
     /// It first checks the module private functions, followed by the global functions.
@@ -240,3 +297,130 @@ impl<'p> GlobalReferences<'p> {
         entry.insert(addr, name);
     }
 }
+
+/// Allocate memory for the global variable `var`.
+fn allocate_variable<'p>(
+    project: &Project,
+    memory: &mut Memory,
+    var: &'p GlobalVariable,
+) -> Result<GlobalReference<'p>, VMError> {
+    // All GlobalVariable's should be pointers. Allocation size is based on the underlying type.
+    let size = match var.ty.as_ref() {
+        Type::PointerType { pointee_type, .. } => project.bit_size(pointee_type)?,
+        _ => panic!("Expected pointer type"),
+    };
+
+    // If the global is zero sized, just allocate a small amount for it.
+    let size = if size == 0 { 4 } else { size };
+
+    // If no specific alignment is specified, use the project default.
+    let align = if var.alignment == 0 {
+        project.default_alignment
+    } else {
+        var.alignment
+    };
+
+    let addr = memory.allocate(size as u64, align as u64)?;
+
+    trace!(
+        "Global {} allocated at: {addr:x} with size: {size} bits, {} bytes",
+        var.name,
+        size / 8
+    );
+    Ok(GlobalReference {
+        addr,
+        kind: GlobalReferenceKind::GlobalVariable {
+            var,
+            initialized: Cell::new(false),
+        },
+    })
+}
+
+impl<'a> VM<'a> {
+    /// Back the global variables that are declared but not defined by fresh symbolic values.
+    ///
+    /// By default such globals, e.g. an `extern` variable defined by a library that is not part
+    /// of the project, cannot be referenced. With this each is allocated and given an
+    /// unconstrained value named after the global, which is added to the path's symbols so its
+    /// solution is reported with the other symbolic values.
+    pub fn with_symbolic_globals(mut self) -> Result<Self, VMError> {
+        self.modify_initial_path(|vm| {
+            let declarations = {
+                let mut memory = vm.state.mem.borrow_mut();
+                vm.state.global_references.add_declarations(&mut memory)?
+            };
+
+            for global in declarations {
+                let var = match &global.kind {
+                    GlobalReferenceKind::GlobalVariable { var, .. } => var,
+                    GlobalReferenceKind::Function(_) => unreachable!(),
+                };
+                let ty = match var.ty.as_ref() {
+                    Type::PointerType { pointee_type, .. } => pointee_type.clone(),
+                    _ => return Err(VMError::MalformedInstruction),
+                };
+                let size = vm.project.bit_size(&ty)?;
+                if size == 0 {
+                    continue;
+                }
+
+                let name = match &var.name {
+                    Name::Name(name) => name.to_string(),
+                    Name::Number(number) => number.to_string(),
+                };
+                let addr = vm.solver.bv_from_u64(global.addr, vm.project.ptr_size);
+                let value = vm.solver.bv_fresh(size, &name);
+                vm.state.mem.borrow_mut().write(&addr, value.clone())?;
+                vm.add_symbol(SolutionVariable {
+                    name,
+                    value,
+                    ty: Some(ty),
+                });
+            }
+            Ok(())
+        })?;
+
+        Ok(self)
+    }
+
+    /// Set the global variable `name` to a concrete `value` before execution starts.
+    ///
+    /// This replaces the initializer of the global, so e.g. configuration flags can be pinned to
+    /// a value. The global must be at most 64 bits, and `value` is truncated to its size.
+    pub fn with_concrete_global(mut self, name: &str, value: u64) -> Result<Self, VMError> {
+        self.modify_initial_path(|vm| {
+            let global = vm
+                .state
+                .global_references
+                .find_variable(name)
+                .ok_or_else(|| VMError::Other(anyhow!("Global variable not found: {name}")))?;
+
+            let size = match &global.kind {
+                GlobalReferenceKind::GlobalVariable { var, initialized } => {
+                    // Mark it as initialized so the initializer does not overwrite the value.
+                    initialized.set(true);
+                    match var.ty.as_ref() {
+                        Type::PointerType { pointee_type, .. } => {
+                            vm.project.bit_size(pointee_type)?
+                        }
+                        _ => return Err(VMError::MalformedInstruction),
+                    }
+                }
+                GlobalReferenceKind::Function(_) => unreachable!(),
+            };
+
+            if size == 0 || size > 64 {
+                return Err(VMError::Other(anyhow!(
+                    "Global variable {name} has size {size}, expected 1 to 64 bits"
+                )));
+            }
+
+            let addr = vm.solver.bv_from_u64(global.addr, vm.project.ptr_size);
+            let value = vm.solver.bv_from_u64(value, 64).slice(0, size - 1);
+            vm.state.mem.borrow_mut().write(&addr, value)?;
+            Ok(())
+        })?;
+
+        Ok(self)
+    }
+}
@@ -1,17 +1,21 @@
+use either::Either;
 use llvm_ir::{
-    instruction::{self, BinaryOp, Instruction},
-    terminator, Terminator, Type,
+    instruction::{self, BinaryOp, HasResult, Instruction},
+    terminator, Name, Operand, Terminator, Type,
 };
 use log::{debug, trace, warn};
+use std::cmp::Ordering;
 
 use crate::{
     common::{
-        binop, cast_to, convert_to_map, extract_value, gep, get_element_offset, icmp, ToValue,
+        cast_to, convert_to_map, extract_value, float_binop, float_to_bv, float_to_int, gep,
+        get_concrete_float, get_concrete_int, insert_value, layout_members, FloatOp, ToValue,
     },
-    hooks::FnInfo,
+    hooks::{FnInfo, HookCtx},
+    memory::{MemoryError, BITS_IN_BYTE, MAX_SYMBOLIC_ALLOCATION, NULL_PAGE_SIZE},
     project::FunctionType,
     solver::BV,
-    vm::{Call, Result, TerminatorResult, VMError, VM},
+    vm::{native::IntOp, Call, Result, TerminatorResult, VMError, VM},
     ReturnValue,
 };
 
@@ -111,11 +115,12 @@ impl<'a> VM<'a> {
             None
         };
 
-        // When returning the variable scope has to be destroyed.
+        // When returning the variable scope has to be destroyed, and the stack allocations of the
+        // function are dead.
         //
         // However, we may not reach this in the case of errors earlier, but that does not matter
         // since if an error is encountered the path is considered dead, and cannot be resumed.
-        self.state.vars.leave_scope();
+        self.state.leave_frame();
 
         Ok(TerminatorResult::Return(value))
     }
@@ -132,16 +137,46 @@ impl<'a> VM<'a> {
     /// the condition and check if the condition can be `true`, `false`, or both. If more than one
     /// path can be selected a save point is created.
     ///
-    /// If the condition cannot be either `true` or `false` [VMError::Unsat] is returned.
+    /// If the condition cannot be either `true` or `false` [VMError::Unsat] is returned. Sides the
+    /// solver cannot decide are explored, see [VM::fork_stats].
+    ///
+    /// With merging enabled, see [VM::with_merging], both sides are instead executed and merged
+    /// into one path if they join again.
+    ///
+    /// With [VM::with_fail_fast] a side that ends in `unreachable`, e.g. after a call to a panic
+    /// handler, is explored first so errors are found before the paths that continue.
     fn condbr(&mut self, instr: &terminator::CondBr) -> Result<TerminatorResult> {
         debug!("{}", instr);
 
         let cond = self.state.get_var(&instr.condition)?;
-        let true_possible = self.solver.is_sat_with_constraint(&cond)?;
-        let false_possible = self.solver.is_sat_with_constraint(&cond.not())?;
+        let true_possible = self.is_feasible(&cond);
+        let false_possible = if true_possible {
+            self.is_feasible(&cond.not())
+        } else {
+            // If `cond` cannot be true the false side is taken whenever the path is satisfiable.
+            let path = self.solver.bv_from_bool(true);
+            self.is_feasible(&path)
+        };
+        self.record_fork(true_possible as usize + false_possible as usize, 2);
+        self.check_wall_time()?;
 
         let target = match (true_possible, false_possible) {
             (true, true) => {
+                // Continue with a single path if both sides can be merged where they join.
+                if self.merging && self.try_merge_branch(instr, &cond)? {
+                    return Ok(TerminatorResult::Branch);
+                }
+
+                // Explore the `false` path first if it is likely to end in an error.
+                if self.fail_fast
+                    && self.ends_in_unreachable(&instr.false_dest)
+                    && !self.ends_in_unreachable(&instr.true_dest)
+                {
+                    self.save_backtracking_path(&instr.true_dest, Some(cond.clone()))?;
+                    self.solver.assert(&cond.not());
+                    return self.branch(&instr.false_dest);
+                }
+
                 // Explore `true` path, and save `false` path for later.
                 self.save_backtracking_path(&instr.false_dest, Some(cond.not()))?;
                 self.solver.assert(&cond);
@@ -155,6 +190,14 @@ impl<'a> VM<'a> {
         self.branch(target)
     }
 
+    /// Returns true if the basic block `block` in the current function ends in `unreachable`.
+    fn ends_in_unreachable(&self, block: &Name) -> bool {
+        matches!(
+            self.state.current_loc.func.get_bb_by_name(block),
+            Some(block) if matches!(block.term, Terminator::Unreachable(_))
+        )
+    }
+
     /// Jump to any number of basic blocks inside the current function.
     ///
     /// A more general version compared to `condbr`. Multiple conditions with jump targets can be
@@ -164,80 +207,103 @@ impl<'a> VM<'a> {
     /// then branch to the first `true` condition it found.
     fn switch(&mut self, instr: &terminator::Switch) -> Result<TerminatorResult> {
         debug!("{}", instr);
-        let value = self.state.get_var(&instr.operand).unwrap();
+        let value = self.state.get_var(&instr.operand)?;
 
         // The condition for the default term in the switch. The default case is built such that
         //   C = true ^ (val != path_cond_1) ^ (val != path_cond_2) ^ ...
         // So if the default one is the only path, we'll still explore.
         let mut default_cond = self.solver.bv_from_bool(true);
 
-        let mut paths = Vec::new();
-
-        // Check if any of the non-default cases can be reached.
+        // Multiple cases can jump to the same basic block, so combine the conditions per target to
+        // only create one path for each of them.
+        let mut targets: Vec<(&Name, BV)> = Vec::new();
         for (constant, target) in instr.dests.iter() {
-            let path_cond = self.state.get_var(constant).unwrap();
+            let path_cond = self.state.get_var(constant)?;
 
             // Build default condition.
             default_cond = default_cond.and(&value.ne(&path_cond));
 
             let cond = value.eq(&path_cond);
-            if self.solver.is_sat_with_constraint(&cond).unwrap() {
+            match targets.iter_mut().find(|(t, _)| *t == target) {
+                Some((_, target_cond)) => *target_cond = target_cond.or(&cond),
+                None => targets.push((target, cond)),
+            }
+        }
+
+        // Check which of the non-default targets can be reached.
+        let num_targets = targets.len();
+        let mut paths = Vec::new();
+        for (target, cond) in targets {
+            if self.is_feasible(&cond) {
                 debug!("switch: path {} possible", target);
                 paths.push((target, cond));
             }
         }
 
         // Check if the default case can be reached.
-        if self.solver.is_sat_with_constraint(&default_cond).unwrap() {
+        if self.is_feasible(&default_cond) {
             debug!("switch: default path possible");
             paths.push((&instr.default_dest, default_cond));
         }
 
+        self.record_fork(paths.len(), num_targets + 1);
+        self.check_wall_time()?;
+
         // Save backtracking points for all paths except one.
         for (target, cond) in paths.iter().skip(1).cloned() {
             self.save_backtracking_path(target, Some(cond))?;
         }
 
         // Jump the the one path that didn't get saved as a backtracking point.
-        if let Some((target, cond)) = paths.first() {
-            self.solver.assert(cond);
-            self.branch(target)
-        } else {
-            // Should never happen, since if we have no paths at all, the
-            // default condition should be just `true`.
-            unreachable!();
+        match paths.first() {
+            Some((target, cond)) => {
+                self.solver.assert(cond);
+                self.branch(target)
+            }
+            // Only happens if the current path is already unsatisfiable.
+            None => Err(VMError::Unsat),
         }
     }
 
     /// Indirect branch to a label in the current function. The target is an address derived from
     /// a `BlockAddress`.
     ///
-    /// This type is not supported in llvm-ir yet. Thus this instruction is unsupported.
+    /// llvm-ir does not say which block a `BlockAddress` refers to, so the address cannot be
+    /// mapped to a block. Only branches with a single possible destination are supported, others
+    /// return [VMError::UnsupportedInstruction].
     fn indirectbr(&mut self, instr: &terminator::IndirectBr) -> Result<TerminatorResult> {
         debug!("{}", instr);
-        Err(VMError::UnsupportedInstruction("indirectbr".to_owned()))
+
+        let (first, rest) = instr
+            .possible_dests
+            .split_first()
+            .ok_or(VMError::MalformedInstruction)?;
+        if rest.iter().any(|target| target != first) {
+            return Err(VMError::UnsupportedInstruction("indirectbr".to_owned()));
+        }
+        self.branch(first)
     }
 
     /// Call the specified function with support for resuming at an exception label.
     ///
-    /// If the function returns normally it resumes execution at the `normal` label, if not
-    /// execution is resumed at the `exception` label.
+    /// If the function returns normally it resumes execution at the `normal` label. If it panics,
+    /// the stack is unwound back to this function and execution is resumed at the `exception`
+    /// label, which is expected to start with a `landingpad`.
     fn invoke(&mut self, instr: &'a terminator::Invoke) -> Result<TerminatorResult> {
         debug!("{}", instr);
-        // Exception handling is not supported, to this is bascially the same as the regular call.
-        //
-        // When execptions are supported, these should be caught and the interpreter should then
-        // instead jump to the exception label.
 
         let current_module = self.state.current_loc.module;
         let name = self.resolve_function(&instr.function)?;
         debug!("resolved function: {}", name);
-        let function = self.project.get_function(&name, current_module)?;
+        let function = self.get_function(&name, current_module)?;
+
+        // Functions called from here push their callsites on top of this.
+        let depth = self.state.callstack.len();
 
         let return_value = match function {
             FunctionType::Hook(hook) => {
-                let info = FnInfo::from_invoke(instr);
-                hook(self, info)?
+                let info = FnInfo::from_invoke(instr, &self.state);
+                hook(&mut HookCtx::new(self, info))
             }
             FunctionType::Function { function, module } => {
                 let arguments = instr
@@ -246,10 +312,21 @@ impl<'a> VM<'a> {
                     .map(|(op, _)| self.state.get_var(op))
                     .collect::<Result<Vec<_>>>()?;
 
-                self.call_fn(Call::Invoke(instr), module, function, arguments)?
+                self.call_fn(Call::Invoke(instr), module, function, arguments)
             }
         };
 
+        let return_value = match return_value {
+            Ok(return_value) => return_value,
+            Err(VMError::Panic(reason)) => {
+                debug!("invoke: unwinding to {}", instr.exception_label);
+                self.unwind_to(depth);
+                self.state.exception = Some(reason);
+                return self.branch(&instr.exception_label);
+            }
+            Err(error) => return Err(error),
+        };
+
         let name = instr.result.clone();
         match return_value {
             ReturnValue::Value(symbol) => self.state.assign_bv(name, symbol)?,
@@ -259,14 +336,43 @@ impl<'a> VM<'a> {
         self.branch(&instr.return_label)
     }
 
+    /// Call to inline assembly that may jump to one of several labels, i.e. `asm goto`.
+    ///
+    /// Inline assembly is not executed, so only `asm goto` without outputs is supported, where
+    /// the assembly is assumed to fall through to the normal label. Everything else returns
+    /// [VMError::UnsupportedInstruction].
     fn callbr(&mut self, instr: &terminator::CallBr) -> Result<TerminatorResult> {
         debug!("{}", instr);
-        todo!()
+
+        let asm = match &instr.function {
+            Either::Left(asm) => asm,
+            Either::Right(_) => return Err(VMError::UnsupportedInstruction("callbr".to_owned())),
+        };
+
+        // The type of the assembly is a pointer to its function type.
+        let ty = self.state.type_of(asm);
+        let ty = match ty.as_ref() {
+            Type::PointerType { pointee_type, .. } => pointee_type.clone(),
+            _ => ty,
+        };
+        let has_outputs = match ty.as_ref() {
+            Type::FuncType { result_type, .. } => !matches!(result_type.as_ref(), Type::VoidType),
+            _ => true,
+        };
+        if has_outputs {
+            return Err(VMError::UnsupportedInstruction("callbr".to_owned()));
+        }
+
+        self.branch(&instr.return_label)
     }
 
+    /// Resumes propagation of the panic that is currently unwinding the stack.
     fn resume(&mut self, instr: &terminator::Resume) -> Result<TerminatorResult> {
         debug!("{}", instr);
-        todo!()
+        match self.state.exception.take() {
+            Some(reason) => Err(VMError::Panic(reason)),
+            None => Err(VMError::InternalError("resume without an unwinding panic")),
+        }
     }
 
     fn catchswitch(&mut self, instr: &terminator::CatchSwitch) -> Result<TerminatorResult> {
@@ -316,120 +422,184 @@ impl<'a> VM<'a> {
     // Division supports `exact` which gives a poison value if the first operand is not a multiple
     // of the second one.
     //
-    // However, these are not exposed in `llvm_ir` so I cannot access these.
+    // However, these are not exposed in `llvm_ir` so I cannot access these. Instead the operations
+    // can be treated as if they have the flags, see [VM::with_nsw_checks], [VM::with_nuw_checks]
+    // and [VM::with_exact_checks].
     // ---------------------------------------------------------------------------------------------
 
     /// Calculate the sum of two integers or two vectors of integers.
     ///
     /// Both arguments must have the same types and must be integers or vectors of integers. On
-    /// unsigned overflow the result is mod 2^n, where n is the size in bits.
+    /// unsigned overflow the result is mod 2^n, where n is the size in bits. Overflow can be
+    /// checked with [VM::with_nsw_checks] and [VM::with_nuw_checks].
     fn add(&mut self, instr: &instruction::Add) -> Result<()> {
         debug!("{}", instr);
-        let result = binop(
-            &self.state,
-            instr.get_operand0(),
-            instr.get_operand1(),
-            BV::add,
-        )?;
+        let (lhs, rhs) = (instr.get_operand0(), instr.get_operand1());
+        self.check_overflow(lhs, rhs, BV::saddo, BV::uaddo)?;
+        let result = self.int_binop(lhs, rhs, IntOp::Add)?;
         self.assign(instr, result)
     }
 
     /// Calculate the sum of two floating points or two vectors of floating points.
     ///
-    /// Floating point is currently unsupported. Always returns [VMError::UnsupportedInstruction].
+    /// Only concrete `float` and `double` operands are supported, see [float_binop].
     fn fadd(&mut self, instr: &instruction::FAdd) -> Result<()> {
         debug!("{}", instr);
-        Err(VMError::UnsupportedInstruction("fadd".to_owned()))
+        self.float_binop(instr, &instr.operand0, &instr.operand1, FloatOp::Add)
     }
 
     /// Calculate the difference of two integers or two vectors of integers.
     ///
     /// Both arguments must have the same types and must be integers or vectors of integers. On
-    /// unsigned overflow the result is mod 2^n, where n is the size in bits.
+    /// unsigned overflow the result is mod 2^n, where n is the size in bits. Overflow can be
+    /// checked with [VM::with_nsw_checks] and [VM::with_nuw_checks].
     fn sub(&mut self, instr: &instruction::Sub) -> Result<()> {
         debug!("{}", instr);
-        let result = binop(
-            &self.state,
-            instr.get_operand0(),
-            instr.get_operand1(),
-            BV::sub,
-        )?;
+        let (lhs, rhs) = (instr.get_operand0(), instr.get_operand1());
+        self.check_overflow(lhs, rhs, BV::ssubo, BV::usubo)?;
+        let result = self.int_binop(lhs, rhs, IntOp::Sub)?;
         self.assign(instr, result)
     }
 
     /// Calculate the difference of two floating points or two vectors of floating points.
     ///
-    /// Floating point is currently unsupported. Always returns [VMError::UnsupportedInstruction].
+    /// Only concrete `float` and `double` operands are supported, see [float_binop].
     fn fsub(&mut self, instr: &instruction::FSub) -> Result<()> {
         debug!("{}", instr);
-        Err(VMError::UnsupportedInstruction("fsub".to_owned()))
+        self.float_binop(instr, &instr.operand0, &instr.operand1, FloatOp::Sub)
     }
 
     /// Calculates the product of two integers or two vectors of integers.
     ///
     /// Both arguments must have the same types and must be integers or vectors of integers. On
-    /// unsigned overflow the result is mod 2^n, where n is the size in bits.
+    /// unsigned overflow the result is mod 2^n, where n is the size in bits. Overflow can be
+    /// checked with [VM::with_nsw_checks] and [VM::with_nuw_checks].
     fn mul(&mut self, instr: &instruction::Mul) -> Result<()> {
         debug!("{}", instr);
-        let result = binop(
-            &self.state,
-            instr.get_operand0(),
-            instr.get_operand1(),
-            BV::mul,
-        )?;
+        let (lhs, rhs) = (instr.get_operand0(), instr.get_operand1());
+        self.check_overflow(lhs, rhs, BV::smulo, BV::umulo)?;
+        let result = self.int_binop(lhs, rhs, IntOp::Mul)?;
         self.assign(instr, result)
     }
 
     /// Calculates the product of two floating points or two vectors of floating points.
     ///
-    /// Floating point is currently unsupported. Always returns [VMError::UnsupportedInstruction].
+    /// Only concrete `float` and `double` operands are supported, see [float_binop].
     fn fmul(&mut self, instr: &instruction::FMul) -> Result<()> {
         debug!("{}", instr);
-        Err(VMError::UnsupportedInstruction("fmul".to_owned()))
+        self.float_binop(instr, &instr.operand0, &instr.operand1, FloatOp::Mul)
+    }
+
+    /// Check if an `add`, `sub` or `mul` can overflow when it is treated as `nsw` or `nuw`.
+    ///
+    /// `signed` and `unsigned` return if the operation overflows as signed or unsigned integers.
+    /// The path where it does terminates with [VMError::PoisonOverflow].
+    fn check_overflow(
+        &mut self,
+        lhs: &Operand,
+        rhs: &Operand,
+        signed: fn(&BV, &BV) -> BV,
+        unsigned: fn(&BV, &BV) -> BV,
+    ) -> Result<()> {
+        if self.nsw_checks {
+            self.check_elements(lhs, rhs, signed, VMError::PoisonOverflow)?;
+        }
+        if self.nuw_checks {
+            self.check_elements(lhs, rhs, unsigned, VMError::PoisonOverflow)?;
+        }
+        Ok(())
+    }
+
+    /// Check if `condition` can be true for the operands, and terminate that path with `error`.
+    ///
+    /// For vectors the condition is checked for each pair of elements, and it is enough that it
+    /// holds for any of them.
+    fn check_elements(
+        &mut self,
+        lhs: &Operand,
+        rhs: &Operand,
+        condition: impl Fn(&BV, &BV) -> BV,
+        error: VMError,
+    ) -> Result<()> {
+        let ty = self.state.type_of(lhs);
+        let lhs = self.state.get_var(lhs)?;
+        let rhs = self.state.get_var(rhs)?;
+
+        let (bits, num_elements) = match ty.as_ref() {
+            Type::VectorType {
+                element_type,
+                num_elements,
+                ..
+            } => (self.project.bit_size(element_type)?, *num_elements as u32),
+            _ => (lhs.len(), 1),
+        };
+
+        let element = |value: &BV, i: u32| value.slice(i * bits, (i + 1) * bits - 1);
+        let possible = (0..num_elements)
+            .map(|i| condition(&element(&lhs, i), &element(&rhs, i)))
+            .reduce(|acc, c| acc.or(&c))
+            .ok_or(VMError::MalformedInstruction)?;
+
+        self.fork_on_error(&possible, error)
     }
 
     /// Calculate the quotient of two integers or two vectors of integers.
     ///
-    /// Returns the unsigned quotient of the operands. The denominator cannot be zero.
+    /// Returns the unsigned quotient of the operands. The denominator cannot be zero. Inexact
+    /// division can be checked with [VM::with_exact_checks].
     fn udiv(&mut self, instr: &instruction::UDiv) -> Result<()> {
         debug!("{}", instr);
         // TODO: We cannot divide by zero here, we may want to provide an analysis for checking that.
         // However, vectors are supported so this should not be done here. Instead it should be
         // done elsewhere I think? Or we could change it to provide a map, and then we can check
         // it ourselves.
-        let result = binop(
-            &self.state,
-            instr.get_operand0(),
-            instr.get_operand1(),
-            BV::udiv,
-        )?;
+        let (lhs, rhs) = (instr.get_operand0(), instr.get_operand1());
+        self.check_exact(lhs, rhs, |lhs, rhs| lhs.urem(rhs))?;
+        let result = self.int_binop(lhs, rhs, IntOp::UDiv)?;
         self.assign(instr, result)
     }
 
     /// Calculate the quotient of two integers or two vectors of integers.
     ///
-    /// Returns the signed quotient of the operands. The denominator cannot be zero. Overflow here
-    /// also leads to undefined behavior.
+    /// Returns the signed quotient of the operands, rounded towards zero. The denominator cannot be
+    /// zero. Overflow here also leads to undefined behavior. Inexact division can be checked with
+    /// [VM::with_exact_checks].
     fn sdiv(&mut self, instr: &instruction::SDiv) -> Result<()> {
         debug!("{}", instr);
         // TODO: Apart from div by zero (see above). The overflow could also be checked I guess,
         // example in docs is 32-bit div with -2147483648 by -1, this may be the only case. i.e.
         // INTx::MIN / -1
-        let result = binop(
-            &self.state,
-            instr.get_operand0(),
-            instr.get_operand1(),
-            BV::sdiv,
-        )?;
+        let (lhs, rhs) = (instr.get_operand0(), instr.get_operand1());
+        self.check_exact(lhs, rhs, |lhs, rhs| lhs.srem(rhs))?;
+        let result = self.int_binop(lhs, rhs, IntOp::SDiv)?;
         self.assign(instr, result)
     }
 
+    /// Check if a `udiv` or `sdiv` can have a remainder when it is treated as `exact`.
+    ///
+    /// `remainder` returns the remainder of the division. The path where it is nonzero terminates
+    /// with [VMError::InexactDivision]. Division by zero is not an inexact division, so the
+    /// denominator must be nonzero for the path to be flagged.
+    fn check_exact(
+        &mut self,
+        lhs: &Operand,
+        rhs: &Operand,
+        remainder: fn(&BV, &BV) -> BV,
+    ) -> Result<()> {
+        if !self.exact_checks {
+            return Ok(());
+        }
+
+        let condition = |lhs: &BV, rhs: &BV| rhs.redor().and(&remainder(lhs, rhs).redor());
+        self.check_elements(lhs, rhs, condition, VMError::InexactDivision)
+    }
+
     /// Calculate the quotient of two floating points or two vectors of floating points.
     ///
-    /// Floating point is currently unsupported. Always returns [VMError::UnsupportedInstruction].
+    /// Only concrete `float` and `double` operands are supported, see [float_binop].
     fn fdiv(&mut self, instr: &instruction::FDiv) -> Result<()> {
         debug!("{}", instr);
-        Err(VMError::UnsupportedInstruction("fdiv".to_owned()))
+        self.float_binop(instr, &instr.operand0, &instr.operand1, FloatOp::Div)
     }
 
     /// Calculate the remainder from unsigned division of two integers or a vector of integers.
@@ -438,12 +608,7 @@ impl<'a> VM<'a> {
     fn urem(&mut self, instr: &instruction::URem) -> Result<()> {
         debug!("{}", instr);
         // TODO: Check div by zero.
-        let result = binop(
-            &self.state,
-            instr.get_operand0(),
-            instr.get_operand1(),
-            BV::urem,
-        )?;
+        let result = self.int_binop(instr.get_operand0(), instr.get_operand1(), IntOp::URem)?;
         self.assign(instr, result)
     }
 
@@ -453,22 +618,33 @@ impl<'a> VM<'a> {
     fn srem(&mut self, instr: &instruction::SRem) -> Result<()> {
         debug!("{}", instr);
         // TODO: Check div by zero.
-        let result = binop(
-            &self.state,
-            instr.get_operand0(),
-            instr.get_operand1(),
-            BV::srem,
-        )?;
+        let result = self.int_binop(instr.get_operand0(), instr.get_operand1(), IntOp::SRem)?;
         self.assign(instr, result)
     }
 
     /// Calculate the remainder from division of two floating points or two vectors of floating
     /// points.
     ///
-    /// Floating point is currently unsupported. Always returns [VMError::UnsupportedInstruction].
+    /// Only concrete `float` and `double` operands are supported, see [float_binop].
     fn frem(&mut self, instr: &instruction::FRem) -> Result<()> {
         debug!("{}", instr);
-        Err(VMError::UnsupportedInstruction("frem".to_owned()))
+        self.float_binop(instr, &instr.operand0, &instr.operand1, FloatOp::Rem)
+    }
+
+    /// Helper to apply the floating point operation `op` and assign the result to `instr`.
+    ///
+    /// The constrained floating point intrinsics use the same operations, so both give the same
+    /// results.
+    fn float_binop(
+        &mut self,
+        instr: &impl HasResult,
+        lhs: &Operand,
+        rhs: &Operand,
+        op: FloatOp,
+    ) -> Result<()> {
+        let ty = self.state.type_of(instr);
+        let result = float_binop(&self.state, lhs, rhs, op, ty.as_ref())?;
+        self.assign(instr, result)
     }
 
     // ---------------------------------------------------------------------------------------------
@@ -482,12 +658,8 @@ impl<'a> VM<'a> {
     fn shl(&mut self, instr: &instruction::Shl) -> Result<()> {
         // TODO: There are a couple ways to get poison values. Read more about those.
         debug!("{}", instr);
-        let result = binop(
-            &self.state,
-            instr.get_operand0(),
-            instr.get_operand1(),
-            BV::sll,
-        )?;
+        self.check_shift_amount(instr.get_operand1())?;
+        let result = self.int_binop(instr.get_operand0(), instr.get_operand1(), IntOp::Shl)?;
         self.assign(instr, result)
     }
 
@@ -495,12 +667,8 @@ impl<'a> VM<'a> {
     fn lshr(&mut self, instr: &instruction::LShr) -> Result<()> {
         // TODO: There are a couple ways to get poison values. Read more about those.
         debug!("{}", instr);
-        let result = binop(
-            &self.state,
-            instr.get_operand0(),
-            instr.get_operand1(),
-            BV::srl,
-        )?;
+        self.check_shift_amount(instr.get_operand1())?;
+        let result = self.int_binop(instr.get_operand0(), instr.get_operand1(), IntOp::LShr)?;
         self.assign(instr, result)
     }
 
@@ -508,48 +676,56 @@ impl<'a> VM<'a> {
     fn ashr(&mut self, instr: &instruction::AShr) -> Result<()> {
         // TODO: There are a couple ways to get poison values. Read more about those.
         debug!("{}", instr);
-        let result = binop(
-            &self.state,
-            instr.get_operand0(),
-            instr.get_operand1(),
-            BV::sra,
-        )?;
+        self.check_shift_amount(instr.get_operand1())?;
+        let result = self.int_binop(instr.get_operand0(), instr.get_operand1(), IntOp::AShr)?;
         self.assign(instr, result)
     }
 
+    /// Check if the shift amount can be larger than or equal to the bit width.
+    ///
+    /// The result of such a shift is poison in LLVM, these paths are terminated with
+    /// [VMError::InvalidShift] instead of continuing with a poison value. For vectors it is enough
+    /// that the shift amount in any element is invalid.
+    fn check_shift_amount(&mut self, amount: &Operand) -> Result<()> {
+        let ty = self.state.type_of(amount);
+        let amount = self.state.get_var(amount)?;
+
+        let (bits, num_elements) = match ty.as_ref() {
+            Type::VectorType {
+                element_type,
+                num_elements,
+                ..
+            } => (self.project.bit_size(element_type)?, *num_elements as u32),
+            _ => (amount.len(), 1),
+        };
+
+        let width = self.solver.bv_from_u64(bits as u64, bits);
+        let invalid = (0..num_elements)
+            .map(|i| amount.slice(i * bits, (i + 1) * bits - 1).ugte(&width))
+            .reduce(|acc, c| acc.or(&c))
+            .ok_or(VMError::MalformedInstruction)?;
+
+        self.fork_on_error(&invalid, VMError::InvalidShift)
+    }
+
     /// Bitwise logical and.
     fn and(&mut self, instr: &instruction::And) -> Result<()> {
         debug!("{}", instr);
-        let result = binop(
-            &self.state,
-            instr.get_operand0(),
-            instr.get_operand1(),
-            BV::and,
-        )?;
+        let result = self.int_binop(instr.get_operand0(), instr.get_operand1(), IntOp::And)?;
         self.assign(instr, result)
     }
 
     /// Bitwise logical or.
     fn or(&mut self, instr: &instruction::Or) -> Result<()> {
         debug!("{}", instr);
-        let result = binop(
-            &self.state,
-            instr.get_operand0(),
-            instr.get_operand1(),
-            BV::or,
-        )?;
+        let result = self.int_binop(instr.get_operand0(), instr.get_operand1(), IntOp::Or)?;
         self.assign(instr, result)
     }
 
     /// Bitwise logical xor.
     fn xor(&mut self, instr: &instruction::Xor) -> Result<()> {
         debug!("{}", instr);
-        let result = binop(
-            &self.state,
-            instr.get_operand0(),
-            instr.get_operand1(),
-            BV::xor,
-        )?;
+        let result = self.int_binop(instr.get_operand0(), instr.get_operand1(), IntOp::Xor)?;
         self.assign(instr, result)
     }
 
@@ -593,12 +769,12 @@ impl<'a> VM<'a> {
     /// should be inserted.
     fn insertvalue(&mut self, instr: &instruction::InsertValue) -> Result<()> {
         debug!("{}", instr);
-        let original = self.state.get_var(&instr.aggregate)?;
-        let value = self.state.get_var(&instr.element)?;
-
-        let (offset, _) = get_element_offset(&self.state, &instr.aggregate, &instr.indices)?;
-        let value = original.replace_part(offset as u32, value);
-
+        let value = insert_value(
+            &self.state,
+            &instr.aggregate,
+            &instr.element,
+            &instr.indices,
+        )?;
         self.assign(instr, value)
     }
 
@@ -610,8 +786,12 @@ impl<'a> VM<'a> {
     /// automatically cleaned up when the function returns.
     fn alloca(&mut self, instr: &instruction::Alloca) -> Result<()> {
         debug!("{}", instr);
+        // Consecutive elements are placed at the allocation size of the type, as by GEP.
+        let element_size = self.project.size_of(&instr.allocated_type)? * BITS_IN_BYTE as u64;
+        if let Operand::LocalOperand { .. } = instr.num_elements {
+            return self.alloca_symbolic(instr, element_size);
+        }
         let num_elements = instr.num_elements.to_value()?;
-        let element_size = self.project.bit_size(&instr.allocated_type)? as u64;
 
         let mut allocation_size = element_size * num_elements;
         if allocation_size == 0 {
@@ -626,10 +806,50 @@ impl<'a> VM<'a> {
         self.assign(instr, addr)
     }
 
+    /// Alloca where the number of elements is symbolic.
+    ///
+    /// The allocation reserves space for the largest possible number of elements, but only the
+    /// requested number of elements are in bounds. Paths where the size can exceed
+    /// [MAX_SYMBOLIC_ALLOCATION] bytes end with an error.
+    fn alloca_symbolic(&mut self, instr: &instruction::Alloca, element_size: u64) -> Result<()> {
+        let ptr_size = self.project.ptr_size;
+        let num_elements = self.state.get_var(&instr.num_elements)?;
+        let num_elements = num_elements.resize_unsigned(ptr_size);
+
+        let element_bytes = (element_size.max(1) + BITS_IN_BYTE as u64 - 1) / BITS_IN_BYTE as u64;
+        let max_elements = MAX_SYMBOLIC_ALLOCATION / element_bytes;
+        let too_large = num_elements.ugt(&self.solver.bv_from_u64(max_elements, ptr_size));
+        self.fork_on_error(
+            &too_large,
+            MemoryError::SymbolicAllocationTooLarge(MAX_SYMBOLIC_ALLOCATION).into(),
+        )?;
+
+        let size = num_elements.mul(&self.solver.bv_from_u64(element_bytes, ptr_size));
+        let max_size = self.solver.get_solution_maximum(&size)?;
+        let max_size = if max_size == 0 {
+            warn!("zero sized alloca");
+            ptr_size as u64
+        } else {
+            max_size * BITS_IN_BYTE as u64
+        };
+
+        let addr = self
+            .state
+            .stack_alloc_symbolic(max_size, instr.alignment as u64, &size)?;
+
+        self.assign(instr, addr)
+    }
+
     /// Load reads a value from memory.
+    ///
+    /// Atomic loads are treated as regular loads, execution is single-threaded so the ordering
+    /// does not matter.
     fn load(&mut self, instr: &instruction::Load) -> Result<()> {
         debug!("{}", instr);
         let addr = self.state.get_var(&instr.address)?;
+        self.check_null_dereference(&addr)?;
+        self.check_use_after_return(&addr)?;
+        self.check_alignment(&addr, instr.alignment)?;
 
         let target_ty = self.state.type_of(instr);
         let target_size = self.project.bit_size(&target_ty)?;
@@ -639,26 +859,147 @@ impl<'a> VM<'a> {
 
     /// Store writes to a value to memory.
     ///
-    /// Accepts a value which will be written to the passed pointer address.
+    /// Accepts a value which will be written to the passed pointer address. Like loads, the
+    /// ordering of atomic stores is ignored.
     fn store(&mut self, instr: &instruction::Store) -> Result<()> {
         debug!("{}", instr);
 
         let value = self.state.get_var(&instr.value)?;
         let addr = self.state.get_var(&instr.address)?;
+        self.check_null_dereference(&addr)?;
+        self.check_use_after_return(&addr)?;
+        self.check_alignment(&addr, instr.alignment)?;
+
         self.state.mem.borrow_mut().write(&addr, value)?;
         Ok(())
     }
 
+    /// Fork the path if `addr` can point into the null page, when null checks are enabled.
+    ///
+    /// The path where it does is terminated with [VMError::NullPointerDereference].
+    fn check_null_dereference(&mut self, addr: &BV) -> Result<()> {
+        if !self.null_checks {
+            return Ok(());
+        }
+
+        let null_page = self.solver.bv_from_u64(NULL_PAGE_SIZE, addr.len());
+        self.fork_on_error(&addr.ult(&null_page), VMError::NullPointerDereference)
+    }
+
+    /// Fork the path if `addr` can be outside the allocation of `base`, other than one byte past
+    /// its end.
+    ///
+    /// The path where it is terminates with [VMError::InboundsViolation]. The allocation is found
+    /// from the lowest and highest possible `base`, if these are in different allocations, or not
+    /// in any allocation, nothing is checked.
+    fn check_inbounds(&mut self, base: &BV, addr: &BV) -> Result<()> {
+        let (lowest, highest) = match base.as_concrete() {
+            Some(base) => (base, base),
+            None => (
+                self.solver.get_solution_minimum(base)?,
+                self.solver.get_solution_maximum(base)?,
+            ),
+        };
+
+        // The base may itself point one byte past the end of its allocation.
+        let allocation_of = |addr: u64| {
+            let mem = self.state.mem.borrow();
+            mem.allocation_containing(addr).or_else(|| {
+                addr.checked_sub(1)
+                    .and_then(|addr| mem.allocation_containing(addr))
+            })
+        };
+        let allocation = match (allocation_of(lowest), allocation_of(highest)) {
+            (Some(first), Some(last)) if first == last => first,
+            _ => return Ok(()),
+        };
+
+        let start = self.solver.bv_from_u64(allocation.addr, addr.len());
+        let end = self
+            .solver
+            .bv_from_u64(allocation.addr + allocation.bytes, addr.len());
+        let outside = addr.ult(&start).or(&addr.ugt(&end));
+        self.fork_on_error(&outside, VMError::InboundsViolation)
+    }
+
+    /// Fork the path if `addr` can point into a stack allocation of a function that has returned,
+    /// when use after return checks are enabled.
+    ///
+    /// The path where it does is terminated with [VMError::UseAfterReturn].
+    fn check_use_after_return(&mut self, addr: &BV) -> Result<()> {
+        if !self.use_after_return_checks || self.state.dead_stack.is_empty() {
+            return Ok(());
+        }
+
+        // Concrete addresses are checked directly without the solver.
+        if let Some(addr) = addr.as_concrete() {
+            let dead = self.state.dead_stack.iter().any(|allocation| {
+                addr >= allocation.addr && addr - allocation.addr < allocation.bytes
+            });
+            return if dead {
+                Err(VMError::UseAfterReturn)
+            } else {
+                Ok(())
+            };
+        }
+
+        let dead = self
+            .state
+            .dead_stack
+            .iter()
+            .map(|allocation| {
+                let start = self.solver.bv_from_u64(allocation.addr, addr.len());
+                let end = self
+                    .solver
+                    .bv_from_u64(allocation.addr + allocation.bytes, addr.len());
+                addr.ugte(&start).and(&addr.ult(&end))
+            })
+            .reduce(|acc, c| acc.or(&c));
+
+        match dead {
+            Some(dead) => self.fork_on_error(&dead, VMError::UseAfterReturn),
+            None => Ok(()),
+        }
+    }
+
+    /// Fork the path if `addr` can have a lower alignment than `alignment` bytes, when alignment
+    /// checks are enabled.
+    ///
+    /// The path where it does is terminated with [VMError::MisalignedAccess].
+    fn check_alignment(&mut self, addr: &BV, alignment: u32) -> Result<()> {
+        if !self.alignment_checks || alignment <= 1 {
+            return Ok(());
+        }
+        if !alignment.is_power_of_two() {
+            return Err(VMError::MalformedInstruction);
+        }
+
+        // The address is aligned if the low bits are all zero.
+        let low_bits = alignment.trailing_zeros();
+        let low = addr.slice(0, low_bits - 1);
+        let misaligned = low.ne(&self.solver.bv_zero(low_bits));
+        self.fork_on_error(&misaligned, VMError::MisalignedAccess(alignment))
+    }
+
+    /// Fences order memory operations between threads.
+    ///
+    /// Execution is single-threaded so this does nothing.
     fn fence(&mut self, instr: &instruction::Fence) -> Result<()> {
         debug!("{}", instr);
-        todo!()
+        Ok(())
     }
 
     /// Atomically loads the value and compares it, if equal store a new value.
+    ///
+    /// The original value is returned together with a flag that is set if the value was replaced.
+    /// Execution is single-threaded so the memory ordering is ignored, and `weak` exchanges never
+    /// fail spuriously.
     fn cmpxchg(&mut self, instr: &instruction::CmpXchg) -> Result<()> {
         debug!("{}", instr);
         // All operations are atomic, so just perform the operation.
         let addr = self.state.get_var(&instr.address)?;
+        self.check_null_dereference(&addr)?;
+        self.check_use_after_return(&addr)?;
         let expected = self.state.get_var(&instr.expected)?;
         let replacement = self.state.get_var(&instr.replacement)?;
 
@@ -669,20 +1010,30 @@ impl<'a> VM<'a> {
         let result = condition.ite(&replacement, &current);
 
         // Write the result to memory.
-        self.state.mem.borrow_mut().write(&addr, result.clone())?;
+        self.state.mem.borrow_mut().write(&addr, result)?;
 
-        // The instructions returns a struct of { expected type, condition i1 }.
-        let return_value = condition.concat(&result);
+        // The instructions returns a struct of { original value, condition i1 }.
+        let ty = self.state.type_of(instr);
+        let return_value = layout_members(
+            &ty,
+            [Some(current), Some(condition)],
+            &self.solver,
+            self.project,
+        )?
+        .ok_or(VMError::MalformedInstruction)?;
         self.assign(instr, return_value)
     }
 
     /// Atomically modify memory.
     ///
     /// The contents of the address is atomically read, modified and written back. The original
-    /// value is assigned to the resulting register.
+    /// value is assigned to the resulting register. Execution is single-threaded so the memory
+    /// ordering is ignored.
     fn atomicrmw(&mut self, instr: &instruction::AtomicRMW) -> Result<()> {
         debug!("{}", instr);
         let addr = self.state.get_var(&instr.address)?;
+        self.check_null_dereference(&addr)?;
+        self.check_use_after_return(&addr)?;
         let rhs = self.state.get_var(&instr.value)?;
 
         let lhs = self.state.mem.borrow_mut().read(&addr, rhs.len())?;
@@ -700,8 +1051,11 @@ impl<'a> VM<'a> {
             Min => lhs.slte(&rhs).ite(&lhs, &rhs),
             UMax => lhs.ugte(&rhs).ite(&lhs, &rhs),
             UMin => lhs.ulte(&rhs).ite(&lhs, &rhs),
-            FAdd => todo!(),
-            FSub => todo!(),
+            FAdd | FSub => {
+                return Err(VMError::UnsupportedInstruction(
+                    "Floating point atomicrmw".to_owned(),
+                ))
+            }
         };
         self.state.mem.borrow_mut().write(&addr, result)?;
 
@@ -709,6 +1063,13 @@ impl<'a> VM<'a> {
     }
 
     /// GetElementPtr calculates the offset into an array or struct from a base pointer.
+    ///
+    /// For `inbounds` with symbolic indices, the path forks on whether the result is outside the
+    /// allocation of the base pointer. The out of bounds path is terminated with
+    /// [MemoryError::OutOfBounds]. This requires [VM::with_bounds_checks] to be enabled.
+    ///
+    /// With [VM::with_inbounds_checks] all `inbounds` results are checked against the allocation
+    /// of the base pointer, see [VM::check_inbounds].
     fn getelementptr(&mut self, instr: &instruction::GetElementPtr) -> Result<()> {
         debug!("{}", instr);
         // TODO: Support vector of pointers. Ref: https://llvm.org/docs/LangRef.html#vector-of-pointers
@@ -721,6 +1082,29 @@ impl<'a> VM<'a> {
         )?;
         trace!("gep calculated address: {target_address:?}");
 
+        // Constant indices are generated by the compiler and assumed to be in bounds.
+        let symbolic_index = instr
+            .indices
+            .iter()
+            .any(|index| matches!(index, Operand::LocalOperand { .. }));
+
+        let bounds_checks = self.state.mem.borrow().bounds_checks();
+        if bounds_checks && instr.in_bounds && symbolic_index {
+            let base_address = self.state.get_var(&instr.address)?;
+            let in_bounds = self
+                .state
+                .mem
+                .borrow()
+                .in_bounds_condition(&base_address, &target_address);
+
+            self.fork_on_error(&in_bounds.not(), MemoryError::OutOfBounds.into())?;
+        }
+
+        if self.inbounds_checks && instr.in_bounds {
+            let base_address = self.state.get_var(&instr.address)?;
+            self.check_inbounds(&base_address, &target_address)?;
+        }
+
         self.assign(instr, target_address)
     }
 
@@ -734,6 +1118,7 @@ impl<'a> VM<'a> {
     /// integers, or vectors of integers of the same length.
     fn trunc(&mut self, instr: &instruction::Trunc) -> Result<()> {
         debug!("{}", instr);
+        self.check_cast_size(&instr.operand, &instr.to_type, Ordering::Less)?;
         let symbol = convert_to_map(
             &self.state,
             &instr.to_type,
@@ -749,6 +1134,7 @@ impl<'a> VM<'a> {
     /// integers, or vectors of integers of the same length.
     fn zext(&mut self, instr: &instruction::ZExt) -> Result<()> {
         debug!("{}", instr);
+        self.check_cast_size(&instr.operand, &instr.to_type, Ordering::Greater)?;
         let symbol = convert_to_map(
             &self.state,
             &instr.to_type,
@@ -764,6 +1150,7 @@ impl<'a> VM<'a> {
     /// integers, or vectors of integers of the same length.
     fn sext(&mut self, instr: &instruction::SExt) -> Result<()> {
         debug!("{}", instr);
+        self.check_cast_size(&instr.operand, &instr.to_type, Ordering::Greater)?;
         let symbol = convert_to_map(
             &self.state,
             &instr.to_type,
@@ -773,52 +1160,113 @@ impl<'a> VM<'a> {
         self.assign(instr, symbol)
     }
 
+    /// Check that casting `operand` to `ty` changes the size in the `expected` direction.
+    ///
+    /// Returns [VMError::MalformedInstruction] otherwise. Vectors have the same number of elements
+    /// so the total size can be compared.
+    fn check_cast_size(&self, operand: &Operand, ty: &Type, expected: Ordering) -> Result<()> {
+        let source_bits = self.project.bit_size(&self.state.type_of(operand))?;
+        let target_bits = self.project.bit_size(ty)?;
+        if target_bits.cmp(&source_bits) == expected {
+            Ok(())
+        } else {
+            Err(VMError::MalformedInstruction)
+        }
+    }
+
     /// Convert a floating point value from a larger type to a smaller type.
     ///
-    /// Floating point is currently unsupported. Always returns [VMError::UnsupportedInstruction].
+    /// Floating point is only supported for concrete values, otherwise returns
+    /// [VMError::UnsupportedInstruction].
     fn fptrunc(&mut self, instr: &instruction::FPTrunc) -> Result<()> {
         debug!("{}", instr);
-        Err(VMError::UnsupportedInstruction("fptrunc".to_owned()))
+        let value = self.get_concrete_float(&instr.operand)?;
+        let result = float_to_bv(&self.state, value, &instr.to_type)?;
+        self.assign(instr, result)
     }
 
     /// Convert a floating point value from a smaller type to a larger type.
     ///
-    /// Floating point is currently unsupported. Always returns [VMError::UnsupportedInstruction].
+    /// Floating point is only supported for concrete values, otherwise returns
+    /// [VMError::UnsupportedInstruction].
     fn fpext(&mut self, instr: &instruction::FPExt) -> Result<()> {
         debug!("{}", instr);
-        Err(VMError::UnsupportedInstruction("fpext".to_owned()))
+        let value = self.get_concrete_float(&instr.operand)?;
+        let result = float_to_bv(&self.state, value, &instr.to_type)?;
+        self.assign(instr, result)
     }
 
     /// Convert a floating point to unsigned integer.
     ///
-    /// Floating point is currently unsupported. Always returns [VMError::UnsupportedInstruction].
+    /// The value is truncated towards zero, if it does not fit in the integer the result is poison.
+    /// Floating point is only supported for concrete values, otherwise returns
+    /// [VMError::UnsupportedInstruction].
     fn fptoui(&mut self, instr: &instruction::FPToUI) -> Result<()> {
         debug!("{}", instr);
-        Err(VMError::UnsupportedInstruction("fptoui".to_owned()))
+        let result = self.float_to_int(&instr.operand, &instr.to_type, false)?;
+        self.assign(instr, result)
     }
 
     /// Convert floating point to signed integer.
     ///
-    /// Floating point is currently unsupported. Always returns [VMError::UnsupportedInstruction].
+    /// The value is truncated towards zero, if it does not fit in the integer the result is poison.
+    /// Floating point is only supported for concrete values, otherwise returns
+    /// [VMError::UnsupportedInstruction].
     fn fptosi(&mut self, instr: &instruction::FPToSI) -> Result<()> {
         debug!("{}", instr);
-        Err(VMError::UnsupportedInstruction("fptosi".to_owned()))
+        let result = self.float_to_int(&instr.operand, &instr.to_type, true)?;
+        self.assign(instr, result)
     }
 
     /// Convert unsigned integer to floating point.
     ///
-    /// Floating point is currently unsupported. Always returns [VMError::UnsupportedInstruction].
+    /// Floating point is only supported for concrete values, otherwise returns
+    /// [VMError::UnsupportedInstruction].
     fn uitofp(&mut self, instr: &instruction::UIToFP) -> Result<()> {
         debug!("{}", instr);
-        Err(VMError::UnsupportedInstruction("uitofp".to_owned()))
+        let result = self.int_to_float(&instr.operand, &instr.to_type, false)?;
+        self.assign(instr, result)
     }
 
     /// Convert signed integer to floating point.
     ///
-    /// Floating point is currently unsupported. Always returns [VMError::UnsupportedInstruction].
+    /// Floating point is only supported for concrete values, otherwise returns
+    /// [VMError::UnsupportedInstruction].
     fn sitofp(&mut self, instr: &instruction::SIToFP) -> Result<()> {
         debug!("{}", instr);
-        Err(VMError::UnsupportedInstruction("sitofp".to_owned()))
+        let result = self.int_to_float(&instr.operand, &instr.to_type, true)?;
+        self.assign(instr, result)
+    }
+
+    /// Returns the value of a floating point operand, which must be concrete.
+    fn get_concrete_float(&self, operand: &Operand) -> Result<f64> {
+        get_concrete_float(&self.state, operand)?
+            .ok_or_else(|| VMError::UnsupportedInstruction("Symbolic floating point".to_owned()))
+    }
+
+    /// Convert a floating point operand to an integer of type `ty`.
+    ///
+    /// Values that do not fit in the integer are poison, this is represented by an unconstrained
+    /// value.
+    fn float_to_int(&self, operand: &Operand, ty: &Type, signed: bool) -> Result<BV> {
+        let value = self.get_concrete_float(operand)?;
+        let bits = self.project.bit_size(ty)?;
+        if bits > 64 {
+            return Err(VMError::UnsupportedInstruction(
+                "Floating point to integers larger than 64 bits".to_owned(),
+            ));
+        }
+
+        let result = float_to_int(&self.state, value.trunc(), bits, signed)
+            .unwrap_or_else(|| self.solver.bv_fresh(bits, "havoc"));
+        Ok(result)
+    }
+
+    /// Convert an integer operand to a floating point value of type `ty`.
+    fn int_to_float(&self, operand: &Operand, ty: &Type, signed: bool) -> Result<BV> {
+        let value = get_concrete_int(&self.state, operand, signed)?
+            .ok_or_else(|| VMError::UnsupportedInstruction("Symbolic floating point".to_owned()))?;
+        float_to_bv(&self.state, value as f64, ty)
     }
 
     /// Takes a pointer or a vector of pointers and converts to an integer or a vector of integers.
@@ -827,6 +1275,15 @@ impl<'a> VM<'a> {
     /// happens element by element.
     fn ptrtoint(&mut self, instr: &instruction::PtrToInt) -> Result<()> {
         debug!("{}", instr);
+        if self.provenance_warnings {
+            match instr.to_type.as_ref() {
+                Type::IntegerType { bits } if *bits < self.project.ptr_size => {
+                    warn!("provenance lost, pointer truncated to {bits} bits: {instr}")
+                }
+                _ => warn!("provenance lost, pointer cast to integer: {instr}"),
+            }
+        }
+
         let bv = convert_to_map(
             &self.state,
             &instr.to_type,
@@ -842,6 +1299,10 @@ impl<'a> VM<'a> {
     /// happens element by element.
     fn inttoptr(&mut self, instr: &instruction::IntToPtr) -> Result<()> {
         debug!("{}", instr);
+        if self.provenance_warnings {
+            warn!("provenance lost, integer cast to pointer: {instr}");
+        }
+
         let bv = convert_to_map(
             &self.state,
             &instr.to_type,
@@ -876,12 +1337,7 @@ impl<'a> VM<'a> {
 
     fn icmp(&mut self, instr: &instruction::ICmp) -> Result<()> {
         debug!("{}", instr);
-        let result = icmp(
-            &self.state,
-            &instr.operand0,
-            &instr.operand1,
-            instr.predicate,
-        )?;
+        let result = self.int_icmp(&instr.operand0, &instr.operand1, instr.predicate)?;
         self.assign(instr, result)
     }
 
@@ -898,25 +1354,41 @@ impl<'a> VM<'a> {
         // Phi takes the value where block in [value, block] was the block that was *just* executed.
         debug!("{}", instr);
 
-        if let Some(previous_block) = self.state.current_loc.previous_block {
-            let mut value = None;
-            for (op, block_name) in instr.incoming_values.iter() {
-                if &previous_block.name == block_name {
-                    let symbol = self.state.get_var(op)?;
-                    value = Some(symbol);
-                    break;
-                }
-            }
+        // All phis at the start of a block are evaluated simultaneously, i.e. one phi cannot see
+        // the new value of another. So the first phi handles all of them, and the rest are no-ops.
+        let block = self.state.current_loc.block;
+        let pc = self.state.current_loc.get_instruction_offset();
+        if pc > 0 && matches!(block.instrs[pc - 1], Instruction::Phi(_)) {
+            return Ok(());
+        }
 
-            match value {
-                Some(value) => self.assign(instr, value),
-                None => {
-                    panic!("Could not find previous block in list")
-                }
-            }
-        } else {
-            panic!("No previous basic block");
+        let previous_block = self
+            .state
+            .current_loc
+            .previous_block
+            .ok_or(VMError::MalformedInstruction)?;
+
+        let phis = block.instrs[pc..].iter().map_while(|instr| match instr {
+            Instruction::Phi(phi) => Some(phi),
+            _ => None,
+        });
+
+        let mut values = Vec::new();
+        for phi in phis {
+            let (op, _) = phi
+                .incoming_values
+                .iter()
+                .find(|(_, block_name)| block_name == &previous_block.name)
+                .ok_or(VMError::MalformedInstruction)?;
+
+            values.push((phi, self.state.get_var(op)?));
         }
+
+        for (phi, value) in values {
+            self.assign(phi, value)?;
+        }
+
+        Ok(())
     }
 
     /// Select chooses one value based on a condition.
@@ -1005,12 +1477,12 @@ impl<'a> VM<'a> {
         let current_module = self.state.current_loc.module;
         let name = self.resolve_function(&instr.function)?;
         debug!("resolved function: {}", name);
-        let function = self.project.get_function(&name, current_module)?;
+        let function = self.get_function(&name, current_module)?;
 
         let return_value = match function {
             FunctionType::Hook(hook) => {
-                let info = FnInfo::from_call(instr);
-                hook(self, info)?
+                let info = FnInfo::from_call(instr, &self.state);
+                hook(&mut HookCtx::new(self, info))?
             }
             FunctionType::Function { function, module } => {
                 let arguments = instr
@@ -1038,14 +1510,24 @@ impl<'a> VM<'a> {
         }
     }
 
+    /// Reads the next variadic argument from a `va_list`, and advances it to the next argument.
     fn va_arg(&mut self, instr: &instruction::VAArg) -> Result<()> {
         debug!("{}", instr);
-        todo!()
+        let va_list = self.state.get_var(&instr.arg_list)?;
+        let value = self.read_vararg(&va_list, &instr.cur_type)?;
+        self.assign(instr, value)
     }
 
+    /// Start of a landing pad, the target of an `invoke` when a panic unwinds the stack.
+    ///
+    /// The exception is not modeled in memory, so the result is zero. This catches every panic
+    /// regardless of the clauses, which matches what Rust emits.
     fn landingpad(&mut self, instr: &instruction::LandingPad) -> Result<()> {
         debug!("{}", instr);
-        todo!()
+        let ty = self.state.type_of(instr);
+        let bits = self.project.bit_size(&ty)?;
+        let result = self.solver.bv_zero(bits);
+        self.assign(instr, result)
     }
 
     fn catchpad(&mut self, instr: &instruction::CatchPad) -> Result<()> {
@@ -1134,6 +1616,13 @@ mod tests {
         assert_eq!(res[0], Ok(Some(20)));
     }
 
+    #[test]
+    fn test_udiv_unsigned() {
+        let res = run("test_udiv_unsigned");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(0x7fffffffffffffff)));
+    }
+
     #[test]
     fn test_sdiv() {
         let res = run("test_sdiv");
@@ -1141,6 +1630,13 @@ mod tests {
         assert_eq!(res[0], Ok(Some(-20)));
     }
 
+    #[test]
+    fn test_sdiv_round() {
+        let res = run("test_sdiv_round");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(-3)));
+    }
+
     #[test]
     fn test_urem() {
         let res = run("test_urem");
@@ -1162,6 +1658,53 @@ mod tests {
         assert_eq!(res[0], Ok(Some(-3)));
     }
 
+    #[test]
+    fn test_fadd() {
+        let res = run("test_fadd");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(3.75f64.to_bits() as i64)));
+    }
+
+    #[test]
+    fn test_fadd_symbolic() {
+        let res = run("test_fadd_symbolic");
+        assert_eq!(res.len(), 1);
+        assert_eq!(
+            res[0],
+            Err(VMError::UnsupportedInstruction(
+                "Symbolic floating point".to_owned()
+            ))
+        );
+    }
+
+    #[test]
+    fn test_fsub() {
+        let res = run("test_fsub");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some((-3f64).to_bits() as i64)));
+    }
+
+    #[test]
+    fn test_fmul_f32() {
+        let res = run("test_fmul_f32");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some((-6f32).to_bits() as i64)));
+    }
+
+    #[test]
+    fn test_fdiv() {
+        let res = run("test_fdiv");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(0.125f64.to_bits() as i64)));
+    }
+
+    #[test]
+    fn test_frem() {
+        let res = run("test_frem");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(1.5f64.to_bits() as i64)));
+    }
+
     #[test]
     fn test_and() {
         let res = run("test_and");
@@ -1267,6 +1810,13 @@ mod tests {
         assert_eq!(res[0], Ok(Some(2)));
     }
 
+    #[test]
+    fn test_extract_value_nested() {
+        let res = run("test_extract_value_nested");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(2)));
+    }
+
     #[test]
     fn test_insert_value_arr1() {
         let res = run("test_insert_value_arr1");
@@ -1330,6 +1880,13 @@ mod tests {
         assert_eq!(res[0], Ok(Some(0x000f0001000a0004)));
     }
 
+    #[test]
+    fn test_insert_value_nested() {
+        let res = run("test_insert_value_nested");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(0x000300090001)));
+    }
+
     #[test]
     fn test_load_store1() {
         let res = run("test_load_store1");
@@ -1400,6 +1957,62 @@ mod tests {
         assert_eq!(res[0], Ok(Some(2)));
     }
 
+    #[test]
+    fn test_bitcast_int_to_vec() {
+        let res = run("test_bitcast_int_to_vec");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(0x1122334455667788)));
+    }
+
+    #[test]
+    fn test_bitcast_ptr() {
+        let res = run("test_bitcast_ptr");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(0x1111222233334444)));
+    }
+
+    #[test]
+    fn test_fptrunc() {
+        let res = run("test_fptrunc");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(0.25f32.to_bits() as i64)));
+    }
+
+    #[test]
+    fn test_fpext() {
+        let res = run("test_fpext");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(1.5f64.to_bits() as i64)));
+    }
+
+    #[test]
+    fn test_fptoui() {
+        let res = run("test_fptoui");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(200)));
+    }
+
+    #[test]
+    fn test_fptosi() {
+        let res = run("test_fptosi");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(-3)));
+    }
+
+    #[test]
+    fn test_uitofp() {
+        let res = run("test_uitofp");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(255f64.to_bits() as i64)));
+    }
+
+    #[test]
+    fn test_sitofp() {
+        let res = run("test_sitofp");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some((-1f64).to_bits() as i64)));
+    }
+
     #[test]
     fn test_bitcast1() {
         let res = run("test_bitcast1");
@@ -1442,6 +2055,62 @@ mod tests {
         assert_eq!(res[0], Ok(Some(0xFFFF)));
     }
 
+    #[test]
+    fn test_trunc_i64_i8() {
+        let res = run("test_trunc_i64_i8");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(0xEF)));
+    }
+
+    #[test]
+    fn test_trunc_vec() {
+        let res = run("test_trunc_vec");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(0x0201)));
+    }
+
+    #[test]
+    fn test_zext_i8_i32() {
+        let res = run("test_zext_i8_i32");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(0x80)));
+    }
+
+    #[test]
+    fn test_zext_i1() {
+        let res = run("test_zext_i1");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(1)));
+    }
+
+    #[test]
+    fn test_sext_i8_i32() {
+        let res = run("test_sext_i8_i32");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(0xFFFFFF80)));
+    }
+
+    #[test]
+    fn test_sext_i8_i32_positive() {
+        let res = run("test_sext_i8_i32_positive");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(0x7F)));
+    }
+
+    #[test]
+    fn test_sext_i1() {
+        let res = run("test_sext_i1");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(-1)));
+    }
+
+    #[test]
+    fn test_sext_vec() {
+        let res = run("test_sext_vec");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(0x0001FFFF)));
+    }
+
     #[test]
     fn test_inttoptr_trunc() {
         let res = run("test_inttoptr_trunc");
@@ -1631,6 +2300,20 @@ mod tests {
         assert_eq!(res[0], Ok(Some(0x1)));
     }
 
+    #[test]
+    fn test_icmp_ult_vec() {
+        let res = run("test_icmp_ult_vec");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(0b0101)));
+    }
+
+    #[test]
+    fn test_icmp_eq_vec_i64() {
+        let res = run("test_icmp_eq_vec_i64");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(0b01)));
+    }
+
     #[test]
     fn test_icmp_eq_vec() {
         let res = run("test_icmp_eq_vec");
@@ -1652,6 +2335,21 @@ mod tests {
         assert_eq!(res[0], Ok(Some(0xcd)));
     }
 
+    #[test]
+    fn test_phi_symbolic() {
+        let res = run("test_phi_symbolic");
+        assert_eq!(res.len(), 2);
+        assert_eq!(res[0], Ok(Some(0xab)));
+        assert_eq!(res[1], Ok(Some(0xcd)));
+    }
+
+    #[test]
+    fn test_phi_swap() {
+        let res = run("test_phi_swap");
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0], Ok(Some(0x12)));
+    }
+
     #[test]
     fn test_select1() {
         let res = run("test_select1");
@@ -1687,6 +2385,91 @@ mod tests {
         assert_eq!(res[0], Ok(Some(0xabcd)));
     }
 
+    fn run_tainted(fn_name: &str, tainted_parameters: &[usize]) -> Vec<bool> {
+        let path = format!("./tests/unit_tests/instructions.bc");
+        let project = Project::from_path(&path).expect("Failed to created proejct");
+        let mut vm = VM::new(fn_name, &project).expect("Failed to create VM");
+        for index in tainted_parameters.iter().copied() {
+            vm.taint_parameter(index)
+                .expect("Failed to taint parameter");
+        }
+
+        let mut path_results = Vec::new();
+        while let Some(path_result) = vm.run() {
+            match path_result {
+                Ok(ReturnValue::Value(value)) => path_results.push(value.is_tainted()),
+                _ => panic!("Expected a return value"),
+            }
+        }
+        path_results
+    }
+
+    #[test]
+    fn test_taint_propagates() {
+        let res = run_tainted("test_taint", &[0]);
+        assert_eq!(res, vec![true]);
+
+        let res = run_tainted("test_taint", &[1]);
+        assert_eq!(res, vec![true]);
+    }
+
+    #[test]
+    fn test_taint_through_memory() {
+        let res = run_tainted("test_taint_memory", &[0]);
+        assert_eq!(res, vec![true]);
+
+        let res = run_tainted("test_taint_memory", &[1]);
+        assert_eq!(res, vec![false]);
+    }
+
+    #[test]
+    fn test_taint_untainted() {
+        let res = run_tainted("test_taint", &[]);
+        assert_eq!(res, vec![false]);
+    }
+
+    #[test]
+    fn test_callbr_asm_goto() {
+        let res = run("test_callbr_asm_goto");
+        assert_eq!(res, vec![Ok(Some(1))]);
+    }
+
+    #[test]
+    fn test_callbr_asm_goto_output() {
+        let res = run("test_callbr_asm_goto_output");
+        let expected = VMError::UnsupportedInstruction("callbr".to_owned());
+        assert_eq!(res, vec![Err(expected)]);
+    }
+
+    #[test]
+    fn test_callbr_cfg() {
+        let path = format!("./tests/unit_tests/instructions.bc");
+        let project = Project::from_path(&path).expect("Failed to created proejct");
+        let cfg = project.function_cfg("test_callbr_asm_goto").unwrap();
+        assert!(cfg.contains("\"%0\" -> \"%fallthrough\";"));
+        assert!(cfg.contains("\"%0\" -> \"%jump\" [label=\"indirect\"];"));
+    }
+
+    #[test]
+    fn test_indirectbr() {
+        let res = run("test_indirectbr");
+        let expected = VMError::UnsupportedInstruction("indirectbr".to_owned());
+        assert_eq!(res, vec![Err(expected)]);
+    }
+
+    #[test]
+    fn test_indirectbr_single() {
+        let res = run("test_indirectbr_single");
+        assert_eq!(res, vec![Ok(Some(2))]);
+    }
+
+    #[test]
+    fn test_indirectbr_table() {
+        let res = run("test_indirectbr_table");
+        let expected = VMError::UnsupportedInstruction("indirectbr".to_owned());
+        assert_eq!(res, vec![Err(expected)]);
+    }
+
     #[test]
     fn test_vector_constant() {
         let res = run("test_vector_constant");
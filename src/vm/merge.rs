@@ -0,0 +1,200 @@
+//! Merging of paths where the two sides of a conditional branch join again.
+//!
+//! Without merging every conditional branch where both sides are possible creates a new path. For
+//! short branches that rejoin, e.g. an `if`/`else` that sets a variable, this quickly leads to a
+//! large number of paths that only differ in a few values.
+//!
+//! With merging enabled, both sides of such a branch are executed one after another from the same
+//! state. The resulting states are then merged into one, where every value that differs is an
+//! `ite` on the branch condition. Only branches where each side is a single basic block that jumps
+//! to the same block are merged, and the sides must not call functions or allocate memory. If the
+//! branch cannot be merged execution forks as usual.
+use llvm_ir::{terminator::CondBr, BasicBlock, Instruction, Name, Terminator};
+use log::debug;
+
+use super::{Dfs, Result, State, VM};
+use crate::BV;
+
+impl<'a> VM<'a> {
+    /// Enable or disable merging of paths where the two sides of a branch join.
+    ///
+    /// Merged paths have values that depend on the branch condition, instead of one path for each
+    /// side of the branch.
+    pub fn with_merging(mut self, enabled: bool) -> Self {
+        self.merging = enabled;
+        self
+    }
+
+    /// Try to execute both sides of a conditional branch and merge them where they join.
+    ///
+    /// Both sides of the branch must be possible. On success the current state is the merged
+    /// state, and `true` is returned. If the branch cannot be merged the state is left as is, and
+    /// `false` is returned.
+    pub(super) fn try_merge_branch(&mut self, instr: &CondBr, cond: &BV) -> Result<bool> {
+        let join = match self.find_join(instr) {
+            Some(join) => join,
+            None => return Ok(false),
+        };
+        debug!("merge: trying to merge branch at {}", join);
+
+        let start = self.state.clone();
+        let true_state = self.run_side(&instr.true_dest, join, cond);
+        self.state = start.clone();
+        let false_state = self.run_side(&instr.false_dest, join, &cond.not());
+
+        let merged = match (true_state, false_state) {
+            (Some(mut true_state), Some(false_state)) => {
+                if merge_states(&mut true_state, &false_state, cond)? {
+                    Some(true_state)
+                } else {
+                    None
+                }
+            }
+            _ => None,
+        };
+
+        match merged {
+            Some(state) => {
+                debug!("merge: merged paths at {}", join);
+                self.state = state;
+                Ok(true)
+            }
+            None => {
+                self.state = start;
+                Ok(false)
+            }
+        }
+    }
+
+    /// Returns the block where both sides of the branch join, if the branch can be merged.
+    fn find_join(&self, instr: &CondBr) -> Option<&'a Name> {
+        let func = self.state.current_loc.func;
+        let current = &self.state.current_loc.block.name;
+        let true_block = func.get_bb_by_name(&instr.true_dest)?;
+        let false_block = func.get_bb_by_name(&instr.false_dest)?;
+
+        let join = match (jump_target(true_block), jump_target(false_block)) {
+            // Diamond, both sides jump to the same block.
+            (Some(t), Some(f)) if t == f => t,
+            // Triangle, one side jumps to the other.
+            (Some(t), _) if t == &false_block.name => &false_block.name,
+            (_, Some(f)) if f == &true_block.name => &true_block.name,
+            _ => return None,
+        };
+
+        // Do not merge loops, the join has to be a different block.
+        if join == current || true_block.name == false_block.name {
+            return None;
+        }
+
+        Some(join)
+    }
+
+    /// Execute one side of a branch until the start of `join`, with `cond` asserted.
+    ///
+    /// Returns the state at the first instruction in `join` that is not a `phi`. If the side
+    /// cannot be merged, e.g. if it forks or ends in an error, `None` is returned. The solver and
+    /// the backtracking paths are left as they were before the call.
+    fn run_side(&mut self, target: &Name, join: &Name, cond: &BV) -> Option<State<'a>> {
+        // Collect the paths forked on this side separately, so they can be discarded.
+        let paths = std::mem::replace(&mut self.backtracking_paths, Box::new(Dfs::new()));
+        self.solver.push();
+        self.solver.assert(cond);
+
+        let result = self.execute_side(target, join);
+
+        // Forks push a solver context for each path they save.
+        let forked = self.backtracking_paths.len();
+        for _ in 0..forked {
+            self.solver.pop();
+        }
+        self.backtracking_paths = paths;
+        self.solver.pop();
+
+        match result {
+            Ok(true) if forked == 0 => Some(self.state.clone()),
+            _ => None,
+        }
+    }
+
+    fn execute_side(&mut self, target: &Name, join: &Name) -> Result<bool> {
+        if target != join {
+            let block = self.state.current_loc.func.get_bb_by_name(target).unwrap();
+            if !block.instrs.iter().all(is_mergeable) {
+                return Ok(false);
+            }
+
+            // Runs until the terminator jumps to `join`.
+            self.branch(target)?;
+            self.execute_basic_block()?;
+        } else {
+            self.branch(join)?;
+        }
+
+        // Phis depend on the block that was just executed, so they have to be evaluated before
+        // the states are merged.
+        let block = self.state.current_loc.block;
+        let phis = block
+            .instrs
+            .iter()
+            .take_while(|instr| matches!(instr, Instruction::Phi(_)))
+            .count();
+
+        for (pc, instr) in block.instrs.iter().enumerate().take(phis) {
+            self.state.current_loc.set_location(pc);
+            self.process_instruction(instr)?;
+        }
+
+        if phis < block.instrs.len() {
+            self.state.current_loc.set_location(phis);
+        } else {
+            self.state.current_loc.set_terminated(&block.term);
+        }
+
+        Ok(true)
+    }
+}
+
+/// Returns the target of an unconditional branch.
+fn jump_target(block: &BasicBlock) -> Option<&Name> {
+    match &block.term {
+        Terminator::Br(br) => Some(&br.dest),
+        _ => None,
+    }
+}
+
+/// Returns `true` if the instruction only affects the local state and memory.
+fn is_mergeable(instr: &Instruction) -> bool {
+    !matches!(
+        instr,
+        Instruction::Alloca(_)
+            | Instruction::Call(_)
+            | Instruction::VAArg(_)
+            | Instruction::LandingPad(_)
+            | Instruction::CatchPad(_)
+            | Instruction::CleanupPad(_)
+            | Instruction::CmpXchg(_)
+            | Instruction::AtomicRMW(_)
+    )
+}
+
+/// Merge `other` into `state`, where `state` is taken when `cond` is true.
+///
+/// Returns `false` if the states cannot be merged, in that case `state` may be partially merged
+/// and should be discarded.
+fn merge_states(state: &mut State<'_>, other: &State<'_>, cond: &BV) -> Result<bool> {
+    // Globals are initialized when first used, and that cannot be expressed in one state if only
+    // one of the sides used it.
+    if state.global_references.initialized_variables()
+        != other.global_references.initialized_variables()
+    {
+        return Ok(false);
+    }
+
+    if !state.mem.borrow_mut().merge(&other.mem.borrow(), cond) {
+        return Ok(false);
+    }
+    state.vars.merge(&other.vars, cond)?;
+
+    Ok(true)
+}
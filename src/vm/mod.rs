@@ -1,24 +1,53 @@
+use anyhow::anyhow;
 use either::Either;
 use llvm_ir::{
+    function::{Parameter, ParameterAttribute},
     instruction::{HasResult, InlineAssembly},
-    Constant, Function, Name, Operand,
+    Constant, Function, Instruction, Name, Operand, Type, TypeRef,
 };
 use log::{debug, trace};
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use rustc_demangle::demangle;
+use std::{
+    collections::{HashMap, VecDeque},
+    time::Instant,
+};
 
 use crate::{
     common::SolutionVariable,
-    project::{ModuleHandle, Project},
+    hooks::{is_intrinsic, Intrinsics},
+    memory::NULL_PAGE_SIZE,
+    project::{FunctionType, ModuleHandle, Project},
     solver::{Solutions, Solver, BV},
 };
 
+mod argv;
+mod budget;
+mod concretize;
+mod ctors;
 mod error;
 mod globals;
 mod instructions;
+mod merge;
+mod native;
+mod replay;
+mod report;
+mod scheduler;
 mod state;
+mod stats;
+mod summary;
+mod varargs;
 
-pub use error::{Result, VMError};
+pub use argv::*;
+pub use budget::Budget;
+pub use concretize::ConcretizationPolicy;
+pub use error::{PanicReason, Result, VMError};
 pub use globals::*;
+pub use replay::Model;
+pub use report::{ErrorLocation, ErrorReport};
+pub use scheduler::*;
 pub use state::*;
+pub use stats::ForkStats;
 
 #[derive(Debug, PartialEq, Eq)]
 pub enum ReturnValue {
@@ -27,6 +56,87 @@ pub enum ReturnValue {
     Void,
 }
 
+/// Maximum number of solutions to count when displaying a symbolic [ReturnValue].
+const DISPLAY_MAX_SOLUTIONS: usize = 16;
+
+impl ReturnValue {
+    /// Render the return value using the current constraints in `solver`.
+    ///
+    /// A value with a single solution is rendered as an integer, interpreted as signed if `signed`
+    /// is set. Otherwise the value is rendered as `symbolic (N solutions)`.
+    pub fn display(&self, solver: &Solver, signed: bool) -> Result<String> {
+        let value = match self {
+            ReturnValue::Value(value) => value,
+            ReturnValue::Void => return Ok("void".to_owned()),
+        };
+
+        let solutions = solver.get_solutions_for_bv(value, DISPLAY_MAX_SOLUTIONS)?;
+        let rendered = match solutions {
+            Solutions::None => return Err(VMError::Unsat),
+            Solutions::Exactly(solutions) if solutions.len() == 1 => {
+                display_integer(solutions[0].as_01x_str(), signed)
+            }
+            Solutions::Exactly(solutions) => format!("symbolic ({} solutions)", solutions.len()),
+            Solutions::AtLeast(solutions) => {
+                format!("symbolic (at least {} solutions)", solutions.len())
+            }
+        };
+
+        Ok(rendered)
+    }
+}
+
+/// Render a binary string as an integer. Values wider than 128 bits are rendered in hexadecimal.
+fn display_integer(binary_str: &str, signed: bool) -> String {
+    let bits = binary_str.len();
+    let value = match u128::from_str_radix(binary_str, 2) {
+        Ok(value) if bits <= 128 => value,
+        _ => return display_hex(binary_str),
+    };
+
+    let negative = binary_str.starts_with('1');
+    if signed && negative {
+        let value = if bits == 128 {
+            value as i128
+        } else {
+            value as i128 - (1 << bits)
+        };
+        value.to_string()
+    } else {
+        value.to_string()
+    }
+}
+
+/// Render a binary string of any width in hexadecimal.
+fn display_hex(binary_str: &str) -> String {
+    // Pad the most significant nibble, so the digits line up with the least significant bit.
+    let padding = (4 - binary_str.len() % 4) % 4;
+    let padded = format!("{}{}", "0".repeat(padding), binary_str);
+
+    let digits: String = padded
+        .as_bytes()
+        .chunks(4)
+        .map(|nibble| {
+            let nibble = std::str::from_utf8(nibble).unwrap();
+            match u32::from_str_radix(nibble, 2) {
+                Ok(digit) => char::from_digit(digit, 16).unwrap(),
+                // Bits that can have any value.
+                Err(_) => 'x',
+            }
+        })
+        .collect();
+
+    format!("0x{digits}")
+}
+
+/// Returns true if `param` is passed `byval`, i.e. as a pointer to a copy owned by the callee.
+fn is_byval(param: &Parameter) -> bool {
+    param
+        .attributes
+        .iter()
+        .any(|attribute| matches!(attribute, ParameterAttribute::ByVal(_)))
+}
+
 pub enum TerminatorResult {
     Return(Option<BV>),
 
@@ -42,12 +152,80 @@ pub struct VM<'a> {
     /// Current state the VM uses for its execution.
     pub state: State<'a>,
 
-    backtracking_paths: Vec<Path<'a>>,
+    /// Paths left to explore, see [VM::with_scheduler].
+    backtracking_paths: Box<dyn Scheduler<'a> + 'a>,
 
     pub solver: Solver,
 
     /// Parameters passed to the initial entry function.
     pub parameters: Vec<SolutionVariable>,
+
+    /// Environment variables that are symbolic, with their length in bytes.
+    symbolic_env_vars: HashMap<String, u32>,
+
+    /// Resource limits for each path.
+    budget: Budget,
+
+    /// Resources used by the current path.
+    budget_usage: budget::BudgetUsage,
+
+    /// Deadline for exploring all paths, see [VM::run_all_with_deadline].
+    deadline: Option<Instant>,
+
+    /// Values for symbols that have not yet been created when replaying a [Model].
+    replay_symbols: VecDeque<String>,
+
+    /// How symbolic values that must be concrete are resolved.
+    concretization_policy: ConcretizationPolicy,
+
+    /// If paths should be merged where the two sides of a branch join, see [VM::with_merging].
+    merging: bool,
+
+    /// Warn when pointers lose their provenance, see [VM::with_provenance_warnings].
+    provenance_warnings: bool,
+
+    /// Stop exploring paths after the first error, see [VM::with_fail_fast].
+    fail_fast: bool,
+
+    /// Only check if paths are satisfiable when they end, see [VM::with_lazy_constraints].
+    lazy_constraints: bool,
+
+    /// Check loads and stores for null pointers, see [VM::with_null_checks].
+    null_checks: bool,
+
+    /// Check loads and stores for dead stack allocations, see
+    /// [VM::with_use_after_return_checks].
+    use_after_return_checks: bool,
+
+    /// Check the alignment of loads and stores, see [VM::with_alignment_checks].
+    alignment_checks: bool,
+
+    /// Check that `getelementptr inbounds` stays in its allocation, see [VM::with_inbounds_checks].
+    inbounds_checks: bool,
+
+    /// Treat `add`, `sub` and `mul` as `nsw`, see [VM::with_nsw_checks].
+    nsw_checks: bool,
+
+    /// Treat `add`, `sub` and `mul` as `nuw`, see [VM::with_nuw_checks].
+    nuw_checks: bool,
+
+    /// Treat `udiv` and `sdiv` as `exact`, see [VM::with_exact_checks].
+    exact_checks: bool,
+
+    /// Evaluate operations on constants natively, see [VM::with_concrete_fast_path].
+    concrete_fast_path: bool,
+
+    /// Random number generator for randomized choices, see [VM::rng]. Created when first used.
+    rng: Option<StdRng>,
+
+    /// If a seed has been set with [VM::with_random_seed].
+    seeded: bool,
+
+    /// Statistics on the forks of all paths, see [VM::fork_stats].
+    fork_stats: ForkStats,
+
+    /// Intrinsics that are used instead of the ones of the project, see [VM::intrinsics_mut].
+    intrinsics: Option<Intrinsics>,
 }
 
 impl<'a> Clone for VM<'a> {
@@ -55,13 +233,40 @@ impl<'a> Clone for VM<'a> {
         Self {
             project: self.project,
             state: self.state.clone(),
-            backtracking_paths: self.backtracking_paths.clone(),
+            backtracking_paths: self.backtracking_paths.box_clone(),
             solver: self.solver.duplicate(),
             parameters: self.parameters.clone(),
+            symbolic_env_vars: self.symbolic_env_vars.clone(),
+            budget: self.budget,
+            budget_usage: self.budget_usage,
+            deadline: self.deadline,
+            replay_symbols: self.replay_symbols.clone(),
+            concretization_policy: self.concretization_policy,
+            merging: self.merging,
+            provenance_warnings: self.provenance_warnings,
+            fail_fast: self.fail_fast,
+            lazy_constraints: self.lazy_constraints,
+            null_checks: self.null_checks,
+            use_after_return_checks: self.use_after_return_checks,
+            alignment_checks: self.alignment_checks,
+            inbounds_checks: self.inbounds_checks,
+            nsw_checks: self.nsw_checks,
+            nuw_checks: self.nuw_checks,
+            exact_checks: self.exact_checks,
+            concrete_fast_path: self.concrete_fast_path,
+            rng: self.rng.clone(),
+            seeded: self.seeded,
+            fork_stats: self.fork_stats,
+            intrinsics: self.intrinsics.clone(),
         }
     }
 }
 
+/// Iterates over the results of the paths, the same as calling [VM::run] repeatedly.
+///
+/// Paths are explored on demand, each call to `next` runs until one more path has ended. Paths
+/// found along the way are saved but not explored, so only the results that are consumed have to
+/// be kept in memory.
 impl<'a> Iterator for VM<'a> {
     type Item = Result<ReturnValue>;
 
@@ -70,6 +275,31 @@ impl<'a> Iterator for VM<'a> {
     }
 }
 
+/// Iterator over the paths of a [VM] that ended in an error, see [VM::error_paths].
+pub struct ErrorPaths<'b, 'a> {
+    vm: &'b mut VM<'a>,
+}
+
+impl<'b, 'a> ErrorPaths<'b, 'a> {
+    /// Returns the VM, with the state as it was when the last error occurred.
+    pub fn vm(&self) -> &VM<'a> {
+        self.vm
+    }
+}
+
+impl<'b, 'a> Iterator for ErrorPaths<'b, 'a> {
+    type Item = VMError;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            match self.vm.run()? {
+                Ok(_) => continue,
+                Err(error) => return Some(error),
+            }
+        }
+    }
+}
+
 impl<'a> VM<'a> {
     /// Creates a new VM for a project that starts in the given function.
     ///
@@ -85,9 +315,31 @@ impl<'a> VM<'a> {
         let mut vm = VM {
             state,
             project,
-            backtracking_paths: Vec::new(),
+            backtracking_paths: Box::new(Dfs::new()),
             solver,
             parameters: Vec::new(),
+            symbolic_env_vars: HashMap::new(),
+            budget: Budget::default(),
+            budget_usage: budget::BudgetUsage::new(),
+            deadline: None,
+            replay_symbols: VecDeque::new(),
+            concretization_policy: ConcretizationPolicy::default(),
+            merging: false,
+            provenance_warnings: false,
+            fail_fast: false,
+            lazy_constraints: false,
+            null_checks: false,
+            use_after_return_checks: false,
+            alignment_checks: false,
+            inbounds_checks: false,
+            nsw_checks: false,
+            nuw_checks: false,
+            exact_checks: false,
+            concrete_fast_path: true,
+            rng: None,
+            seeded: false,
+            fork_stats: ForkStats::default(),
+            intrinsics: None,
         };
 
         // Setup before the execution of a function can start.
@@ -104,15 +356,80 @@ impl<'a> VM<'a> {
         Ok(vm)
     }
 
+    /// Creates a new VM that starts in the basic block `block_label` of the given function.
+    ///
+    /// All parameters and all locals defined in the function are bound to fresh symbols, so the
+    /// values that live into the block are unconstrained. Phis at the start of the block are not
+    /// executed since there is no previous block, their results are also fresh symbols. The
+    /// symbols for the locals are available in [State::symbols].
+    ///
+    /// This is not sound in general. Values that are unconstrained may be impossible when reaching
+    /// the block from the start of the function, e.g. pointers to stack allocations can alias
+    /// each other or be null. So paths that are found may not be reachable in a real execution.
+    pub fn new_at(fn_name: &str, block_label: &Name, project: &'a Project) -> Result<Self> {
+        let mut vm = Self::new(fn_name, project)?;
+        debug!("Starting at basic block {}", block_label);
+
+        // Replace the path at the start of the function with one at the basic block.
+        vm.backtracking_paths.clear();
+        vm.solver.pop();
+
+        let function = vm.state.current_loc.func;
+        let demangled = format!("{:#}", demangle(&function.name));
+        for instr in function.basic_blocks.iter().flat_map(|block| &block.instrs) {
+            let name = match instr.try_get_result() {
+                Some(name) => name,
+                None => continue,
+            };
+            let size = project.bit_size(&vm.state.type_of(instr))?;
+            if size == 0 {
+                continue;
+            }
+
+            let local = name.to_string();
+            let local = local.trim_start_matches('%');
+            let symbol_name = vm.solver.fresh_name(&format!("local_{demangled}_{local}"));
+            let bv = vm.solver.bv(size, &symbol_name);
+            vm.state.vars.insert(name.clone(), bv.clone())?;
+            vm.state.symbols.push(SolutionVariable {
+                name: symbol_name,
+                value: bv,
+                ty: Some(vm.state.type_of(instr)),
+            });
+        }
+
+        let mut location = Location::jump_bb(vm.state.current_loc.clone(), block_label)?;
+        location.previous_block = None;
+        let block = location.block;
+        let num_phis = block
+            .instrs
+            .iter()
+            .take_while(|instr| matches!(instr, Instruction::Phi(_)))
+            .count();
+        if num_phis == block.instrs.len() {
+            location.set_terminated(&block.term);
+        } else if num_phis > 0 {
+            location.set_location(num_phis);
+        }
+
+        vm.state.current_loc = location;
+        vm.save_backtracking_path_at_current(None)?;
+
+        Ok(vm)
+    }
+
     // Helper to create unconstrained symbols for all parameters.
     fn setup_parameters(&mut self) -> Result<()> {
-        for param in self.state.current_loc.func.parameters.iter() {
+        let fn_name = format!("{:#}", demangle(&self.state.current_loc.func.name));
+        for (index, param) in self.state.current_loc.func.parameters.iter().enumerate() {
             let size = self.project.bit_size(&param.ty)?;
             assert_ne!(size, 0);
 
-            let bv = self.solver.bv(size as u32, &param.name.to_string());
+            let name = self.solver.fresh_name(&format!("input_{fn_name}_{index}"));
+            let bv = self.solver.bv(size as u32, &name);
+
             let solution_var = SolutionVariable {
-                name: param.name.to_string(),
+                name,
                 value: bv.clone(),
                 ty: Some(param.ty.clone()),
             };
@@ -124,11 +441,432 @@ impl<'a> VM<'a> {
         Ok(())
     }
 
+    /// Returns a condition that the parameters of the entry function that are known to be
+    /// dereferenceable are outside the null page.
+    fn non_null_parameters(&self) -> BV {
+        let mut condition = self.solver.bv_from_bool(true);
+
+        let parameters = self.state.current_loc.func.parameters.iter();
+        for (param, variable) in parameters.zip(self.parameters.iter()) {
+            let non_null = param.attributes.iter().any(|attribute| {
+                matches!(
+                    attribute,
+                    ParameterAttribute::NonNull
+                        | ParameterAttribute::Dereferenceable(_)
+                        | ParameterAttribute::SRet(_)
+                )
+            });
+            if non_null {
+                let value = &variable.value;
+                let null_page = self.solver.bv_from_u64(NULL_PAGE_SIZE, value.len());
+                condition = condition.and(&value.ugte(&null_page));
+            }
+        }
+
+        condition
+    }
+
+    /// Mark the parameter at `index` of the entry function as tainted.
+    ///
+    /// All values derived from the parameter will be tainted as well, including values stored to
+    /// and loaded from memory. So after a path has finished [BV::is_tainted] on the return value
+    /// tells if it depends on the parameter. Dependencies through control flow are not tracked.
+    /// This should be called before any paths have been executed.
+    pub fn taint_parameter(&mut self, index: usize) -> Result<()> {
+        let parameter = self
+            .parameters
+            .get_mut(index)
+            .ok_or(VMError::InternalError("Parameter index out of bounds"))?;
+        parameter.value = parameter.value.taint();
+        let value = parameter.value.clone();
+
+        // The parameters are already bound in the initial path, so these have to be updated.
+        let name = self.state.current_loc.func.parameters[index].name.clone();
+        let mut paths = Vec::new();
+        while let Some(mut path) = self.backtracking_paths.pop() {
+            path.state.vars.insert(name.clone(), value.clone())?;
+            paths.push(path);
+        }
+        // Push them back in the order they were saved.
+        for path in paths.into_iter().rev() {
+            self.backtracking_paths.push(path);
+        }
+        self.state.vars.insert(name, value)
+    }
+
+    /// Constrain the parameter at `index` of the entry function to the range `min..=max`.
+    ///
+    /// The bounds are compared unsigned and must fit in the size of the parameter. Paths that
+    /// require the parameter to be outside the range are never explored. This should be called
+    /// before any paths have been executed.
+    pub fn constrain_input(&mut self, index: usize, min: u64, max: u64) -> Result<()> {
+        let value = self.parameter_value(index)?;
+        let bits = value.len();
+        if bits < 64 && max >> bits != 0 {
+            return Err(VMError::Other(anyhow!(
+                "Range {min}..={max} does not fit parameter {index} of {bits} bits"
+            )));
+        }
+        if min > max {
+            return Err(VMError::Other(anyhow!("Range {min}..={max} is empty")));
+        }
+
+        self.modify_initial_path(|vm| {
+            let min = vm.solver.bv_from_u64(min, 64).resize_unsigned(bits);
+            let max = vm.solver.bv_from_u64(max, 64).resize_unsigned(bits);
+            vm.solver.assert(&value.ugte(&min));
+            vm.solver.assert(&value.ulte(&max));
+            Ok(())
+        })
+    }
+
+    /// Constrain the parameter at `index` of the entry function to the signed range `min..=max`.
+    ///
+    /// Like [VM::constrain_input], but the bounds are compared signed and must fit in the size of
+    /// the parameter as signed values.
+    pub fn constrain_input_signed(&mut self, index: usize, min: i64, max: i64) -> Result<()> {
+        let value = self.parameter_value(index)?;
+        let bits = value.len();
+        if bits < 64 {
+            let limit = 1i64 << (bits - 1);
+            if min < -limit || max >= limit {
+                return Err(VMError::Other(anyhow!(
+                    "Range {min}..={max} does not fit parameter {index} of {bits} bits"
+                )));
+            }
+        }
+        if min > max {
+            return Err(VMError::Other(anyhow!("Range {min}..={max} is empty")));
+        }
+
+        self.modify_initial_path(|vm| {
+            let min = vm.solver.bv_from_u64(min as u64, 64).resize_signed(bits);
+            let max = vm.solver.bv_from_u64(max as u64, 64).resize_signed(bits);
+            vm.solver.assert(&value.sgte(&min));
+            vm.solver.assert(&value.slte(&max));
+            Ok(())
+        })
+    }
+
+    /// Returns the value of the parameter at `index` of the entry function.
+    fn parameter_value(&self, index: usize) -> Result<BV> {
+        let parameter = self.parameters.get(index).ok_or_else(|| {
+            let count = self.parameters.len();
+            VMError::Other(anyhow!(
+                "Parameter index {index} out of bounds, the function has {count} parameters"
+            ))
+        })?;
+        Ok(parameter.value.clone())
+    }
+
+    /// Helper to modify the initial path before execution has started.
+    ///
+    /// Constraints added to the solver after the initial path has been saved are lost when the
+    /// path is resumed, so the path is removed, modified by `f`, and then saved again.
+    fn modify_initial_path(&mut self, f: impl FnOnce(&mut Self) -> Result<()>) -> Result<()> {
+        if self.backtracking_paths.len() != 1 {
+            return Err(VMError::InternalError(
+                "Initial path can only be modified before execution",
+            ));
+        }
+
+        let path = self.backtracking_paths.pop().unwrap();
+        self.solver.pop();
+        self.state = path.state;
+
+        f(self)?;
+
+        let bb_label = self.state.current_loc.block.name.clone();
+        self.save_backtracking_path(&bb_label, None)
+    }
+
+    /// Make the environment variable `name` symbolic.
+    ///
+    /// Calls to `getenv` with `name` return either null, or a null terminated string of `len`
+    /// unconstrained bytes. All other environment variables are treated as unset.
+    pub fn set_symbolic_env_var(&mut self, name: impl Into<String>, len: u32) {
+        self.symbolic_env_vars.insert(name.into(), len);
+    }
+
+    /// Returns the length in bytes of the environment variable `name` if it is symbolic.
+    pub fn symbolic_env_var(&self, name: &str) -> Option<u32> {
+        self.symbolic_env_vars.get(name).copied()
+    }
+
+    /// Enable or disable warnings when pointers are cast to and from integers.
+    ///
+    /// Memory accesses are not checked against the allocation a pointer was derived from, so the
+    /// casts always succeed. The warnings help to find where pointers lose their provenance.
+    pub fn with_provenance_warnings(mut self, enabled: bool) -> Self {
+        self.provenance_warnings = enabled;
+        self
+    }
+
+    /// Enable or disable stopping at the first path that ends in an error.
+    ///
+    /// When enabled the error is returned as usual, and all remaining paths are discarded so the
+    /// next call to [VM::run] returns `None`. Paths found to be unsatisfiable do not count as
+    /// errors. Branches where one side ends in `unreachable`, such as a call to a panic handler,
+    /// explore that side first so an error is found before any path that returns.
+    pub fn with_fail_fast(mut self, enabled: bool) -> Self {
+        self.fail_fast = enabled;
+        self
+    }
+
+    /// Enable or disable deferring the satisfiability checks of paths until they end.
+    ///
+    /// When enabled, branches fork to every successor whose condition is not constant false
+    /// without asking the solver. The full path condition is instead solved once the path ends,
+    /// and paths that turn out to be unsatisfiable are dropped without being returned. This saves
+    /// solver calls when branches are cheap but the constraints are expensive, at the cost of
+    /// executing infeasible paths until they end.
+    pub fn with_lazy_constraints(mut self, enabled: bool) -> Self {
+        self.lazy_constraints = enabled;
+        self
+    }
+
+    /// Apply `update` to all paths that have been saved but not yet explored.
+    fn update_pending_paths(&mut self, mut update: impl FnMut(&mut Path<'a>)) {
+        let mut paths = Vec::new();
+        while let Some(mut path) = self.backtracking_paths.pop() {
+            update(&mut path);
+            paths.push(path);
+        }
+        for path in paths.into_iter().rev() {
+            self.backtracking_paths.push(path);
+        }
+    }
+
+    /// Use `scheduler` to pick the order that paths are explored in.
+    ///
+    /// The default is depth-first search with [Dfs]. Paths that have already been saved are moved
+    /// to the new scheduler.
+    pub fn with_scheduler(mut self, scheduler: impl Scheduler<'a> + 'a) -> Self {
+        let mut paths = Vec::new();
+        while let Some(path) = self.backtracking_paths.pop() {
+            paths.push(path);
+        }
+
+        self.backtracking_paths = Box::new(scheduler);
+        if self.seeded {
+            let seed = self.rng().gen();
+            self.backtracking_paths.set_seed(seed);
+        }
+        for path in paths.into_iter().rev() {
+            self.backtracking_paths.push(path);
+        }
+        self
+    }
+
+    /// Seed the random number generator of the VM, so randomized choices are reproducible.
+    ///
+    /// The scheduler is reseeded from it, see [Scheduler::set_seed], and hooks can draw from it
+    /// with [VM::rng]. Without a seed the generator is seeded from the system.
+    pub fn with_random_seed(mut self, seed: u64) -> Self {
+        self.seeded = true;
+        let seed = self.rng.insert(StdRng::seed_from_u64(seed)).gen();
+        self.backtracking_paths.set_seed(seed);
+        self
+    }
+
+    /// Returns the random number generator of the VM.
+    ///
+    /// Hooks that make random choices should use this, so the choices are reproducible when a
+    /// seed is set with [VM::with_random_seed]. Without a seed the generator is seeded from the
+    /// system the first time it is used.
+    pub fn rng(&mut self) -> &mut StdRng {
+        self.rng.get_or_insert_with(StdRng::from_entropy)
+    }
+
+    /// Enable or disable checking loads and stores for null pointer dereferences.
+    ///
+    /// When enabled, a path is forked if the address can be in the null page, and the path where
+    /// it is terminates with [VMError::NullPointerDereference]. Parameters of the entry function
+    /// marked `nonnull`, `dereferenceable` or `sret` are then assumed to be outside the null page.
+    pub fn with_null_checks(mut self, enabled: bool) -> Self {
+        if enabled && !self.null_checks {
+            // The parameters are already bound in the initial path, so it is constrained there.
+            let condition = self.non_null_parameters();
+            self.update_pending_paths(|path| {
+                path.constraint = match &path.constraint {
+                    Some(constraint) => Some(constraint.and(&condition)),
+                    None => Some(condition.clone()),
+                };
+            });
+        }
+
+        self.null_checks = enabled;
+        self
+    }
+
+    /// Enable or disable checking loads and stores for use of stack memory after it is freed.
+    ///
+    /// When enabled, a path is forked if the address can point into a stack allocation of a
+    /// function that has returned, or one freed by `llvm.stackrestore`, and the path where it does
+    /// terminates with [VMError::UseAfterReturn].
+    pub fn with_use_after_return_checks(mut self, enabled: bool) -> Self {
+        self.use_after_return_checks = enabled;
+        self
+    }
+
+    /// Enable or disable checking that loads and stores are aligned.
+    ///
+    /// When enabled, a path is forked if the address can have a lower alignment than the one
+    /// given on the instruction, and the path where it does terminates with
+    /// [VMError::MisalignedAccess].
+    pub fn with_alignment_checks(mut self, enabled: bool) -> Self {
+        self.alignment_checks = enabled;
+        self
+    }
+
+    /// Enable or disable checking that `getelementptr inbounds` stays in its allocation.
+    ///
+    /// When enabled, a path is forked if the computed address can be outside the allocation of the
+    /// base pointer, or more than one byte past its end. The result is poison in that case, so the
+    /// path where it is terminates with [VMError::InboundsViolation].
+    pub fn with_inbounds_checks(mut self, enabled: bool) -> Self {
+        self.inbounds_checks = enabled;
+        self
+    }
+
+    /// Enable or disable treating `add`, `sub` and `mul` as if they have the `nsw` flag.
+    ///
+    /// The `nsw` and `nuw` flags are not exposed by `llvm_ir`, so they cannot be read from the
+    /// instructions. When enabled, a path is forked if the operation can overflow as signed
+    /// integers, and the path where it does terminates with [VMError::PoisonOverflow]. This finds
+    /// signed overflow in C, which is compiled to `nsw` operations, but also flags overflow of
+    /// operations that are allowed to wrap.
+    pub fn with_nsw_checks(mut self, enabled: bool) -> Self {
+        self.nsw_checks = enabled;
+        self
+    }
+
+    /// Enable or disable treating `add`, `sub` and `mul` as if they have the `nuw` flag.
+    ///
+    /// Works as [VM::with_nsw_checks], but checks for overflow as unsigned integers.
+    pub fn with_nuw_checks(mut self, enabled: bool) -> Self {
+        self.nuw_checks = enabled;
+        self
+    }
+
+    /// Enable or disable treating `udiv` and `sdiv` as if they have the `exact` flag.
+    ///
+    /// The `exact` flag is not exposed by `llvm_ir`, so it cannot be read from the instructions.
+    /// When enabled, a path is forked if the division can have a nonzero remainder, and the path
+    /// where it does terminates with [VMError::InexactDivision]. This is useful for code that
+    /// expects its divisions to be exact, as with `exact_div` in Rust.
+    pub fn with_exact_checks(mut self, enabled: bool) -> Self {
+        self.exact_checks = enabled;
+        self
+    }
+
+    /// Enable or disable checking that memory accesses stay in their allocation.
+    ///
+    /// When enabled, reads that cross an allocation boundary terminate with
+    /// [MemoryError::OutOfBounds](crate::memory::MemoryError::OutOfBounds), and so do paths where
+    /// `getelementptr inbounds` with a symbolic index can leave the allocation of the base pointer.
+    /// See [Memory::set_bounds_checks](crate::memory::Memory::set_bounds_checks).
+    pub fn with_bounds_checks(mut self, enabled: bool) -> Self {
+        self.state.mem.borrow_mut().set_bounds_checks(enabled);
+        self.update_pending_paths(|path| path.state.mem.borrow_mut().set_bounds_checks(enabled));
+        self
+    }
+
+    /// Enable or disable evaluating integer operations on constants natively.
+    ///
+    /// Enabled by default. When both operands are constant the result is computed without the
+    /// solver, which speeds up paths that are mostly concrete. The results are the same either way.
+    pub fn with_concrete_fast_path(mut self, enabled: bool) -> Self {
+        self.concrete_fast_path = enabled;
+        self
+    }
+
+    /// Returns the output written by `puts` and `printf` on the current path.
+    pub fn output(&self) -> &str {
+        &self.state.output
+    }
+
+    /// Returns the intrinsic hooks used by this VM.
+    pub fn intrinsics(&self) -> &Intrinsics {
+        match &self.intrinsics {
+            Some(intrinsics) => intrinsics,
+            None => self.project.hooks().intrinsics(),
+        }
+    }
+
+    /// Returns the intrinsic hooks used by this VM, these can be used to replace or remove the
+    /// default intrinsics.
+    ///
+    /// The VM starts out with the intrinsics of the project, changes only affect this VM. They
+    /// should be done before any paths have been executed.
+    pub fn intrinsics_mut(&mut self) -> &mut Intrinsics {
+        let project = self.project;
+        self.intrinsics
+            .get_or_insert_with(|| project.hooks().intrinsics().clone())
+    }
+
+    /// Returns the function or hook that is called for `name`, see [Project::get_function].
+    fn get_function(&self, name: &str, module: ModuleHandle) -> Result<FunctionType<'a>> {
+        match &self.intrinsics {
+            Some(intrinsics) if is_intrinsic(name) => match intrinsics.get(name) {
+                Some(hook) => Ok(FunctionType::Hook(*hook)),
+                None => Err(VMError::UnsupportedIntrinsic(name.to_owned())),
+            },
+            _ => self.project.get_function(name, module),
+        }
+    }
+
+    /// Returns the value the entry function returned through its `sret` parameter, if it has one.
+    ///
+    /// Large aggregates are returned by writing them to memory pointed to by a parameter marked
+    /// `sret`, so the function itself returns void. This reads the value back from memory
+    /// together with its type, and should be called after a path has finished.
+    pub fn sret_value(&self) -> Result<Option<(BV, TypeRef)>> {
+        let entry = match self.state.callstack.first() {
+            Some(callsite) => callsite.location.func,
+            None => self.state.current_loc.func,
+        };
+
+        let index = entry.parameters.iter().position(|parameter| {
+            parameter
+                .attributes
+                .iter()
+                .any(|attribute| matches!(attribute, ParameterAttribute::SRet(_)))
+        });
+        let (index, parameter) = match index {
+            Some(index) => (index, &entry.parameters[index]),
+            None => return Ok(None),
+        };
+
+        let ty = match parameter.ty.as_ref() {
+            Type::PointerType { pointee_type, .. } => pointee_type.clone(),
+            _ => return Err(VMError::MalformedInstruction),
+        };
+        let bits = self.project.bit_size(&ty)?;
+
+        let addr = &self.parameters[index].value;
+        let value = self.state.mem.borrow().read(addr, bits)?;
+        Ok(Some((value, ty)))
+    }
+
     /// Execute a single path in the VM to completion.
     pub fn run(&mut self) -> Option<Result<ReturnValue>> {
         self.backtrack_and_resume_execution()
     }
 
+    /// Returns the number of paths that have been saved but not yet explored.
+    pub fn pending_paths(&self) -> usize {
+        self.backtracking_paths.len()
+    }
+
+    /// Returns an iterator over only the paths that end in an error.
+    ///
+    /// Paths that return successfully are skipped. The state of the VM at the time of each error
+    /// can be inspected through [ErrorPaths::vm].
+    pub fn error_paths(&mut self) -> ErrorPaths<'_, 'a> {
+        ErrorPaths { vm: self }
+    }
+
     /// Resume execution from a stored path.
     ///
     /// When we restore the state from a stored path, the VM's call stack is empty. So it cannot
@@ -140,7 +878,33 @@ impl<'a> VM<'a> {
         loop {
             // When executing a basic block we can either get a value from e.g. `ret` but we can
             // also want to resume execution for e.g. `br`.
-            let result = self.execute_function()?;
+            let result = match self.execute_function() {
+                Ok(result) => result,
+                Err(VMError::Panic(reason)) => {
+                    // Panics unwind to the innermost `invoke` on the stored callstack, if there
+                    // is none the panic ends the path.
+                    let invoke = self
+                        .state
+                        .callstack
+                        .iter()
+                        .rposition(|callsite| matches!(callsite.instruction, Call::Invoke(_)));
+
+                    match invoke {
+                        Some(depth) => {
+                            let invoke = match self.state.callstack[depth].instruction {
+                                Call::Invoke(invoke) => invoke,
+                                Call::Call(_) => unreachable!(),
+                            };
+                            self.unwind_to(depth);
+                            self.state.exception = Some(reason);
+                            self.branch(&invoke.exception_label)?;
+                            continue;
+                        }
+                        None => return Err(VMError::Panic(reason)),
+                    }
+                }
+                Err(error) => return Err(error),
+            };
 
             let mut callsite = if let Some(callstack) = self.state.callstack.pop() {
                 callstack
@@ -227,12 +991,14 @@ impl<'a> VM<'a> {
             .skip(offset_into_block)
         {
             self.state.current_loc.set_location(pc);
+            self.consume_budget()?;
             self.process_instruction(inst)?;
         }
 
         // Handle terminator.
         let terminator = &self.state.current_loc.block.term;
         self.state.current_loc.set_terminated(terminator);
+        self.consume_budget()?;
         self.process_terminator(terminator)
     }
 
@@ -261,27 +1027,124 @@ impl<'a> VM<'a> {
         Ok(())
     }
 
+    /// Save a backtracking path that resumes at the current instruction.
+    ///
+    /// When resumed the instruction is executed again, with `constraint` added.
+    pub fn save_backtracking_path_at_current(&mut self, constraint: Option<BV>) -> Result<()> {
+        trace!(
+            "Save backtracking path at current instruction: constraint={:?}",
+            constraint
+        );
+
+        self.solver.push();
+
+        let location = self.state.current_loc.clone();
+        let path = Path::new_with_constraint(self.state.clone(), location, constraint);
+        self.backtracking_paths.push(path);
+
+        Ok(())
+    }
+
+    /// Check if `error_condition` can be true, and if so terminate that path with `error`.
+    ///
+    /// If the condition can be both `true` and `false` a path is saved that resumes at the current
+    /// instruction with the condition set, and the current path continues with it unset.
+    pub fn fork_on_error(&mut self, error_condition: &BV, error: VMError) -> Result<()> {
+        let error_possible = self.solver.is_sat_with_constraint(error_condition)?;
+        let ok_possible = self.solver.is_sat_with_constraint(&error_condition.not())?;
+        self.check_wall_time()?;
+
+        match (error_possible, ok_possible) {
+            (true, true) => {
+                self.save_backtracking_path_at_current(Some(error_condition.clone()))?;
+                self.solver.assert(&error_condition.not());
+                Ok(())
+            }
+            (false, true) => Ok(()),
+            (true, false) => Err(error),
+            (false, false) => Err(VMError::Unsat),
+        }
+    }
+
+    /// Fork the current path on `condition`, and return the value it has on the current path.
+    ///
+    /// If the condition can be both `true` and `false` a path is saved that resumes at the current
+    /// instruction with the condition unset, and the current path continues with it set.
+    pub(crate) fn fork_on_condition(&mut self, condition: &BV) -> Result<bool> {
+        let true_possible = self.solver.is_sat_with_constraint(condition)?;
+        let false_possible = self.solver.is_sat_with_constraint(&condition.not())?;
+        self.check_wall_time()?;
+
+        match (true_possible, false_possible) {
+            (true, true) => {
+                self.save_backtracking_path_at_current(Some(condition.not()))?;
+                self.solver.assert(condition);
+                Ok(true)
+            }
+            (true, false) => Ok(true),
+            (false, true) => Ok(false),
+            (false, false) => Err(VMError::Unsat),
+        }
+    }
+
     /// Backtrack and resume execution with that state.
     fn backtrack_and_resume_execution(&mut self) -> Option<Result<ReturnValue>> {
-        if let Some(path) = self.backtracking_paths.pop() {
+        while let Some(path) = self.backtracking_paths.pop() {
             trace!("Backtrack, {} paths remain", self.backtracking_paths.len());
 
             // Replace the current state.
             self.state = path.state;
 
             // Return to the the solver context when the path was created.
-            self.solver.pop();
+            self.solver.restore(&path.solver);
 
             // Add the constraint.
             if let Some(constraint) = path.constraint {
                 self.solver.assert(&constraint);
             }
 
-            // Resume execution.
-            Some(self.resume_execution())
-        } else {
-            None
+            // Prune the path directly if its constraints cannot be satisfied.
+            if !self.lazy_constraints {
+                match self.solver.is_sat() {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        debug!("Pruning unsatisfiable path");
+                        continue;
+                    }
+                    Err(err) => return Some(Err(err.into())),
+                }
+            }
+
+            // Resume execution, the budget only counts the time the path itself executes.
+            self.reset_budget_usage();
+            let result = self.resume_execution();
+
+            // With lazy constraints the path is first checked when it has ended. A path stopped by
+            // the deadline has not ended, it was saved to be resumed later.
+            if self.lazy_constraints && !matches!(result, Err(VMError::Timeout)) {
+                match self.solver.is_sat() {
+                    Ok(true) => {}
+                    Ok(false) => {
+                        debug!("Dropping unsatisfiable path");
+                        continue;
+                    }
+                    Err(err) => return Some(Err(err.into())),
+                }
+            }
+
+            let failed = !matches!(&result, Ok(_) | Err(VMError::Unsat | VMError::Timeout));
+            if self.fail_fast && failed {
+                debug!(
+                    "Fail fast, discarding {} paths",
+                    self.backtracking_paths.len()
+                );
+                self.backtracking_paths.clear();
+            }
+
+            return Some(result);
         }
+
+        None
     }
 
     /// Helper to call a function.
@@ -296,17 +1159,19 @@ impl<'a> VM<'a> {
         function: &'a Function,
         arguments: Vec<BV>,
     ) -> Result<ReturnValue> {
-        if arguments.len() != function.parameters.len() {
-            if function.is_var_arg {
-                panic!("variadic functions are not supported");
-            } else {
-                panic!("invalid fn call");
-            }
-        }
+        let num_parameters = function.parameters.len();
+        let varargs = if arguments.len() == num_parameters {
+            None
+        } else if function.is_var_arg && arguments.len() > num_parameters {
+            Some(self.spill_varargs(&arguments[num_parameters..])?)
+        } else {
+            panic!("invalid fn call");
+        };
 
         // Create new location at the start of function to call, and store our current
         // position in the callstack so we can return here later.
         let mut new_location = Location::new(module, function);
+        new_location.varargs = varargs;
         std::mem::swap(&mut new_location, &mut self.state.current_loc);
 
         let callsite = match call {
@@ -321,6 +1186,11 @@ impl<'a> VM<'a> {
 
         // Map arguments to parameters.
         for (param, arg) in function.parameters.iter().zip(arguments) {
+            let arg = if is_byval(param) {
+                self.copy_byval(param, &arg)?
+            } else {
+                arg
+            };
             self.state.vars.insert(param.name.clone(), arg)?;
         }
 
@@ -336,6 +1206,48 @@ impl<'a> VM<'a> {
         Ok(return_value)
     }
 
+    /// Copy the value a `byval` argument points to into a new stack allocation of the callee.
+    ///
+    /// Aggregates passed `byval` are passed as a pointer, but the callee gets its own copy of the
+    /// value. This makes writes through the parameter invisible to the caller.
+    fn copy_byval(&mut self, param: &Parameter, arg: &BV) -> Result<BV> {
+        let ty = match param.ty.as_ref() {
+            Type::PointerType { pointee_type, .. } => pointee_type,
+            _ => return Err(VMError::MalformedInstruction),
+        };
+        let bits = self.project.bit_size(ty)? as u64;
+        let align = param
+            .attributes
+            .iter()
+            .find_map(|attribute| match attribute {
+                ParameterAttribute::Alignment(align) => Some(*align),
+                _ => None,
+            })
+            .unwrap_or(self.project.default_alignment as u64);
+
+        let allocation_size = bits.max(self.project.ptr_size as u64);
+        let addr = self.state.stack_alloc(allocation_size, align)?;
+        if bits > 0 {
+            let mut mem = self.state.mem.borrow_mut();
+            let value = mem.read(arg, bits as u32)?;
+            mem.write(&addr, value)?;
+        }
+
+        Ok(addr)
+    }
+
+    /// Pop the callstack until it has `depth` callsites left.
+    ///
+    /// Used when a panic unwinds the stack. The frames of the popped functions are left, see
+    /// [State::leave_frame], and the location is restored to the last popped callsite.
+    pub fn unwind_to(&mut self, depth: usize) {
+        while self.state.callstack.len() > depth {
+            let callsite = self.state.callstack.pop().unwrap();
+            self.state.leave_frame();
+            self.state.current_loc = callsite.location;
+        }
+    }
+
     /// Helper to update the location to another basic block inside the same function.
     pub fn branch(&mut self, target: &Name) -> Result<TerminatorResult> {
         self.state.current_loc.set_basic_block(target);
@@ -358,7 +1270,10 @@ impl<'a> VM<'a> {
         function: &Either<InlineAssembly, Operand>,
     ) -> Result<String> {
         match function {
-            Either::Left(_) => todo!(),
+            Either::Left(_) => {
+                let caller = self.state.current_loc.func.name.clone();
+                Err(VMError::InlineAsmUnsupported(caller))
+            }
             Either::Right(operand) => match operand {
                 Operand::ConstantOperand(constant) => match constant.as_ref() {
                     Constant::GlobalReference {
@@ -0,0 +1,196 @@
+//! Native evaluation of integer operations on constant values.
+//!
+//! When both operands of an operation are constant the result is computed in Rust, and a new
+//! constant is created from it. This avoids building expressions in the solver for programs that
+//! are mostly concrete. Operations with any symbolic operand, vectors, or integers wider than 64
+//! bits fall back to the solver. See [VM::with_concrete_fast_path].
+use llvm_ir::{IntPredicate, Operand, Type};
+
+use super::{Result, VM};
+use crate::{
+    common::{binop, icmp},
+    BV,
+};
+
+/// Integer binary operations that can be evaluated natively.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub(super) enum IntOp {
+    Add,
+    Sub,
+    Mul,
+    UDiv,
+    SDiv,
+    URem,
+    SRem,
+    Shl,
+    LShr,
+    AShr,
+    And,
+    Or,
+    Xor,
+}
+
+impl IntOp {
+    /// Returns the solver operation.
+    fn symbolic(self) -> fn(&BV, &BV) -> BV {
+        match self {
+            IntOp::Add => BV::add,
+            IntOp::Sub => BV::sub,
+            IntOp::Mul => BV::mul,
+            IntOp::UDiv => BV::udiv,
+            IntOp::SDiv => BV::sdiv,
+            IntOp::URem => BV::urem,
+            IntOp::SRem => BV::srem,
+            IntOp::Shl => BV::sll,
+            IntOp::LShr => BV::srl,
+            IntOp::AShr => BV::sra,
+            IntOp::And => BV::and,
+            IntOp::Or => BV::or,
+            IntOp::Xor => BV::xor,
+        }
+    }
+
+    /// Evaluate the operation on two `bits` wide integers.
+    ///
+    /// Returns `None` for division by zero and shifts by at least `bits`, these are left to the
+    /// solver so the result is the same as without the fast path.
+    fn concrete(self, lhs: u64, rhs: u64, bits: u32) -> Option<u64> {
+        let signed_lhs = sign_extend(lhs, bits);
+        let signed_rhs = sign_extend(rhs, bits);
+
+        let result = match self {
+            IntOp::Add => lhs.wrapping_add(rhs),
+            IntOp::Sub => lhs.wrapping_sub(rhs),
+            IntOp::Mul => lhs.wrapping_mul(rhs),
+            IntOp::UDiv => lhs.checked_div(rhs)?,
+            IntOp::SDiv if rhs == 0 => return None,
+            IntOp::SDiv => signed_lhs.wrapping_div(signed_rhs) as u64,
+            IntOp::URem => lhs.checked_rem(rhs)?,
+            IntOp::SRem if rhs == 0 => return None,
+            IntOp::SRem => signed_lhs.wrapping_rem(signed_rhs) as u64,
+            IntOp::Shl | IntOp::LShr | IntOp::AShr if rhs >= bits as u64 => return None,
+            IntOp::Shl => lhs << rhs,
+            IntOp::LShr => lhs >> rhs,
+            IntOp::AShr => (signed_lhs >> rhs) as u64,
+            IntOp::And => lhs & rhs,
+            IntOp::Or => lhs | rhs,
+            IntOp::Xor => lhs ^ rhs,
+        };
+
+        Some(result & mask(bits))
+    }
+}
+
+/// Evaluate the comparison `predicate` on two `bits` wide integers.
+fn compare(predicate: IntPredicate, lhs: u64, rhs: u64, bits: u32) -> bool {
+    let signed_lhs = sign_extend(lhs, bits);
+    let signed_rhs = sign_extend(rhs, bits);
+
+    match predicate {
+        IntPredicate::EQ => lhs == rhs,
+        IntPredicate::NE => lhs != rhs,
+        IntPredicate::UGT => lhs > rhs,
+        IntPredicate::UGE => lhs >= rhs,
+        IntPredicate::ULT => lhs < rhs,
+        IntPredicate::ULE => lhs <= rhs,
+        IntPredicate::SGT => signed_lhs > signed_rhs,
+        IntPredicate::SGE => signed_lhs >= signed_rhs,
+        IntPredicate::SLT => signed_lhs < signed_rhs,
+        IntPredicate::SLE => signed_lhs <= signed_rhs,
+    }
+}
+
+/// Mask with the lower `bits` bits set.
+fn mask(bits: u32) -> u64 {
+    if bits >= 64 {
+        u64::MAX
+    } else {
+        (1 << bits) - 1
+    }
+}
+
+/// Interpret the lower `bits` bits of `value` as a signed integer.
+fn sign_extend(value: u64, bits: u32) -> i64 {
+    let unused = 64 - bits;
+    ((value << unused) as i64) >> unused
+}
+
+impl<'a> VM<'a> {
+    /// Perform the integer operation `op` on two operands.
+    ///
+    /// Works as [binop], but the result is computed natively if both operands are constant.
+    pub(super) fn int_binop(&self, lhs: &Operand, rhs: &Operand, op: IntOp) -> Result<BV> {
+        if self.concrete_fast_path {
+            let result = self
+                .native_operands(lhs, rhs)?
+                .and_then(|(l, r, bits, taint)| {
+                    op.concrete(l, r, bits)
+                        .map(|value| self.native_result(value, bits, taint))
+                });
+            if let Some(result) = result {
+                return Ok(result);
+            }
+        }
+
+        binop(&self.state, lhs, rhs, op.symbolic())
+    }
+
+    /// Compare two operands with `predicate`.
+    ///
+    /// Works as [icmp], but the result is computed natively if both operands are constant.
+    pub(super) fn int_icmp(
+        &self,
+        lhs: &Operand,
+        rhs: &Operand,
+        predicate: IntPredicate,
+    ) -> Result<BV> {
+        if self.concrete_fast_path {
+            if let Some((l, r, bits, taint)) = self.native_operands(lhs, rhs)? {
+                let value = compare(predicate, l, r, bits) as u64;
+                return Ok(self.native_result(value, 1, taint));
+            }
+        }
+
+        icmp(&self.state, lhs, rhs, predicate)
+    }
+
+    /// Returns the values of the operands if both are constant integers or pointers of the same
+    /// width, at most 64 bits. Also returns the width, and if either of the operands is tainted.
+    fn native_operands(
+        &self,
+        lhs: &Operand,
+        rhs: &Operand,
+    ) -> Result<Option<(u64, u64, u32, bool)>> {
+        let is_scalar = |op: &Operand| {
+            matches!(
+                self.state.type_of(op).as_ref(),
+                Type::IntegerType { .. } | Type::PointerType { .. }
+            )
+        };
+        if !is_scalar(lhs) || !is_scalar(rhs) {
+            return Ok(None);
+        }
+
+        let lhs = self.state.get_var(lhs)?;
+        let rhs = self.state.get_var(rhs)?;
+        if lhs.len() != rhs.len() || lhs.len() > 64 {
+            return Ok(None);
+        }
+
+        let taint = lhs.is_tainted() || rhs.is_tainted();
+        Ok(lhs
+            .as_concrete()
+            .zip(rhs.as_concrete())
+            .map(|(l, r)| (l, r, lhs.len(), taint)))
+    }
+
+    /// Create a constant for a natively computed result, keeping the taint of the operands.
+    fn native_result(&self, value: u64, bits: u32, taint: bool) -> BV {
+        let result = self.solver.bv_from_u64(value, bits);
+        if taint {
+            result.taint()
+        } else {
+            result
+        }
+    }
+}
@@ -0,0 +1,107 @@
+//! Replay of a single path with concrete inputs.
+//!
+//! A [Model] holds concrete values for all the inputs of a path, i.e. the parameters to the entry
+//! function and all values marked as symbolic. Replaying the model on a new [VM] executes with
+//! those values, so exactly one path is followed. This can be used to confirm that a generated test
+//! case reproduces the behavior of the path it was generated from.
+use std::collections::VecDeque;
+
+use super::{Result, ReturnValue, VMError, VM};
+use crate::{common::SolutionVariable, solver::SolutionGenerator, BV};
+
+/// Concrete values for the inputs to a path.
+///
+/// The values are binary strings with the most significant bit first.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Model {
+    /// Values for the parameters of the entry function.
+    pub parameters: Vec<String>,
+
+    /// Values for the symbols, in the order they were created.
+    pub symbols: Vec<String>,
+}
+
+impl<'a> VM<'a> {
+    /// Generate a [Model] for the path that was last executed.
+    pub fn model(&self) -> Result<Model> {
+        let mut cache = SolutionGenerator::new(self.solver.clone())?;
+
+        let mut solve = |variables: &[SolutionVariable]| -> Result<Vec<String>> {
+            variables
+                .iter()
+                .map(|v| Ok(cache.get_solution(&v.value)?.as_01x_str().to_owned()))
+                .collect()
+        };
+
+        Ok(Model {
+            parameters: solve(&self.parameters)?,
+            symbols: solve(&self.state.symbols)?,
+        })
+    }
+
+    /// Execute the entry function with all inputs fixed to the values in `model`.
+    ///
+    /// This must be called on a newly created VM, and returns the result of the single path that
+    /// the model leads to.
+    pub fn replay(&mut self, model: &Model) -> Result<ReturnValue> {
+        if model.parameters.len() != self.parameters.len() {
+            return Err(VMError::InternalError(
+                "Model does not match the entry function parameters",
+            ));
+        }
+        if self.backtracking_paths.len() != 1 {
+            return Err(VMError::InternalError(
+                "Replay can only be done before execution",
+            ));
+        }
+
+        // Constraints must be added to the initial path, otherwise they are lost when it resumes.
+        let path = self.backtracking_paths.pop().unwrap();
+        self.solver.pop();
+        self.state = path.state;
+
+        // Symbols created before execution (e.g. from `argv`) are constrained directly, the rest
+        // are constrained as they are created.
+        let existing = self.state.symbols.len().min(model.symbols.len());
+        let variables = self
+            .parameters
+            .iter()
+            .chain(&self.state.symbols[..existing]);
+        let values = model.parameters.iter().chain(&model.symbols[..existing]);
+        let constraint = variables
+            .zip(values)
+            .map(|(variable, value)| self.fixed_to(&variable.value, value))
+            .reduce(|acc, c| acc.and(&c));
+        self.replay_symbols = model.symbols[existing..].iter().cloned().collect();
+
+        let bb_label = self.state.current_loc.block.name.clone();
+        self.save_backtracking_path(&bb_label, constraint)?;
+
+        let result = self
+            .run()
+            .unwrap_or(Err(VMError::InternalError("No path to replay")));
+
+        // With all inputs fixed there should not be any other paths.
+        self.backtracking_paths.clear();
+        self.replay_symbols = VecDeque::new();
+
+        result
+    }
+
+    /// Add a new symbol to the current state.
+    ///
+    /// When replaying a [Model] the symbol is fixed to the next value in the model.
+    pub fn add_symbol(&mut self, symbol: SolutionVariable) {
+        if let Some(value) = self.replay_symbols.pop_front() {
+            let constraint = self.fixed_to(&symbol.value, &value);
+            self.solver.assert(&constraint);
+        }
+        self.state.symbols.push(symbol);
+    }
+
+    /// Helper to create a constraint that `bv` is equal to the binary string `value`.
+    fn fixed_to(&self, bv: &BV, value: &str) -> BV {
+        let value = self.solver.from_binary_string(value);
+        bv.eq(&value)
+    }
+}
@@ -0,0 +1,87 @@
+//! Deduplication of error paths into unique reports.
+//!
+//! The same bug is often reachable through many paths, e.g. an out of bounds access in a loop
+//! fails on each iteration where the index can be too large. [VM::unique_errors] groups the error
+//! paths by the kind of error and the instruction where it occurred, and keeps a single
+//! representative for each group.
+use llvm_ir::Name;
+use std::collections::HashMap;
+
+use super::{Model, Result, VMError, VM};
+
+/// The instruction where an error occurred.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct ErrorLocation {
+    /// Name of the function.
+    pub function: String,
+
+    /// Name of the basic block in the function.
+    pub block: Name,
+
+    /// Index of the instruction in the basic block, the terminator has the index after the last
+    /// instruction.
+    pub instruction: usize,
+}
+
+/// A unique error, see [VM::unique_errors].
+#[derive(Debug)]
+pub struct ErrorReport {
+    /// Error of the first path that reached this location.
+    pub error: VMError,
+
+    /// Where the error occurred.
+    pub location: ErrorLocation,
+
+    /// Inputs that reproduce the error, see [VM::replay].
+    pub model: Model,
+
+    /// Number of error paths that were grouped into this report.
+    pub count: usize,
+}
+
+impl<'a> VM<'a> {
+    /// Explore all remaining paths and return one report for each unique error.
+    ///
+    /// Errors are unique by their [VMError::kind] and the instruction they occurred at. The
+    /// reports are in the order the errors were first found.
+    pub fn unique_errors(&mut self) -> Result<Vec<ErrorReport>> {
+        let mut reports: Vec<ErrorReport> = Vec::new();
+        let mut groups = HashMap::new();
+
+        while let Some(result) = self.run() {
+            let error = match result {
+                Ok(_) => continue,
+                Err(error) => error,
+            };
+
+            let location = self.instruction_location();
+            match groups.get(&(error.kind(), location.clone())) {
+                Some(&index) => reports[index].count += 1,
+                None => {
+                    groups.insert((error.kind(), location.clone()), reports.len());
+                    reports.push(ErrorReport {
+                        model: self.model()?,
+                        error,
+                        location,
+                        count: 1,
+                    });
+                }
+            }
+        }
+
+        Ok(reports)
+    }
+
+    /// Returns the location of the instruction that is currently executed.
+    ///
+    /// After a path has ended this is the instruction that terminated it, i.e. the `ret` or the
+    /// instruction that returned the error.
+    pub fn instruction_location(&self) -> ErrorLocation {
+        let location = &self.state.current_loc;
+        ErrorLocation {
+            function: location.func.name.clone(),
+            block: location.block.name.clone(),
+            instruction: location.get_instruction_offset(),
+        }
+    }
+}
@@ -0,0 +1,160 @@
+//! Scheduling of the paths that are left to explore.
+//!
+//! Each time a path forks, the new paths are handed to a [Scheduler]. When the current path ends
+//! the scheduler picks which path to resume next. The default is [Dfs], which resumes the most
+//! recently saved path, but any search heuristic can be used by implementing [Scheduler] and
+//! passing it to [VM::with_scheduler](super::VM::with_scheduler).
+use rand::{rngs::StdRng, Rng, SeedableRng};
+use std::collections::VecDeque;
+
+use super::Path;
+
+/// Picks the order that paths are explored in.
+///
+/// The solver state of each path is saved in the path, so paths can be resumed in any order.
+pub trait Scheduler<'a>: SchedulerClone<'a> {
+    /// Add a path to explore.
+    fn push(&mut self, path: Path<'a>);
+
+    /// Remove and return the next path to explore, or `None` if there are no paths left.
+    fn pop(&mut self) -> Option<Path<'a>>;
+
+    /// Returns the number of paths left to explore.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if there are no paths left to explore.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Remove all paths.
+    fn clear(&mut self) {
+        while self.pop().is_some() {}
+    }
+
+    /// Reseed the scheduler, this is called by [VM::with_random_seed](super::VM::with_random_seed).
+    ///
+    /// Schedulers that make random choices should use `seed` so runs are reproducible. The default
+    /// does nothing.
+    fn set_seed(&mut self, _seed: u64) {}
+}
+
+/// Helper to clone a boxed [Scheduler], this is implemented for all schedulers that are [Clone].
+pub trait SchedulerClone<'a> {
+    fn box_clone(&self) -> Box<dyn Scheduler<'a> + 'a>;
+}
+
+impl<'a, T> SchedulerClone<'a> for T
+where
+    T: Scheduler<'a> + Clone + 'a,
+{
+    fn box_clone(&self) -> Box<dyn Scheduler<'a> + 'a> {
+        Box::new(self.clone())
+    }
+}
+
+/// Depth-first search, the most recently saved path is explored first.
+#[derive(Debug, Clone, Default)]
+pub struct Dfs<'a> {
+    paths: Vec<Path<'a>>,
+}
+
+impl<'a> Dfs<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<'a> Scheduler<'a> for Dfs<'a> {
+    fn push(&mut self, path: Path<'a>) {
+        self.paths.push(path);
+    }
+
+    fn pop(&mut self) -> Option<Path<'a>> {
+        self.paths.pop()
+    }
+
+    fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    fn clear(&mut self) {
+        self.paths.clear();
+    }
+}
+
+/// Breadth-first search, the least recently saved path is explored first.
+#[derive(Debug, Clone, Default)]
+pub struct Bfs<'a> {
+    paths: VecDeque<Path<'a>>,
+}
+
+impl<'a> Bfs<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+}
+
+impl<'a> Scheduler<'a> for Bfs<'a> {
+    fn push(&mut self, path: Path<'a>) {
+        self.paths.push_back(path);
+    }
+
+    fn pop(&mut self) -> Option<Path<'a>> {
+        self.paths.pop_front()
+    }
+
+    fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    fn clear(&mut self) {
+        self.paths.clear();
+    }
+}
+
+/// Explores the paths in a random order.
+///
+/// The order is determined by the seed, so the same seed explores the paths in the same order.
+#[derive(Debug, Clone)]
+pub struct RandomScheduler<'a> {
+    paths: Vec<Path<'a>>,
+
+    rng: StdRng,
+}
+
+impl<'a> RandomScheduler<'a> {
+    pub fn new(seed: u64) -> Self {
+        Self {
+            paths: Vec::new(),
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+impl<'a> Scheduler<'a> for RandomScheduler<'a> {
+    fn push(&mut self, path: Path<'a>) {
+        self.paths.push(path);
+    }
+
+    fn pop(&mut self) -> Option<Path<'a>> {
+        if self.paths.is_empty() {
+            return None;
+        }
+
+        let index = self.rng.gen_range(0..self.paths.len());
+        Some(self.paths.swap_remove(index))
+    }
+
+    fn len(&self) -> usize {
+        self.paths.len()
+    }
+
+    fn clear(&mut self) {
+        self.paths.clear();
+    }
+
+    fn set_seed(&mut self, seed: u64) {
+        self.rng = StdRng::seed_from_u64(seed);
+    }
+}
@@ -1,7 +1,7 @@
 use anyhow::{anyhow, Result};
 use llvm_ir::{BasicBlock, DebugLoc, Function, HasDebugLoc, Name};
 
-use crate::project::ModuleHandle;
+use crate::{project::ModuleHandle, BV};
 
 /// Determines the index to the next instruction to execute in a Basic Block.
 #[derive(Debug, Clone)]
@@ -41,6 +41,9 @@ pub struct Location<'a> {
 
     /// todo
     pub source_loc: Option<&'a DebugLoc>,
+
+    /// Address of the variadic arguments passed to the function, if any.
+    pub varargs: Option<BV>,
 }
 
 // The llvm-ir types do not implement Debug, so create a basic on that only
@@ -54,6 +57,7 @@ impl<'a> std::fmt::Debug for Location<'a> {
             .field("previous_block", &self.previous_block.map(|b| &b.name))
             .field("instr", &self.instr)
             .field("source_loc", &self.source_loc)
+            .field("varargs", &self.varargs)
             .finish()
     }
 }
@@ -72,6 +76,7 @@ impl<'a> Location<'a> {
             previous_block: None,
             instr: InstructionIndex::NotStarted,
             source_loc: None,
+            varargs: None,
         }
     }
 
@@ -90,6 +95,7 @@ impl<'a> Location<'a> {
             block,
             instr: InstructionIndex::NotStarted,
             source_loc: None,
+            varargs: location.varargs,
         })
     }
 
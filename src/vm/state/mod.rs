@@ -8,11 +8,12 @@ use llvm_ir::{
 };
 use log::warn;
 
-use super::{GlobalReference, GlobalReferences, Result};
+use super::{GlobalReference, GlobalReferences, PanicReason, Result};
 use crate::{
     common::{const_to_symbol, operand_to_symbol, Op, SolutionVariable},
-    memory::Memory,
+    memory::{AllocationInfo, Memory},
     project::{ModuleHandle, Project},
+    solver::SolverSnapshot,
     {Solver, BV},
 };
 
@@ -64,6 +65,9 @@ pub struct Path<'a> {
 
     /// Constraint to add before starting execution on this path.
     pub constraint: Option<BV>,
+
+    /// Solver state when the path was created, restored when the path is resumed.
+    pub(crate) solver: SolverSnapshot,
 }
 
 impl<'a> Path<'a> {
@@ -73,9 +77,11 @@ impl<'a> Path<'a> {
     /// This should be used for the initial path in the program. When no constraints have been added
     /// to the initial function. And that it should start executing at the state's current location.
     pub fn new(state: State<'a>) -> Self {
+        let solver = state.solver.snapshot();
         Self {
             state,
             constraint: None,
+            solver,
         }
     }
 
@@ -92,8 +98,13 @@ impl<'a> Path<'a> {
     ) -> Self {
         let mut state = state;
         state.current_loc = location;
+        let solver = state.solver.snapshot();
 
-        Self { state, constraint }
+        Self {
+            state,
+            constraint,
+            solver,
+        }
     }
 }
 
@@ -125,6 +136,15 @@ pub struct State<'a> {
     ///
     /// This holds the mapping between the name of the global reference and its address.
     pub global_references: GlobalReferences<'a>,
+
+    /// The panic that is unwinding the stack, set when jumping to a landing pad.
+    pub exception: Option<PanicReason>,
+
+    /// Stack allocations of functions that have returned, see [State::leave_frame].
+    pub dead_stack: Vec<AllocationInfo>,
+
+    /// Output written by `puts` and `printf` on this path.
+    pub output: String,
 }
 
 impl<'a> State<'a> {
@@ -146,6 +166,9 @@ impl<'a> State<'a> {
             callstack: Vec::new(),
             symbols: Vec::new(),
             global_references,
+            exception: None,
+            dead_stack: Vec::new(),
+            output: String::new(),
         };
 
         // state.initialize_global_references().unwrap();
@@ -176,16 +199,81 @@ impl<'a> State<'a> {
 
     /// Allocate an uninitialized value `name` on the stack with size `allocation_size`.
     pub fn stack_alloc(&mut self, allocation_size: u64, align: u64) -> Result<BV> {
-        let align = if align == 0 {
+        let align = self.stack_alignment(align);
+        let addr = self.mem.borrow_mut().allocate(allocation_size, align)?;
+        self.vars.add_stack_allocation(addr);
+        let bv = self.solver.bv_from_u64(addr, self.project.ptr_size as u32);
+        Ok(bv)
+    }
+
+    /// Allocate `max_size` bits on the stack, where only the first `size` bytes are in bounds.
+    ///
+    /// See [Memory::allocate_symbolic].
+    pub fn stack_alloc_symbolic(&mut self, max_size: u64, align: u64, size: &BV) -> Result<BV> {
+        let align = self.stack_alignment(align);
+        let addr = self
+            .mem
+            .borrow_mut()
+            .allocate_symbolic(max_size, align, size)?;
+        self.vars.add_stack_allocation(addr);
+        let bv = self.solver.bv_from_u64(addr, self.project.ptr_size as u32);
+        Ok(bv)
+    }
+
+    /// Leave the variable scope of the current function, and mark its stack allocations as dead.
+    ///
+    /// The dead allocations are kept in [State::dead_stack], so accesses to them after the
+    /// function has returned can be detected.
+    pub fn leave_frame(&mut self) {
+        let addrs = self.vars.leave_scope();
+        self.free_stack(addrs);
+    }
+
+    /// Returns a marker for the current top of the stack, see [State::stack_restore].
+    ///
+    /// The marker is the number of stack allocations made in the current function.
+    pub fn stack_save(&self) -> u64 {
+        self.vars.num_stack_allocations() as u64
+    }
+
+    /// Free the stack allocations the current function made after `marker` was saved.
+    ///
+    /// Like the allocations of a function that has returned, these are kept in
+    /// [State::dead_stack].
+    pub fn stack_restore(&mut self, marker: u64) {
+        let addrs = self.vars.truncate_stack_allocations(marker as usize);
+        self.free_stack(addrs);
+    }
+
+    /// Mark the stack allocations at `addrs` as dead.
+    fn free_stack(&mut self, addrs: Vec<u64>) {
+        let mut mem = self.mem.borrow_mut();
+        for addr in addrs {
+            if let Some(allocation) = mem.allocation_containing(addr) {
+                mem.free(addr);
+                self.dead_stack.push(AllocationInfo {
+                    live: false,
+                    ..allocation
+                });
+            }
+        }
+    }
+
+    fn stack_alignment(&self, align: u64) -> u64 {
+        if align == 0 {
             warn!("Alignment of 0");
             self.project.default_alignment as u64
         } else {
             align
-        };
+        }
+    }
 
-        let addr = self.mem.borrow_mut().allocate(allocation_size, align)?;
-        let bv = self.solver.bv_from_u64(addr, self.project.ptr_size as u32);
-        Ok(bv)
+    /// Returns all allocations made in memory, in the order they were made.
+    ///
+    /// This includes stack and heap allocations, as well as the space for globals and functions.
+    pub fn allocations(&self) -> impl Iterator<Item = AllocationInfo> {
+        let allocations: Vec<_> = self.mem.borrow().allocations().collect();
+        allocations.into_iter()
     }
 
     // -------------------------------------------------------------------------
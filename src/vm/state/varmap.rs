@@ -8,12 +8,16 @@ use crate::{vm::VMError, BV};
 struct Scope {
     /// Variables on the stack.
     vars: HashMap<Name, BV>,
+
+    /// Addresses of the stack allocations made in the function.
+    stack_allocations: Vec<u64>,
 }
 
 impl Scope {
     fn new() -> Self {
         Self {
             vars: HashMap::new(),
+            stack_allocations: Vec::new(),
         }
     }
 }
@@ -36,8 +40,40 @@ impl VarMap {
         self.scopes.push(Scope::new());
     }
 
-    pub fn leave_scope(&mut self) {
-        self.scopes.pop();
+    /// Leave the current scope, returning the addresses of the stack allocations made in it.
+    pub fn leave_scope(&mut self) -> Vec<u64> {
+        self.scopes
+            .pop()
+            .map(|scope| scope.stack_allocations)
+            .unwrap_or_default()
+    }
+
+    /// Record a stack allocation at `addr` in the current scope.
+    ///
+    /// Allocations made before any scope has been entered are not recorded.
+    pub fn add_stack_allocation(&mut self, addr: u64) {
+        if let Some(current) = self.scopes.last_mut() {
+            current.stack_allocations.push(addr);
+        }
+    }
+
+    /// Returns the number of stack allocations made in the current scope.
+    pub fn num_stack_allocations(&self) -> usize {
+        self.scopes
+            .last()
+            .map(|scope| scope.stack_allocations.len())
+            .unwrap_or_default()
+    }
+
+    /// Remove the stack allocations made in the current scope after the first `len`, returning
+    /// their addresses.
+    pub fn truncate_stack_allocations(&mut self, len: usize) -> Vec<u64> {
+        match self.scopes.last_mut() {
+            Some(current) if len < current.stack_allocations.len() => {
+                current.stack_allocations.split_off(len)
+            }
+            _ => Vec::new(),
+        }
     }
 
     pub fn insert(&mut self, name: Name, val: BV) -> Result<(), VMError> {
@@ -49,6 +85,33 @@ impl VarMap {
         Ok(())
     }
 
+    /// Merge the variables in the current scope of `other` into `self`.
+    ///
+    /// Variables that differ are set to the value in `self` where `cond` is true, and to the value
+    /// in `other` where it is false. Variables that only exist in `other` are copied as is.
+    pub fn merge(&mut self, other: &VarMap, cond: &BV) -> Result<(), VMError> {
+        let (current, other) = match (self.scopes.last_mut(), other.scopes.last()) {
+            (Some(current), Some(other)) => (current, other),
+            _ => {
+                return Err(VMError::InternalError(
+                    "Tried to merge, but no scope has been added",
+                ))
+            }
+        };
+
+        for (name, other_value) in other.vars.iter() {
+            match current.vars.get_mut(name) {
+                Some(value) if value != other_value => *value = cond.ite(value, other_value),
+                Some(_) => {}
+                None => {
+                    current.vars.insert(name.clone(), other_value.clone());
+                }
+            }
+        }
+
+        Ok(())
+    }
+
     pub fn get(&self, name: &Name) -> Option<&BV> {
         let current = self.scopes.last().unwrap();
         current.vars.get(name)
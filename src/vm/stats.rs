@@ -0,0 +1,56 @@
+//! Statistics on how paths fork.
+use super::VM;
+use crate::{solver::SolverResult, BV};
+
+/// Counts of the conditional branches that have been executed, see [VM::fork_stats].
+///
+/// A branch where only one successor is feasible does not create any new paths, so a low number of
+/// pruned branches compared to the total indicates that the number of paths grows quickly.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ForkStats {
+    /// Number of conditional branches and switches that have been executed.
+    pub forks: u64,
+
+    /// Number of the forks where at least one of the successors was unsatisfiable.
+    pub pruned: u64,
+
+    /// Number of successors where the solver could not decide if they were satisfiable, e.g.
+    /// because of [Solver::set_query_timeout](crate::Solver::set_query_timeout). These are
+    /// explored as if they were satisfiable.
+    pub unknown: u64,
+}
+
+impl<'a> VM<'a> {
+    /// Returns the fork statistics for all paths that have been executed so far.
+    pub fn fork_stats(&self) -> ForkStats {
+        self.fork_stats
+    }
+
+    /// Returns if the current path can continue with `condition`.
+    ///
+    /// Conditions the solver cannot decide are treated as feasible so no paths are lost, and are
+    /// counted in [ForkStats::unknown]. With [VM::with_lazy_constraints] only conditions that are
+    /// constant false are infeasible.
+    pub(super) fn is_feasible(&mut self, condition: &BV) -> bool {
+        if self.lazy_constraints {
+            return condition.as_concrete() != Some(0);
+        }
+
+        match self.solver.check_with_constraint(condition) {
+            SolverResult::Sat => true,
+            SolverResult::Unsat => false,
+            SolverResult::Unknown => {
+                self.fork_stats.unknown += 1;
+                true
+            }
+        }
+    }
+
+    /// Record a fork where `feasible` out of `successors` successors were satisfiable.
+    pub(super) fn record_fork(&mut self, feasible: usize, successors: usize) {
+        self.fork_stats.forks += 1;
+        if feasible < successors {
+            self.fork_stats.pruned += 1;
+        }
+    }
+}
@@ -0,0 +1,42 @@
+//! Symbolic summaries of functions.
+//!
+//! A summary is the return value of a function expressed in terms of its inputs. This is useful
+//! for compositional analysis, where a function is analyzed once and the result is reused at
+//! each call site.
+use anyhow::anyhow;
+
+use super::{Result, ReturnValue, VMError, VM};
+use crate::{common::SolutionVariable, project::Project, BV};
+
+impl<'a> VM<'a> {
+    /// Run `fn_name` with symbolic inputs, and return the return value together with the inputs.
+    ///
+    /// The returned [BV] is an expression of the symbols in the returned inputs. This requires the
+    /// function to have exactly one path, that returns a value.
+    ///
+    /// # Errors
+    ///
+    /// Returns [VMError::MultiplePaths] if the function has more than one path, and the error of
+    /// the path if it does not return successfully.
+    pub fn symbolic_return_of(
+        fn_name: &str,
+        project: &'a Project,
+    ) -> Result<(BV, Vec<SolutionVariable>)> {
+        let mut vm = VM::new(fn_name, project)?;
+
+        let value = match vm.run() {
+            Some(Ok(ReturnValue::Value(value))) => value,
+            Some(Ok(ReturnValue::Void)) => {
+                return Err(anyhow!("Function {fn_name} does not return a value").into())
+            }
+            Some(Err(error)) => return Err(error),
+            None => return Err(VMError::Unsat),
+        };
+
+        if vm.run().is_some() {
+            return Err(VMError::MultiplePaths);
+        }
+
+        Ok((value, vm.parameters))
+    }
+}
@@ -0,0 +1,139 @@
+//! Support for variadic functions.
+//!
+//! Arguments passed to the variadic part of a function are spilled to a memory area by the caller,
+//! each argument in its own slot aligned to the pointer size. `llvm.va_start` sets up the
+//! `va_list` to point to that area, and `va_arg` reads one argument at a time from it.
+//!
+//! On x86-64 the `va_list` is a struct with offsets into a register save area, as well as a
+//! pointer to the arguments passed on the stack. Clang lowers `va_arg` to reads of these fields
+//! instead of a `va_arg` instruction. The offsets are set so that all registers appear used, which
+//! makes all arguments be read from the spilled area. For other targets the `va_list` is assumed
+//! to be a pointer.
+use llvm_ir::Type;
+
+use super::{Result, VM};
+use crate::{memory::BITS_IN_BYTE, BV};
+
+/// Offset of the general purpose registers in the x86-64 register save area, when all are used.
+const X86_64_GP_OFFSET_END: u64 = 6 * 8;
+
+/// Offset of the floating point registers in the x86-64 register save area, when all are used.
+const X86_64_FP_OFFSET_END: u64 = X86_64_GP_OFFSET_END + 8 * 16;
+
+impl<'a> VM<'a> {
+    /// Write `arguments` to a new stack allocation, and return the address to it.
+    pub(super) fn spill_varargs(&mut self, arguments: &[BV]) -> Result<BV> {
+        let slot_size = self.project.ptr_size as u64;
+        let slots: Vec<_> = arguments
+            .iter()
+            .map(|arg| round_up(arg.len() as u64, slot_size))
+            .collect();
+
+        let size = slots.iter().sum::<u64>().max(slot_size);
+        let area = self
+            .state
+            .stack_alloc(size, slot_size / BITS_IN_BYTE as u64)?;
+
+        let mut offset = 0;
+        for (arg, slot) in arguments.iter().zip(slots) {
+            let addr = area.add(&self.byte_offset(offset));
+            let arg = arg
+                .clone()
+                .resize_unsigned(round_up(arg.len() as u64, 8) as u32);
+            self.state.mem.borrow_mut().write(&addr, arg)?;
+            offset += slot;
+        }
+
+        Ok(area)
+    }
+
+    /// Initialize the `va_list` at `va_list` to point to the variadic arguments of the current
+    /// function.
+    pub(crate) fn va_start(&mut self, va_list: &BV) -> Result<()> {
+        let ptr_size = self.project.ptr_size;
+        let area = match &self.state.current_loc.varargs {
+            Some(area) => area.clone(),
+            None => self.solver.bv_zero(ptr_size),
+        };
+
+        let mut mem = self.state.mem.borrow_mut();
+        if self.is_x86_64() {
+            // struct { i32 gp_offset, i32 fp_offset, i8* overflow_arg_area, i8* reg_save_area }
+            let gp_offset = self.solver.bv_from_u64(X86_64_GP_OFFSET_END, 32);
+            let fp_offset = self.solver.bv_from_u64(X86_64_FP_OFFSET_END, 32);
+            mem.write(va_list, gp_offset)?;
+            mem.write(&va_list.add(&self.byte_offset(32)), fp_offset)?;
+            mem.write(&va_list.add(&self.byte_offset(64)), area)?;
+            mem.write(
+                &va_list.add(&self.byte_offset(128)),
+                self.solver.bv_zero(ptr_size),
+            )?;
+        } else {
+            mem.write(va_list, area)?;
+        }
+
+        Ok(())
+    }
+
+    /// Copy the `va_list` at `src` to `dst`.
+    pub(crate) fn va_copy(&mut self, dst: &BV, src: &BV) -> Result<()> {
+        let mut mem = self.state.mem.borrow_mut();
+        let bits = self.va_list_size();
+        let mut offset = 0;
+        while offset < bits {
+            let value = mem.read(&src.add(&self.byte_offset(offset)), self.project.ptr_size)?;
+            mem.write(&dst.add(&self.byte_offset(offset)), value)?;
+            offset += self.project.ptr_size as u64;
+        }
+
+        Ok(())
+    }
+
+    /// Read the next argument of type `ty` from the `va_list` at `va_list`.
+    pub(super) fn read_vararg(&mut self, va_list: &BV, ty: &Type) -> Result<BV> {
+        let ptr_size = self.project.ptr_size;
+        let bits = self.project.bit_size(ty)?;
+
+        let area_ptr = if self.is_x86_64() {
+            va_list.add(&self.byte_offset(64))
+        } else {
+            va_list.clone()
+        };
+
+        let mut mem = self.state.mem.borrow_mut();
+        let area = mem.read(&area_ptr, ptr_size)?;
+        let value = mem.read(&area, round_up(bits as u64, 8) as u32)?;
+
+        let slot = round_up(bits as u64, ptr_size as u64);
+        mem.write(&area_ptr, area.add(&self.byte_offset(slot)))?;
+
+        Ok(value.resize_unsigned(bits))
+    }
+
+    /// Size of a `va_list` in bits.
+    fn va_list_size(&self) -> u64 {
+        if self.is_x86_64() {
+            64 + 2 * self.project.ptr_size as u64
+        } else {
+            self.project.ptr_size as u64
+        }
+    }
+
+    fn is_x86_64(&self) -> bool {
+        self.project
+            .target_triple()
+            .map(|triple| triple.starts_with("x86_64"))
+            .unwrap_or(false)
+    }
+
+    /// Returns a pointer sized offset of `bits` converted to bytes.
+    fn byte_offset(&self, bits: u64) -> BV {
+        self.solver
+            .bv_from_u64(bits / BITS_IN_BYTE as u64, self.project.ptr_size)
+    }
+}
+
+/// Round `bits` up to a multiple of `multiple`.
+fn round_up(bits: u64, multiple: u64) -> u64 {
+    (bits + multiple - 1) / multiple * multiple
+}
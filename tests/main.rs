@@ -1,11 +1,13 @@
+use llvm_ir::{Constant, Name, Terminator, Type};
 use x0001e::{
-    memory::{MemoryError, CHECK_OUT_OF_BOUNDS},
-    vm::VMError,
+    memory::{MemoryError, MAX_SYMBOLIC_ALLOCATION, NULL_PAGE_SIZE},
+    vm::{Argv, Budget, PanicReason, Path, RandomScheduler, Scheduler, VMError},
+    Project, ReturnValue, Solutions, Solver, VM,
 };
 
 mod test_runner;
 
-use test_runner::{run, ConcreteValue};
+use test_runner::{run, run_vm, ConcreteValue, PathResult};
 
 fn as_u64(v: &Option<ConcreteValue>) -> Option<u64> {
     if let Some(v) = v {
@@ -27,6 +29,33 @@ fn simple() {
     assert_eq!(res[0].result.as_ref().map(as_u64), Ok(Some(0)));
 }
 
+// Check that a project can be created from bitcode in memory.
+#[test]
+fn simple_from_bytes() {
+    let bitcode = std::fs::read("tests/samples/simple.bc").unwrap();
+    let project = Project::from_bytes(&bitcode).unwrap();
+    let res = run_vm(VM::new("main", &project).unwrap()).unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    assert_eq!(res[0].result.as_ref().map(as_u64), Ok(Some(0)));
+
+    // Invalid bitcode is an error.
+    assert!(Project::from_bytes(b"not bitcode").is_err());
+}
+
+// Check that textual IR gives the same results as bitcode.
+#[test]
+fn simple_from_ll() {
+    let bitcode = run("tests/samples/simple.bc", "main").unwrap();
+    let text = run("tests/samples/simple.ll", "main").unwrap();
+    assert_eq!(text.len(), bitcode.len(), "expected same number of paths");
+    for (text, bitcode) in text.iter().zip(&bitcode) {
+        assert_eq!(
+            text.result.as_ref().map(as_u64),
+            bitcode.result.as_ref().map(as_u64)
+        );
+    }
+}
+
 // Check that simple if statements work.
 //
 // The path is hardcoded so only a single path should be taken.
@@ -37,6 +66,41 @@ fn ifs() {
     assert_eq!(res[0].result.as_ref().map(as_u64), Ok(Some(1)));
 }
 
+// Check that a concrete return value is rendered as an integer.
+#[test]
+fn ifs_display_return_value() {
+    let project = Project::from_path("tests/samples/ifs.bc").unwrap();
+    let mut vm = VM::new("main", &project).unwrap();
+
+    let return_value = vm.run().unwrap().unwrap();
+    assert_eq!(return_value.display(&vm.solver, true).unwrap(), "1");
+    assert!(vm.run().is_none(), "expected 1 path");
+}
+
+// Check that values wider than 128 bits are rendered in hexadecimal.
+#[test]
+fn display_wide_return_value() {
+    let solver = Solver::new();
+    let value = solver.from_binary_string(&format!("1{}", "0".repeat(135)));
+
+    let rendered = ReturnValue::Value(value).display(&solver, true).unwrap();
+    assert_eq!(rendered, format!("0x8{}", "0".repeat(33)));
+}
+
+// Check that the control-flow graph has the edges of both branches and where they join.
+#[test]
+fn ifs_function_cfg() {
+    let project = Project::from_path("tests/samples/ifs.bc").unwrap();
+    let cfg = project.function_cfg("main").unwrap();
+
+    assert!(cfg.starts_with("digraph \"main\" {"));
+    assert!(cfg.contains("\"%0\" -> \"%5\" [label=\"true\"];"));
+    assert!(cfg.contains("\"%0\" -> \"%6\" [label=\"false\"];"));
+    assert!(cfg.contains("\"%5\" -> \"%7\";"));
+    assert!(cfg.contains("\"%6\" -> \"%7\";"));
+    assert_eq!(cfg.matches("->").count(), 4);
+}
+
 // Check that if statements work.
 //
 // The input here is symbolic so both paths should be taken.
@@ -48,6 +112,220 @@ fn multiple_paths() {
     assert_eq!(res[1].result.as_ref().map(as_u64), Ok(Some(2)));
 }
 
+// Check that paths are explored on demand, the second path is saved but not explored until the
+// next result is requested.
+#[test]
+fn multiple_paths_lazy() {
+    let project = Project::from_path("tests/samples/multiple_paths.bc").unwrap();
+    let mut vm = VM::new("foo", &project).unwrap();
+    assert_eq!(vm.pending_paths(), 1);
+
+    assert!(vm.next().unwrap().is_ok());
+    assert_eq!(vm.pending_paths(), 1, "expected 1 pending path");
+
+    assert!(vm.next().unwrap().is_ok());
+    assert_eq!(vm.pending_paths(), 0);
+    assert!(vm.next().is_none());
+}
+
+// Check that the model dump contains the input symbol.
+#[test]
+fn multiple_paths_model_string() {
+    let project = Project::from_path("tests/samples/multiple_paths.bc").unwrap();
+    let mut vm = VM::new("foo", &project).unwrap();
+    assert!(vm.run().unwrap().is_ok());
+
+    let model = vm.solver.model_string().unwrap();
+    let name = &vm.parameters[0].name;
+    assert!(
+        model.contains(name.as_str()),
+        "{name} not in model: {model}"
+    );
+}
+
+// Check that input symbols are named after the function and parameter index, and that the names
+// are the same across runs.
+#[test]
+fn input_symbol_names() {
+    let project = Project::from_path("tests/samples/call.bc").unwrap();
+    let names =
+        |vm: &VM<'_>| -> Vec<String> { vm.parameters.iter().map(|p| p.name.clone()).collect() };
+
+    let mut vm = VM::new("bar", &project).unwrap();
+    let first = names(&vm);
+    assert_eq!(first, vec!["input_bar_0".to_owned()]);
+    while vm.run().is_some() {}
+
+    let vm = VM::new("bar", &project).unwrap();
+    assert_eq!(names(&vm), first);
+}
+
+// Check that the two sides of the branch are merged into one path with merging enabled.
+//
+// The return value of the merged path should still be able to take both values.
+#[test]
+fn multiple_paths_merged() {
+    let project = Project::from_path("tests/samples/multiple_paths.bc").unwrap();
+    let mut vm = VM::new("foo", &project).unwrap().with_merging(true);
+
+    let value = match vm.run() {
+        Some(Ok(ReturnValue::Value(value))) => value,
+        result => panic!("expected a return value, got {result:?}"),
+    };
+    assert_eq!(vm.run(), None, "expected 1 path");
+
+    let mut values: Vec<_> = match vm.solver.get_solutions_for_bv(&value, 2).unwrap() {
+        Solutions::Exactly(s) => s.iter().map(|s| s.as_u64().unwrap()).collect(),
+        solutions => panic!("expected exactly 2 solutions, got {solutions:?}"),
+    };
+    values.sort_unstable();
+    assert_eq!(values, vec![1, 2]);
+}
+
+// Check that deferring the satisfiability checks until the paths end gives the same paths.
+#[test]
+fn multiple_paths_lazy_constraints() {
+    let project = Project::from_path("tests/samples/multiple_paths.bc").unwrap();
+    let vm = VM::new("foo", &project)
+        .unwrap()
+        .with_lazy_constraints(true);
+
+    let res = run_vm(vm).unwrap();
+    assert_eq!(res.len(), 2, "expected 2 paths");
+    assert_eq!(res[0].result.as_ref().map(as_u64), Ok(Some(1)));
+    assert_eq!(res[1].result.as_ref().map(as_u64), Ok(Some(2)));
+}
+
+// Check that forks are counted, and that a fork with a constant condition is pruned.
+#[test]
+fn fork_stats() {
+    let project = Project::from_path("tests/samples/ifs.bc").unwrap();
+    let mut vm = VM::new("main", &project).unwrap();
+    while vm.run().is_some() {}
+    let hardcoded = vm.fork_stats();
+    assert_eq!(hardcoded.forks, 1);
+    assert_eq!(hardcoded.pruned, 1);
+
+    let project = Project::from_path("tests/samples/multiple_paths.bc").unwrap();
+    let mut vm = VM::new("foo", &project).unwrap();
+    while vm.run().is_some() {}
+    let symbolic = vm.fork_stats();
+    assert_eq!(symbolic.forks, 1);
+    assert_eq!(symbolic.pruned, 0);
+    assert_ne!(hardcoded, symbolic);
+}
+
+// Check that branches the solver cannot decide within its timeout are explored and counted, and
+// that the paths end without panicking.
+#[test]
+fn fork_stats_unknown() {
+    let project = Project::from_path("tests/samples/factor.bc").unwrap();
+    let mut vm = VM::new("is_factor", &project).unwrap();
+    vm.solver.set_query_timeout(Some(std::time::Duration::ZERO));
+
+    let mut paths = 0;
+    while let Some(result) = vm.run() {
+        paths += 1;
+        assert!(
+            matches!(result, Ok(_) | Err(VMError::SolverTimeout)),
+            "unexpected result {result:?}"
+        );
+    }
+    assert!(paths > 0, "expected at least 1 path");
+    assert!(vm.fork_stats().unknown > 0, "expected undecided branches");
+}
+
+// Check that constant expressions in instructions and global initializers are evaluated.
+#[test]
+fn constant_expressions() {
+    let project = Project::from_path("tests/samples/const_expr.bc").unwrap();
+    let run_fn = |name: &str| {
+        let vm = VM::new(name, &project).unwrap();
+        let globals = &vm.state.global_references;
+        let table = globals.find_variable("table").unwrap().addr;
+        let res = run_vm(vm).unwrap();
+        assert_eq!(res.len(), 1, "expected 1 path");
+        (table, res[0].result.as_ref().map(as_u64))
+    };
+
+    // getelementptr
+    let (table, result) = run_fn("element_address");
+    assert_eq!(result, Ok(Some(table + 8)));
+    let (_, result) = run_fn("read_third");
+    assert_eq!(result, Ok(Some(30)));
+
+    // ptrtoint and bitcast
+    let (table, result) = run_fn("element_integer");
+    assert_eq!(result, Ok(Some(table + 4)));
+    let (_, result) = run_fn("read_byte");
+    assert_eq!(result, Ok(Some(20)));
+
+    // inttoptr
+    let (_, result) = run_fn("fixed_address");
+    assert_eq!(result, Ok(Some(0x1000)));
+}
+
+// Check that sizes and alignments follow the data layout, and match the same types in Rust.
+#[test]
+fn layout_size_of() {
+    fn rust_layout<T>() -> (usize, usize) {
+        (std::mem::size_of::<T>(), std::mem::align_of::<T>())
+    }
+
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct Padded(i16, i32);
+
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct Mixed(u8, f64, u8);
+
+    #[allow(dead_code)]
+    #[repr(C)]
+    struct Nested(u8, Padded);
+
+    #[allow(dead_code)]
+    #[repr(C, packed)]
+    struct Packed(u8, i32);
+
+    let project = Project::from_path("tests/samples/layout.bc").unwrap();
+    let layout = |name: &str| {
+        let (_, var) = project
+            .get_public_global_variables()
+            .find(|(_, var)| var.name == Name::from(name))
+            .unwrap();
+        let ty = match var.ty.as_ref() {
+            Type::PointerType { pointee_type, .. } => pointee_type.clone(),
+            ty => panic!("expected a pointer, got {ty:?}"),
+        };
+        let size = project.size_of(&ty).unwrap() as usize;
+        let align = project.align_of(&ty).unwrap() as usize;
+        (size, align)
+    };
+
+    assert_eq!(layout("padded"), rust_layout::<Padded>());
+    assert_eq!(layout("mixed"), rust_layout::<Mixed>());
+    assert_eq!(layout("nested"), rust_layout::<Nested>());
+    assert_eq!(layout("packed"), rust_layout::<Packed>());
+    assert_eq!(layout("mixed_array"), rust_layout::<[Mixed; 3]>());
+    assert_eq!(layout("flag"), rust_layout::<bool>());
+}
+
+// Check that fields are read from the same layout as the globals are initialized with, including
+// the padding between fields and between array elements.
+#[test]
+fn layout_field_access() {
+    let run_fn = |name: &str| {
+        let res = run("tests/samples/layout.bc", name).unwrap();
+        assert_eq!(res.len(), 1, "expected 1 path for {name}");
+        res[0].result.as_ref().map(as_u64).unwrap()
+    };
+
+    assert_eq!(run_fn("read_padded"), Some(4));
+    assert_eq!(run_fn("read_mixed"), Some(7));
+    assert_eq!(run_fn("copy_mixed"), Some(12));
+}
+
 // Check that function calls work.
 //
 // This also has backtracking so it ensures it correctly handles re-entry into an inner function.
@@ -59,6 +337,121 @@ fn call() {
     assert_eq!(res[1].result.as_ref().map(as_u64), Ok(Some(1)));
 }
 
+// Check that calling a function without a definition or hook reports the function name.
+#[test]
+fn undefined_function() {
+    let res = run("tests/samples/extern.bc", "call_external").unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    assert_eq!(
+        res[0].result,
+        Err(VMError::UndefinedFunction("external".to_owned()))
+    );
+}
+
+// Check that globals without a definition are symbolic, so both branches on them are feasible.
+#[test]
+fn symbolic_globals() {
+    let project = Project::from_path("tests/samples/extern_global.bc").unwrap();
+    let vm = VM::new("check_config", &project)
+        .unwrap()
+        .with_symbolic_globals()
+        .unwrap();
+
+    let res = run_vm(vm).unwrap();
+    assert_eq!(res.len(), 2, "expected 2 paths");
+    let mut results: Vec<_> = res.iter().map(|r| r.result.as_ref().map(as_u64)).collect();
+    results.sort_by_key(|r| r.clone().ok().flatten());
+    assert_eq!(results, vec![Ok(Some(0)), Ok(Some(1))]);
+
+    // The value of the global is reported with the symbols of each path.
+    for path in &res {
+        assert_eq!(path.symbolics.len(), 1, "expected the global as a symbol");
+        let config = path.symbolics[0].as_u64() as i32;
+        match as_u64(path.result.as_ref().unwrap()) {
+            Some(1) => assert!(config > 10, "config {config} should be above 10"),
+            _ => assert!(config <= 10, "config {config} should be at most 10"),
+        }
+    }
+}
+
+// Check that variadic arguments are read back in order.
+#[test]
+fn varargs() {
+    let res = run("tests/samples/varargs.bc", "call_variadic").unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    let a = res[0].inputs[0].as_u64();
+    let b = res[0].inputs[1].as_u64();
+    assert_eq!(
+        res[0].result.as_ref().map(as_u64),
+        Ok(Some(a.wrapping_sub(b) & 0xffff_ffff))
+    );
+}
+
+// Check that the summary of a branch-free function is an expression of its inputs.
+#[test]
+fn symbolic_return_summary() {
+    let project = Project::from_path("tests/samples/summary.bc").unwrap();
+    let (value, inputs) = VM::symbolic_return_of("affine", &project).unwrap();
+    assert_eq!(inputs.len(), 2);
+
+    let solver = value.get_solver();
+    let x = inputs[0].value.eq(&solver.bv_from_u64(2, 32));
+    let y = inputs[1].value.eq(&solver.bv_from_u64(5, 32));
+    let expected = value.eq(&solver.bv_from_u64(4, 32));
+    assert!(solver
+        .is_sat_with_constraints(&[&x, &y, &expected])
+        .unwrap());
+    assert!(!solver
+        .is_sat_with_constraints(&[&x, &y, &expected.not()])
+        .unwrap());
+
+    let res = VM::symbolic_return_of("abs_value", &project);
+    assert_eq!(res.map(|_| ()), Err(VMError::MultiplePaths));
+}
+
+// Check that calls to aliases run the aliasee, and that aliased globals share memory.
+#[test]
+fn aliases() {
+    let res = run("tests/samples/alias.bc", "call_alias").unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    let x = res[0].inputs[0].as_u64();
+    assert_eq!(
+        res[0].result.as_ref().map(as_u64),
+        Ok(Some((2 * x + 2) & 0xffff_ffff))
+    );
+
+    let res = run("tests/samples/alias.bc", "read_alias").unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    assert_eq!(res[0].result.as_ref().map(as_u64), Ok(Some(12)));
+}
+
+// Check that thread locals are read from their initialized global.
+#[test]
+fn thread_local() {
+    let res = run("tests/samples/thread_local.bc", "thread_local::read").unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    assert_eq!(res[0].result.as_ref().map(as_u64), Ok(Some(42)));
+
+    let res = run("tests/samples/thread_local.bc", "thread_local::add").unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    let x = res[0].inputs[0].as_u64();
+    assert_eq!(
+        res[0].result.as_ref().map(as_u64),
+        Ok(Some((42 + x) & 0xffff_ffff))
+    );
+}
+
+// Test that calls to inline assembly return an error with the calling function.
+#[test]
+fn inline_asm() {
+    let res = run("tests/samples/inline_asm.bc", "inline_asm::nop").unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    match &res[0].result {
+        Err(VMError::InlineAsmUnsupported(caller)) => assert!(caller.contains("inline_asm3nop")),
+        result => panic!("expected InlineAsmUnsupported, got {result:?}"),
+    }
+}
+
 // Test that it can handle traits (global variable vtables).
 #[test]
 fn traits() {
@@ -75,6 +468,161 @@ fn match_works() {
     assert_eq!(res[0].result, Ok(None));
 }
 
+// Test that matching on niche-optimized options forks on whether the pointer is null.
+#[test]
+fn niche_option() {
+    for function in ["niche::is_some_ref", "niche::is_some_non_null"] {
+        let res = run("tests/samples/niche.bc", function).unwrap();
+        assert_eq!(res.len(), 2, "expected 2 paths for {function}");
+
+        let mut results: Vec<_> = res.iter().map(|r| r.result.as_ref().map(as_u64)).collect();
+        results.sort_by_key(|r| r.clone().ok().flatten());
+        assert_eq!(results, vec![Ok(Some(0)), Ok(Some(1))]);
+
+        // The `None` path is the one where the pointer is null.
+        for path in &res {
+            let is_null = path.inputs[0].as_u64() == 0;
+            let expected = if is_null { 0 } else { 1 };
+            assert_eq!(path.result.as_ref().map(as_u64), Ok(Some(expected)));
+        }
+    }
+}
+
+// Check that `leading_zeros` is defined for zero, while zero is poison for `__builtin_clz`.
+#[test]
+fn leading_zeros_poison() {
+    let res = run("tests/samples/leading_zeros.bc", "leading_zeros::count").unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    let expected = (res[0].inputs[0].as_u64() as u32).leading_zeros() as u64;
+    assert_eq!(res[0].result.as_ref().map(as_u64), Ok(Some(expected)));
+
+    let res = run("tests/samples/clz.bc", "clz").unwrap();
+    assert_eq!(res.len(), 2, "expected 2 paths");
+    assert_ne!(res[0].inputs[0].as_u64(), 0);
+    let expected = (res[0].inputs[0].as_u64() as u32).leading_zeros() as u64;
+    assert_eq!(res[0].result.as_ref().map(as_u64), Ok(Some(expected)));
+    assert_eq!(res[1].result, Err(VMError::PoisonValue));
+}
+
+// Check that a symbolic switch forks into one path per reachable target.
+//
+// Two of the cases share a target, so there should only be one path for them.
+#[test]
+fn match_symbolic() {
+    let res = run("tests/samples/match.bc", "match::classify").unwrap();
+    assert_eq!(res.len(), 5, "expected 5 paths");
+    let mut results: Vec<_> = res
+        .iter()
+        .map(|r| r.result.as_ref().map(as_u64).unwrap().unwrap())
+        .collect();
+    results.sort_unstable();
+    assert_eq!(results, vec![1, 2, 3, 4, 5]);
+}
+
+/// Scheduler that explores paths starting in `block` first, and the rest depth-first.
+#[derive(Clone)]
+struct BlockPriority<'a> {
+    block: Name,
+    paths: Vec<Path<'a>>,
+}
+
+impl<'a> Scheduler<'a> for BlockPriority<'a> {
+    fn push(&mut self, path: Path<'a>) {
+        self.paths.push(path);
+    }
+
+    fn pop(&mut self) -> Option<Path<'a>> {
+        let prioritized = self
+            .paths
+            .iter()
+            .rposition(|path| path.state.current_loc.block.name == self.block);
+        match prioritized {
+            Some(index) => Some(self.paths.remove(index)),
+            None => self.paths.pop(),
+        }
+    }
+
+    fn len(&self) -> usize {
+        self.paths.len()
+    }
+}
+
+// Check that a custom scheduler decides the order paths are explored in.
+//
+// The case for `1` is prioritized, so it should be the first path resumed after the switch.
+#[test]
+fn match_custom_scheduler() {
+    let project = Project::from_path("tests/samples/match.bc").unwrap();
+    let (_, function) = project.find_entry_function("match::classify").unwrap();
+    let block = function
+        .basic_blocks
+        .iter()
+        .find_map(|block| match &block.term {
+            Terminator::Switch(switch) => switch
+                .dests
+                .iter()
+                .find(|(value, _)| matches!(value.as_ref(), Constant::Int { value: 1, .. }))
+                .map(|(_, target)| target.clone()),
+            _ => None,
+        })
+        .unwrap();
+
+    let vm = VM::new("match::classify", &project)
+        .unwrap()
+        .with_scheduler(BlockPriority {
+            block,
+            paths: Vec::new(),
+        });
+    let res = run_vm(vm).unwrap();
+    assert_eq!(res.len(), 5, "expected 5 paths");
+
+    let results: Vec<_> = res
+        .iter()
+        .map(|r| r.result.as_ref().map(as_u64).unwrap().unwrap())
+        .collect();
+    assert_eq!(results[1], 2, "expected the prioritized path second");
+
+    let mut sorted = results.clone();
+    sorted.sort_unstable();
+    assert_eq!(sorted, vec![1, 2, 3, 4, 5]);
+}
+
+// Check that the random scheduler explores the paths in the same order given the same seed, and in
+// different orders for different seeds.
+#[test]
+fn match_random_seed() {
+    let project = Project::from_path("tests/samples/match.bc").unwrap();
+    let run_seeded = |seed| {
+        let vm = VM::new("match::classify", &project)
+            .unwrap()
+            .with_scheduler(RandomScheduler::new(0))
+            .with_random_seed(seed);
+        run_vm(vm)
+            .unwrap()
+            .iter()
+            .map(|r| r.result.as_ref().map(as_u64).unwrap().unwrap())
+            .collect::<Vec<_>>()
+    };
+
+    let first = run_seeded(1234);
+    assert_eq!(first.len(), 5, "expected 5 paths");
+    assert_eq!(first, run_seeded(1234));
+
+    // A few seeds could happen to give the same order, but not all of them.
+    let mut sorted = first.clone();
+    sorted.sort_unstable();
+    let others: Vec<_> = (0..8).map(run_seeded).collect();
+    for other in others.iter() {
+        let mut other = other.clone();
+        other.sort_unstable();
+        assert_eq!(other, sorted, "expected the same paths in any order");
+    }
+    assert!(
+        others.iter().any(|other| *other != first),
+        "expected different seeds to give different orders"
+    );
+}
+
 // Check that it can handle global references to functions.
 //
 // This will call one of two functions depending on a bool.
@@ -86,9 +634,141 @@ fn fn_refs() {
     assert_eq!(res[1].result.as_ref().map(as_u64), Ok(Some(11)));
 }
 
+// Check that loads through a pointer that can be null are flagged when null checks are enabled.
+#[test]
+fn null_pointer_dereference() {
+    let project = Project::from_path("tests/samples/null_deref.bc").unwrap();
+    let vm = VM::new("deref", &project).unwrap().with_null_checks(true);
+    let res = run_vm(vm).unwrap();
+    assert_eq!(res.len(), 2, "expected 2 paths");
+    assert!(res[0].result.is_ok());
+    assert!(res[0].inputs[0].as_u64() >= NULL_PAGE_SIZE);
+
+    assert_eq!(res[1].result, Err(VMError::NullPointerDereference));
+    assert!(res[1].inputs[0].as_u64() < NULL_PAGE_SIZE);
+
+    // Without null checks the pointer is not checked.
+    let res = run("tests/samples/null_deref.bc", "deref").unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+
+    // Pointers marked `nonnull` are assumed to be outside the null page.
+    let vm = VM::new("deref_nonnull", &project)
+        .unwrap()
+        .with_null_checks(true);
+    let res = run_vm(vm).unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    assert!(res[0].result.is_ok());
+    assert!(res[0].inputs[0].as_u64() >= NULL_PAGE_SIZE);
+}
+
+// Check that execution can start in the middle of a function.
+#[test]
+fn start_at_block() {
+    let project = Project::from_path("tests/samples/start_block.bc").unwrap();
+    let (_, function) = project.find_entry_function("pick").unwrap();
+    let block = &function.basic_blocks[1].name;
+
+    let vm = VM::new_at("pick", block, &project).unwrap();
+    let res = run_vm(vm).unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    assert!(res[0].result.is_ok());
+    assert!(!res[0].symbolics.is_empty(), "expected symbolic locals");
+}
+
+// Check that the output of `printf` and `puts` is captured for the path.
+#[test]
+fn printf_output() {
+    let project = Project::from_path("tests/samples/printf.bc").unwrap();
+    let mut vm = VM::new("print_value", &project).unwrap();
+    assert!(vm.run().unwrap().is_ok());
+    assert_eq!(vm.output(), "value: -42, hex: ff, ok\ndone\n");
+
+    let mut vm = VM::new("print_symbolic", &project).unwrap();
+    assert!(vm.run().unwrap().is_ok());
+    assert_eq!(vm.output(), "<symbolic>\n");
+}
+
+// Check that evaluating concrete operations natively gives the same result as the solver.
+#[test]
+fn concrete_fast_path() {
+    let project = Project::from_path("tests/samples/concrete.bc").unwrap();
+    let fast = run_vm(VM::new("main", &project).unwrap()).unwrap();
+    let vm = VM::new("main", &project)
+        .unwrap()
+        .with_concrete_fast_path(false);
+    let slow = run_vm(vm).unwrap();
+
+    assert_eq!(fast.len(), 1, "expected 1 path");
+    assert_eq!(slow.len(), 1, "expected 1 path");
+    assert!(fast[0].result.is_ok());
+    assert_eq!(fast[0].result, slow[0].result);
+}
+
+// Check that reading a local through a pointer after its function returned is detected.
+#[test]
+fn use_after_return() {
+    let project = Project::from_path("tests/samples/dangling.bc").unwrap();
+    let vm = VM::new("dangling", &project)
+        .unwrap()
+        .with_use_after_return_checks(true);
+    let res = run_vm(vm).unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    assert_eq!(res[0].result, Err(VMError::UseAfterReturn));
+
+    // Without the checks the dead allocation is read as usual.
+    let res = run("tests/samples/dangling.bc", "dangling").unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    assert!(res[0].result.is_ok());
+}
+
+// Check that misaligned vector loads are flagged when alignment checks are enabled.
+#[test]
+fn misaligned_vector_load() {
+    let project = Project::from_path("tests/samples/alignment.bc").unwrap();
+    let vm = VM::new("misaligned", &project)
+        .unwrap()
+        .with_alignment_checks(true);
+    let res = run_vm(vm).unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    assert_eq!(res[0].result, Err(VMError::MisalignedAccess(16)));
+
+    let vm = VM::new("aligned", &project)
+        .unwrap()
+        .with_alignment_checks(true);
+    let res = run_vm(vm).unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    assert_eq!(res[0].result.as_ref().map(as_u64), Ok(Some(0)));
+}
+
+// Check that fail fast mode stops at the first error path.
+#[test]
+fn array_index_fail_fast() {
+    let project = Project::from_path("tests/samples/array_index.bc").unwrap();
+    let vm = VM::new("array_index::get", &project)
+        .unwrap()
+        .with_fail_fast(true);
+    let res = run_vm(vm).unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    assert!(matches!(
+        res[0].result,
+        Err(VMError::Panic(PanicReason::BoundsCheck { len: 4, .. }))
+    ));
+
+    // Without fail fast both out of bounds accesses are found.
+    let res = run("tests/samples/array_index.bc", "array_index::get_sum").unwrap();
+    assert_eq!(res.len(), 3, "expected 3 paths");
+
+    let vm = VM::new("array_index::get_sum", &project)
+        .unwrap()
+        .with_fail_fast(true);
+    let res = run_vm(vm).unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    assert!(matches!(res[0].result, Err(VMError::Panic(_))));
+}
+
 // Check that array indexing works.
 //
-// For typical checked array gets it will return panic on out of bounds.
+// For typical checked array gets it will return panic on out of bounds, with the index and length.
 #[test]
 fn array_index() {
     let res = run("tests/samples/array_index.bc", "array_index::get").unwrap();
@@ -96,10 +776,120 @@ fn array_index() {
     assert!(res[0].result.is_ok());
     assert!(res[0].inputs[0].as_u64() <= 3);
 
-    assert_eq!(res[1].result, Err(VMError::Abort(-1)));
+    match &res[1].result {
+        Err(VMError::Panic(PanicReason::BoundsCheck { index, len })) => {
+            assert!(*index > 3);
+            assert_eq!(*len, 4);
+        }
+        result => panic!("expected bounds check panic, got {result:?}"),
+    }
     assert!(res[1].inputs[0].as_u64() > 3);
 }
 
+// Check that constraining the index to be in bounds removes the out of bounds path.
+#[test]
+fn array_index_constrain_input() {
+    let project = Project::from_path("tests/samples/array_index.bc").unwrap();
+    let mut vm = VM::new("array_index::get", &project).unwrap();
+    vm.constrain_input(0, 0, 3).unwrap();
+
+    let res = run_vm(vm).unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    assert!(res[0].result.is_ok());
+    assert!(res[0].inputs[0].as_u64() <= 3);
+}
+
+// Check that inputs can be constrained to signed ranges, and that ranges must fit the input.
+#[test]
+fn gep_constrain_input_signed() {
+    let project = Project::from_path("tests/samples/gep.bc").unwrap();
+    let mut vm = VM::new("get", &project).unwrap();
+    vm.constrain_input_signed(0, -2, 1).unwrap();
+
+    let res = run_vm(vm).unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    let index = res[0].inputs[0].as_u64() as u32 as i32;
+    assert!((-2..=1).contains(&index), "index {index} out of range");
+    assert_eq!(
+        res[0].result.as_ref().map(as_u64),
+        Ok(Some((index + 2) as u64))
+    );
+
+    // The parameter is an `int`, so the bounds must fit in 32 bits.
+    let mut vm = VM::new("get", &project).unwrap();
+    let is_other = |res| matches!(res, Err(VMError::Other(_)));
+    assert!(is_other(vm.constrain_input(0, 0, 1 << 32)));
+    let (below, above) = (i32::MIN as i64 - 1, i32::MAX as i64 + 1);
+    assert!(is_other(vm.constrain_input_signed(0, below, 0)));
+    assert!(is_other(vm.constrain_input_signed(0, 0, above)));
+    assert!(vm.constrain_input_signed(0, i32::MIN as i64, 0).is_ok());
+
+    // Empty ranges and parameters that do not exist are errors by the caller.
+    assert!(is_other(vm.constrain_input(0, 2, 1)));
+    assert!(is_other(vm.constrain_input(1, 0, 1)));
+}
+
+// Check that only the out of bounds path is returned when iterating over error paths.
+#[test]
+fn array_index_error_paths() {
+    let project = Project::from_path("tests/samples/array_index.bc").unwrap();
+    let mut vm = VM::new("array_index::get", &project).unwrap();
+
+    let mut errors = vm.error_paths();
+    assert!(matches!(errors.next(), Some(VMError::Panic(_))));
+
+    // The error path should only be reachable with an out of bounds index.
+    let vm = errors.vm();
+    let idx = &vm.parameters[0].value;
+    let max_idx = vm.solver.bv_from_u64(3, idx.len());
+    assert!(!vm
+        .solver
+        .is_sat_with_constraint(&idx.ulte(&max_idx))
+        .unwrap());
+    assert_eq!(errors.next(), None);
+}
+
+// Check that replaying the model from the out of bounds path reproduces the panic.
+#[test]
+fn array_index_replay() {
+    let project = Project::from_path("tests/samples/array_index.bc").unwrap();
+    let mut vm = VM::new("array_index::get", &project).unwrap();
+
+    let mut errors = vm.error_paths();
+    assert!(matches!(errors.next(), Some(VMError::Panic(_))));
+    let model = errors.vm().model().unwrap();
+    let index = u64::from_str_radix(&model.parameters[0], 2).unwrap();
+
+    let mut replay_vm = VM::new("array_index::get", &project).unwrap();
+    let expected = VMError::Panic(PanicReason::BoundsCheck { index, len: 4 });
+    assert_eq!(replay_vm.replay(&model), Err(expected));
+    assert_eq!(replay_vm.run(), None);
+}
+
+// Check that a panic reached on several loop iterations is reported once.
+#[test]
+fn array_index_unique_errors() {
+    let res = run("tests/samples/array_index.bc", "array_index::sum_shifted").unwrap();
+    let errors = res.iter().filter(|path| path.result.is_err()).count();
+    assert_eq!(errors, 4, "expected a panic on each iteration");
+
+    let project = Project::from_path("tests/samples/array_index.bc").unwrap();
+    let mut vm = VM::new("array_index::sum_shifted", &project).unwrap();
+    let reports = vm.unique_errors().unwrap();
+    assert_eq!(reports.len(), 1, "expected 1 unique error");
+    assert_eq!(reports[0].count, 4);
+    assert!(matches!(
+        reports[0].error,
+        VMError::Panic(PanicReason::BoundsCheck { len: 4, .. })
+    ));
+
+    let mut replay_vm = VM::new("array_index::sum_shifted", &project).unwrap();
+    assert!(matches!(
+        replay_vm.replay(&reports[0].model),
+        Err(VMError::Panic(PanicReason::BoundsCheck { len: 4, .. }))
+    ));
+}
+
 // Check that array indexing works.
 #[test]
 fn array_index_works() {
@@ -112,19 +902,276 @@ fn array_index_works() {
     assert_eq!(res[0].result.as_ref().map(as_u64), Ok(Some(3)));
 }
 
+// Check that panics unwind to the landing pad of an `invoke`.
+//
+// The panic from the out of bounds path is caught by `catch_unwind`, so both paths return.
+#[test]
+fn catch_unwind() {
+    let res = run("tests/samples/unwind.bc", "unwind::catch").unwrap();
+    assert_eq!(res.len(), 2, "expected 2 paths");
+
+    let mut caught = 0;
+    for path in res.iter() {
+        let idx = path.inputs[0].as_u64();
+        let result = path.result.as_ref().map(as_u64).unwrap().unwrap();
+        if idx <= 3 {
+            assert_eq!(result, idx);
+        } else {
+            assert_eq!(result as u32 as i32, -1);
+            caught += 1;
+        }
+    }
+    assert_eq!(caught, 1, "expected the out of bounds path to be caught");
+}
+
+// Check that overflow checks in debug builds are followed.
+//
+// One path should return the sum, and the other should panic with the overflow message.
+#[test]
+fn add_overflow() {
+    let res = run("tests/samples/overflow.bc", "overflow::add").unwrap();
+    assert_eq!(res.len(), 2, "expected 2 paths");
+
+    let expected = Err(VMError::Panic(PanicReason::Message(
+        "attempt to add with overflow".to_owned(),
+    )));
+    let (ok, overflow) = match res[0].result.is_ok() {
+        true => (&res[0], &res[1]),
+        false => (&res[1], &res[0]),
+    };
+
+    let (a, b) = (ok.inputs[0].as_u64(), ok.inputs[1].as_u64());
+    assert!(a + b <= u8::MAX as u64);
+    assert_eq!(ok.result.as_ref().map(as_u64), Ok(Some(a + b)));
+
+    assert_eq!(overflow.result, expected);
+    let (a, b) = (overflow.inputs[0].as_u64(), overflow.inputs[1].as_u64());
+    assert!(a + b > u8::MAX as u64);
+}
+
+// Check that the stack allocation for the array shows up in the allocations.
+#[test]
+fn array_index_allocations() {
+    let project = Project::from_path("tests/samples/array_index.bc").unwrap();
+    let mut vm = VM::new("array_index::indexing_works", &project).unwrap();
+    let before = vm.state.allocations().count();
+
+    assert!(vm.run().unwrap().is_ok());
+    let new: Vec<_> = vm.state.allocations().skip(before).collect();
+    assert!(
+        new.iter().any(|a| a.bytes == 16 && a.live),
+        "expected a 16 byte allocation, got {new:?}"
+    );
+}
+
 // Check that array indexing works even when using the unsafe `get_unchecked`
 #[test]
 fn array_index_get_unchecked() {
-    if CHECK_OUT_OF_BOUNDS {
-        let res = run("tests/samples/array_index.bc", "array_index::get_unchecked").unwrap();
-        assert_eq!(res.len(), 1, "expected 1 path");
+    // The `getelementptr` forks on the index being out of bounds, and the in bounds path can
+    // still read one past the end.
+    let project = Project::from_path("tests/samples/array_index.bc").unwrap();
+    let vm = VM::new("array_index::get_unchecked", &project)
+        .unwrap()
+        .with_bounds_checks(true);
+    let res = run_vm(vm).unwrap();
+    assert_eq!(res.len(), 2, "expected 2 paths");
+    for path in res {
         assert_eq!(
-            res[0].result,
+            path.result,
             Err(VMError::MemoryError(MemoryError::OutOfBounds))
         );
     }
 }
 
+// Check that `getelementptr` handles negative symbolic indices.
+//
+// The pointer is in the middle of the array, so small negative indices are valid while larger ones
+// are out of bounds.
+#[test]
+fn gep_negative_index() {
+    let project = Project::from_path("tests/samples/gep.bc").unwrap();
+    let vm = VM::new("get", &project).unwrap().with_bounds_checks(true);
+    let mut res = run_vm(vm).unwrap();
+    assert_eq!(res.len(), 3, "expected 3 paths");
+
+    // Sort by the index, so the result does not depend on the order paths are explored in.
+    res.sort_by_key(|path| path.inputs[0].as_u64() as u32 as i32);
+
+    assert_eq!(
+        res[0].result,
+        Err(VMError::MemoryError(MemoryError::OutOfBounds))
+    );
+    let index = res[0].inputs[0].as_u64() as u32 as i32;
+    assert!(index < -2, "index {index} should be out of bounds");
+
+    assert!(res[1].result.is_ok());
+    let index = res[1].inputs[0].as_u64() as u32 as i32;
+    assert!(
+        (-2..=1).contains(&index),
+        "index {index} should be in bounds"
+    );
+    assert_eq!(
+        res[1].result.as_ref().map(as_u64),
+        Ok(Some((index + 2) as u64))
+    );
+
+    let index = res[2].inputs[0].as_u64() as u32 as i32;
+    assert!(index > 1, "index {index} should take the early return");
+    assert_eq!(
+        res[2].result.as_ref().map(as_u64),
+        Ok(Some(-1i32 as u32 as u64))
+    );
+}
+
+// Check that an inbounds getelementptr past the end of an array is poison.
+//
+// Pointing one element past the end is allowed, but not further than that.
+#[test]
+fn gep_inbounds_past_end() {
+    let project = Project::from_path("tests/samples/inbounds.bc").unwrap();
+    let vm = VM::new("past_end", &project)
+        .unwrap()
+        .with_inbounds_checks(true);
+    let res = run_vm(vm).unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    assert_eq!(res[0].result, Err(VMError::InboundsViolation));
+
+    let vm = VM::new("offset", &project)
+        .unwrap()
+        .with_inbounds_checks(true);
+    let res = run_vm(vm).unwrap();
+    assert_eq!(res.len(), 2, "expected 2 paths");
+    for path in res {
+        let index = path.inputs[0].as_u64() as u32 as i32;
+        if path.result.is_ok() {
+            assert!((0..=4).contains(&index), "index {index} out of bounds");
+        } else {
+            assert_eq!(path.result, Err(VMError::InboundsViolation));
+            assert!(!(0..=4).contains(&index), "index {index} should be poison");
+        }
+    }
+}
+
+// Check that variable length arrays are bounded by their symbolic size.
+#[test]
+fn vla_symbolic_size() {
+    let project = Project::from_path("tests/samples/vla.bc").unwrap();
+    let vm = VM::new("read_past_end", &project)
+        .unwrap()
+        .with_bounds_checks(true);
+    let res = run_vm(vm).unwrap();
+    let out_of_bounds: Vec<_> = res
+        .iter()
+        .filter(|path| path.result == Err(VMError::MemoryError(MemoryError::OutOfBounds)))
+        .collect();
+    assert_eq!(out_of_bounds.len(), 1, "expected 1 out of bounds path");
+    for path in res.iter().filter(|path| path.result.is_ok()) {
+        assert_eq!(
+            path.result.as_ref().map(as_u64),
+            Ok(Some(-1i32 as u32 as u64))
+        );
+    }
+
+    let vm = VM::new("read_at_end", &project)
+        .unwrap()
+        .with_bounds_checks(true);
+    let res = run_vm(vm).unwrap();
+    assert!(res.iter().all(|path| path.result.is_ok()));
+}
+
+// Check that variable length arrays that can exceed the maximum size end with an error.
+#[test]
+fn vla_unbounded() {
+    let mut res = run("tests/samples/vla.bc", "unbounded").unwrap();
+    assert_eq!(res.len(), 2, "expected 2 paths");
+
+    // Sort the successful path first, so the result does not depend on the exploration order.
+    res.sort_by_key(|path| path.result.is_err());
+    assert_eq!(res[0].result.as_ref().map(as_u64), Ok(Some(1)));
+    assert_eq!(
+        res[1].result,
+        Err(VMError::MemoryError(
+            MemoryError::SymbolicAllocationTooLarge(MAX_SYMBOLIC_ALLOCATION)
+        ))
+    );
+}
+
+// Check that arrays freed by `llvm.stackrestore` cannot be used, while the array of the current
+// iteration can.
+#[test]
+fn stackrestore_frees_allocas() {
+    let project = Project::from_path("tests/samples/stackrestore.bc").unwrap();
+    let vm = VM::new("read_in_loop", &project)
+        .unwrap()
+        .with_use_after_return_checks(true);
+    let res = run_vm(vm).unwrap();
+    assert_eq!(res.len(), 2, "expected 2 paths");
+    for path in res.iter() {
+        let value = path.result.as_ref().map(as_u64).unwrap();
+        assert!(value == Some(3) || value == Some(-1i32 as u32 as u64));
+    }
+
+    let vm = VM::new("use_after_restore", &project)
+        .unwrap()
+        .with_use_after_return_checks(true);
+    let res = run_vm(vm).unwrap();
+    assert_eq!(res.len(), 2, "expected 2 paths");
+    let errors: Vec<_> = res.iter().filter(|path| path.result.is_err()).collect();
+    assert_eq!(errors.len(), 1, "expected 1 error path");
+    assert_eq!(errors[0].result, Err(VMError::UseAfterReturn));
+}
+
+// Check that a pointer cast to an integer and back can still be dereferenced.
+#[test]
+fn ptr_int_round_trip() {
+    let project = Project::from_path("tests/samples/ptr_cast.bc").unwrap();
+    let vm = VM::new("ptr_cast::round_trip", &project)
+        .unwrap()
+        .with_provenance_warnings(true);
+
+    let res = run_vm(vm).unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    let input = res[0].inputs[0].as_u64();
+    assert_eq!(
+        res[0].result.as_ref().map(as_u64),
+        Ok(Some(input.wrapping_add(1) & 0xffff_ffff))
+    );
+}
+
+// Check that atomics behave as a read-modify-write that returns the old value.
+#[test]
+fn atomics_fetch_add() {
+    let res = run("tests/samples/atomics.bc", "atomics::fetch_add").unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    let x = res[0].inputs[0].as_u64();
+    assert_eq!(
+        res[0].result.as_ref().map(as_u64),
+        Ok(Some((20 + x) & 0xffff_ffff))
+    );
+}
+
+// Check that compare exchange only replaces the value if it matches the expected value.
+#[test]
+fn atomics_compare_exchange() {
+    let res = run("tests/samples/atomics.bc", "atomics::compare_exchange").unwrap();
+    assert_eq!(res.len(), 2, "expected 2 paths");
+    let mut results: Vec<_> = res
+        .iter()
+        .map(|r| r.result.as_ref().map(as_u64).unwrap().unwrap())
+        .collect();
+    results.sort_unstable();
+    assert_eq!(results, vec![12, 505]);
+}
+
+// Check that fences and atomic loads and stores with an ordering run to completion.
+#[test]
+fn atomics_fences() {
+    let res = run("tests/samples/atomics.bc", "atomics::fences").unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    let x = res[0].inputs[0].as_u64();
+    assert_eq!(res[0].result.as_ref().map(as_u64), Ok(Some(x)));
+}
+
 // Check that basic loops work
 #[test]
 fn loops_work() {
@@ -133,6 +1180,81 @@ fn loops_work() {
     assert_eq!(res[0].result.as_ref().map(as_u64), Ok(Some(0xbc)));
 }
 
+// Check that a path is terminated when it exceeds the instruction budget.
+#[test]
+fn loops_budget_exhausted() {
+    let project = Project::from_path("tests/samples/loop.bc").unwrap();
+    let vm = VM::new("loop::simple_loop_works", &project)
+        .unwrap()
+        .with_budget(Budget {
+            max_instructions: Some(10),
+            max_wall_time: None,
+        });
+
+    let res = run_vm(vm).unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    assert_eq!(res[0].result, Err(VMError::BudgetExhausted));
+}
+
+// Check that the paths finished before the deadline are returned, and that the rest are kept.
+#[test]
+fn call_deadline_partial_results() {
+    let project = Project::from_path("tests/samples/call.bc").unwrap();
+    let mut vm = VM::new("bar", &project).unwrap();
+
+    // Each path takes longer than the deadline, so only the first path finishes.
+    let slow_path = |_: &VM<'_>, result: x0001e::Result<ReturnValue>| {
+        std::thread::sleep(std::time::Duration::from_millis(20));
+        result.is_ok()
+    };
+    let deadline = std::time::Duration::from_millis(5);
+    let (results, timed_out) = vm.run_all_with_deadline(deadline, slow_path);
+    assert_eq!(results, vec![true]);
+    assert!(timed_out);
+
+    let deadline = std::time::Duration::from_secs(60);
+    let (results, timed_out) = vm.run_all_with_deadline(deadline, |_, result| result.is_ok());
+    assert_eq!(results, vec![true]);
+    assert!(!timed_out);
+}
+
+// Check that a path stopped by the deadline is saved and resumed by a later call.
+#[test]
+fn deadline_resumes_stopped_path() {
+    let project = Project::from_path("tests/samples/multiple_paths.bc").unwrap();
+    let mut vm = VM::new("foo", &project).unwrap();
+
+    // The deadline has passed before the first instruction, so the path is stopped and saved.
+    let (results, timed_out) = vm.run_all_with_deadline(std::time::Duration::ZERO, |_, r| r);
+    assert!(results.is_empty());
+    assert!(timed_out);
+    assert_eq!(vm.pending_paths(), 1);
+
+    let deadline = std::time::Duration::from_secs(60);
+    let (results, timed_out) = vm.run_all_with_deadline(deadline, |_, result| result.is_ok());
+    assert_eq!(results, vec![true, true]);
+    assert!(!timed_out);
+}
+
+// Check that cached satisfiability results avoid solver checks, without changing the result.
+#[test]
+fn loops_fewer_solver_checks() {
+    let project = Project::from_path("tests/samples/loop.bc").unwrap();
+    let vm = VM::new("loop::simple_loop_works", &project).unwrap();
+    let solver = vm.solver.clone();
+
+    let res = run_vm(vm).unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    assert_eq!(res[0].result.as_ref().map(as_u64), Ok(Some(0xbc)));
+
+    let stats = solver.stats();
+    assert!(
+        stats.checks < stats.queries,
+        "expected cached results, got {:?}",
+        stats
+    );
+}
+
 //
 #[test]
 fn structs_work() {
@@ -141,9 +1263,253 @@ fn structs_work() {
     assert_eq!(res[0].result.as_ref().map(as_u64), Ok(Some(15)));
 }
 
-// #[test]
-// fn structs_output_is_good() {
-//     let res = run("tests/samples/structs.bc", "structs::foobar").unwrap();
-//     assert_eq!(res.len(), 1, "expected 1 path");
-//     // assert_eq!(res[0].result.as_ref().map(as_u64), Ok(Some(15)));
-// }
+// Check that aggregate initializers of globals are written to memory.
+#[test]
+fn globals_aggregate_initializer() {
+    let res = run("tests/samples/globals.bc", "globals::read_static").unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    assert_eq!(res[0].result.as_ref().map(as_u64), Ok(Some(6)));
+}
+
+// Check that globals are only initialized once, so writes to them are kept.
+#[test]
+fn globals_initialized_once() {
+    let res = run("tests/samples/globals.bc", "globals::increment").unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    assert_eq!(res[0].result.as_ref().map(as_u64), Ok(Some(2)));
+}
+
+// Check that a global can be pinned to a concrete value, so only the matching branch is taken.
+#[test]
+fn globals_concrete_override() {
+    let project = Project::from_path("tests/samples/globals.bc").unwrap();
+
+    let res = run_vm(VM::new("globals::feature", &project).unwrap()).unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    assert_eq!(res[0].result.as_ref().map(as_u64), Ok(Some(2)));
+
+    let vm = VM::new("globals::feature", &project)
+        .unwrap()
+        .with_concrete_global("globals::ENABLED", 1)
+        .unwrap();
+    let res = run_vm(vm).unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    assert_eq!(res[0].result.as_ref().map(as_u64), Ok(Some(1)));
+}
+
+// Check that global constructors run in priority order before the entry function.
+#[test]
+fn global_ctors() {
+    let project = Project::from_path("tests/samples/ctors.bc").unwrap();
+    let vm = VM::new("get_value", &project)
+        .unwrap()
+        .with_global_ctors()
+        .unwrap();
+
+    let res = run_vm(vm).unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    assert_eq!(res[0].result.as_ref().map(as_u64), Ok(Some(12)));
+}
+
+// Check that global constructors are not run unless requested.
+#[test]
+fn global_ctors_not_run() {
+    let res = run("tests/samples/ctors.bc", "get_value").unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    assert_eq!(res[0].result.as_ref().map(as_u64), Ok(Some(0)));
+}
+
+// Check that `main` can be called with a mix of concrete and symbolic arguments.
+//
+// The first character of the symbolic argument decides the branch, so both should be reachable.
+#[test]
+fn argv() {
+    let project = Project::from_path("tests/samples/argv.bc").unwrap();
+    let vm = VM::new("main", &project)
+        .unwrap()
+        .with_argv(&[Argv::Concrete("argv".to_owned()), Argv::Symbolic(1)])
+        .unwrap();
+
+    let res = run_vm(vm).unwrap();
+    assert_eq!(res.len(), 2, "expected 2 paths");
+    let mut results: Vec<_> = res.iter().map(|r| r.result.as_ref().map(as_u64)).collect();
+    results.sort_by_key(|r| r.clone().ok().flatten());
+    assert_eq!(results, vec![Ok(Some(1)), Ok(Some(2))]);
+
+    // The symbolic argument is named after its index.
+    let mut vm = VM::new("main", &project)
+        .unwrap()
+        .with_argv(&[Argv::Concrete("argv".to_owned()), Argv::Symbolic(1)])
+        .unwrap();
+    assert!(vm.run().unwrap().is_ok());
+    let model = vm.solver.model_string().unwrap();
+    assert!(model.contains("argv_1"), "argv_1 not in model: {model}");
+
+    // Entry functions where `argv` is not a pointer are rejected.
+    let res = VM::new("not_main", &project)
+        .unwrap()
+        .with_argv(&[Argv::Symbolic(1)]);
+    assert!(matches!(res, Err(VMError::Other(_))));
+}
+
+// Check that `getenv` forks on whether a symbolic environment variable is set.
+#[test]
+fn getenv() {
+    let project = Project::from_path("tests/samples/getenv.bc").unwrap();
+    let mut vm = VM::new("is_set", &project).unwrap();
+    vm.set_symbolic_env_var("X", 4);
+
+    let res = run_vm(vm).unwrap();
+    assert_eq!(res.len(), 2, "expected 2 paths");
+    let mut results: Vec<_> = res.iter().map(|r| r.result.as_ref().map(as_u64)).collect();
+    results.sort_by_key(|r| r.clone().ok().flatten());
+    assert_eq!(results, vec![Ok(Some(0)), Ok(Some(1))]);
+
+    // The value of the global is reported with the symbols of each path.
+    for path in &res {
+        assert_eq!(path.symbolics.len(), 1, "expected the global as a symbol");
+        let config = path.symbolics[0].as_u64() as i32;
+        match as_u64(path.result.as_ref().unwrap()) {
+            Some(1) => assert!(config > 10, "config {config} should be above 10"),
+            _ => assert!(config <= 10, "config {config} should be at most 10"),
+        }
+    }
+}
+
+// Check that environment variables not marked as symbolic are unset.
+#[test]
+fn getenv_unset() {
+    let res = run("tests/samples/getenv.bc", "is_set").unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    assert_eq!(res[0].result.as_ref().map(as_u64), Ok(Some(0)));
+}
+
+// Check that shifts by a symbolic amount flag the path where the amount exceeds the bit width.
+#[test]
+fn shift_exceeds_width() {
+    for function in ["shl", "lshr", "ashr"] {
+        let res = run("tests/samples/shift.bc", function).unwrap();
+        assert_eq!(res.len(), 2, "expected 2 paths for {function}");
+        assert!(res[0].result.is_ok());
+        assert!(res[0].inputs[1].as_u64() < 32);
+
+        assert_eq!(res[1].result, Err(VMError::InvalidShift));
+        assert!(res[1].inputs[1].as_u64() >= 32);
+    }
+}
+
+// Check that arithmetic treated as `nsw` or `nuw` flags the path where it overflows.
+#[test]
+fn no_wrap_overflow() {
+    let project = Project::from_path("tests/samples/wrap.bc").unwrap();
+    let inputs = |res: &[PathResult], i: usize| {
+        let inputs = &res[i].inputs;
+        (inputs[0].as_u64(), inputs[1].as_u64())
+    };
+
+    let vm = VM::new("add", &project).unwrap().with_nsw_checks(true);
+    let res = run_vm(vm).unwrap();
+    assert_eq!(res.len(), 2, "expected 2 paths");
+    let (a, b) = inputs(&res, 0);
+    let sum = (a as i32)
+        .checked_add(b as i32)
+        .expect("expected no overflow");
+    assert_eq!(
+        res[0].result.as_ref().map(as_u64),
+        Ok(Some(sum as u32 as u64))
+    );
+
+    assert_eq!(res[1].result, Err(VMError::PoisonOverflow));
+    let (a, b) = inputs(&res, 1);
+    assert!((a as i32).checked_add(b as i32).is_none());
+
+    // Without the checks the addition wraps.
+    let res = run("tests/samples/wrap.bc", "add").unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+
+    let vm = VM::new("mul", &project).unwrap().with_nuw_checks(true);
+    let res = run_vm(vm).unwrap();
+    assert_eq!(res.len(), 2, "expected 2 paths");
+    let (a, b) = inputs(&res, 0);
+    assert!((a as u32).checked_mul(b as u32).is_some());
+
+    assert_eq!(res[1].result, Err(VMError::PoisonOverflow));
+    let (a, b) = inputs(&res, 1);
+    assert!((a as u32).checked_mul(b as u32).is_none());
+}
+
+// Check that division treated as `exact` flags the path where it has a remainder.
+#[test]
+fn exact_division() {
+    let project = Project::from_path("tests/samples/exact_div.bc").unwrap();
+    let vm = VM::new("half", &project).unwrap().with_exact_checks(true);
+    let res = run_vm(vm).unwrap();
+    assert_eq!(res.len(), 2, "expected 2 paths");
+    let x = res[0].inputs[0].as_u64();
+    assert_eq!(x % 2, 0);
+    assert_eq!(res[0].result.as_ref().map(as_u64), Ok(Some(x / 2)));
+
+    assert_eq!(res[1].result, Err(VMError::InexactDivision));
+    assert_eq!(res[1].inputs[0].as_u64() % 2, 1);
+
+    let vm = VM::new("third", &project).unwrap().with_exact_checks(true);
+    let res = run_vm(vm).unwrap();
+    assert_eq!(res.len(), 2, "expected 2 paths");
+    assert_eq!(res[0].inputs[0].as_u64() as i32 % 3, 0);
+
+    assert_eq!(res[1].result, Err(VMError::InexactDivision));
+    assert_ne!(res[1].inputs[0].as_u64() as i32 % 3, 0);
+
+    // Without the checks the division is truncated.
+    let res = run("tests/samples/exact_div.bc", "half").unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+}
+
+// Check that aggregates returned by value are converted into their fields.
+#[test]
+fn structs_pair_output() {
+    let res = run("tests/samples/structs.bc", "structs::pair").unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    let expected = ConcreteValue::Struct {
+        fields: vec![
+            ConcreteValue::Value {
+                value: 10,
+                bits: 32,
+            },
+            ConcreteValue::Value { value: 3, bits: 8 },
+        ],
+    };
+    assert_eq!(res[0].result, Ok(Some(expected)));
+}
+
+// Check that structs returned through an `sret` parameter are read back from memory.
+//
+// The field order is decided by the compiler, so only check the integer field values.
+#[test]
+fn structs_output_is_good() {
+    let res = run("tests/samples/structs.bc", "structs::foobar").unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    let fields = match &res[0].result {
+        Ok(Some(ConcreteValue::Struct { fields })) => fields,
+        result => panic!("expected struct, got {result:?}"),
+    };
+
+    let mut values: Vec<_> = fields
+        .iter()
+        .filter_map(|field| match field {
+            ConcreteValue::Value { value, .. } => Some(*value),
+            _ => None,
+        })
+        .collect();
+    values.sort_unstable();
+    assert_eq!(values, vec![0, 0, 10, 15]);
+}
+
+// Check that structs passed `byval` are copied, so the callee sees the fields but its writes are
+// not visible to the caller.
+#[test]
+fn structs_byval_copy() {
+    let res = run("tests/samples/byval.bc", "pass_by_value").unwrap();
+    assert_eq!(res.len(), 1, "expected 1 path");
+    assert_eq!(res[0].result.as_ref().map(as_u64), Ok(Some(15)));
+}
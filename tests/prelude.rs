@@ -0,0 +1,23 @@
+use x0001e::prelude::*;
+
+// Check that the prelude is enough to run a project and inspect the result.
+#[test]
+fn simple_with_prelude() {
+    let project = Project::from_path("tests/samples/simple.bc").unwrap();
+    let mut vm = VM::new("main", &project).unwrap();
+
+    let value = match vm.run() {
+        Some(Ok(ReturnValue::Value(value))) => value,
+        result => panic!("expected a return value, got {result:?}"),
+    };
+    assert_eq!(vm.run().map(|result| result.err()), None, "expected 1 path");
+
+    let value: BV = value;
+    match vm.solver.get_solutions_for_bv(&value, 1).unwrap() {
+        Solutions::Exactly(solutions) => assert_eq!(solutions[0].as_u64(), Some(0)),
+        solutions => panic!("expected 1 solution, got {solutions:?}"),
+    }
+
+    let error: VMError = MemoryError::SymbolicString.into();
+    assert_eq!(error.kind(), "MemoryError");
+}
@@ -0,0 +1,22 @@
+int target(int x) {
+    return x + 1;
+}
+
+int aliased(int x) __attribute__((alias("target")));
+int weak_aliased(int x) __attribute__((weak, alias("target")));
+
+int counter = 5;
+extern int counter_alias __attribute__((alias("counter")));
+
+int call_alias(int x) {
+    return aliased(x) + weak_aliased(x);
+}
+
+int read_alias() {
+    counter_alias += 1;
+    return counter + counter_alias;
+}
+
+int main() {
+    return call_alias(1) + read_alias();
+}
@@ -0,0 +1,20 @@
+typedef int v4i32 __attribute__((vector_size(16)));
+
+int load_vector(int offset) {
+    char buf[32] __attribute__((aligned(16))) = {0};
+    v4i32 *p = (v4i32 *)(buf + offset);
+    v4i32 v = *p;
+    return v[0];
+}
+
+int aligned() {
+    return load_vector(0);
+}
+
+int misaligned() {
+    return load_vector(4);
+}
+
+int main() {
+    return aligned();
+}
@@ -0,0 +1,16 @@
+int main(int argc, char **argv) {
+    if (argc < 2) {
+        return -1;
+    }
+
+    if (argv[1][0] == 'a') {
+        return 1;
+    } else {
+        return 2;
+    }
+}
+
+// Takes an integer instead of a pointer as `argv`.
+int not_main(int argc, int argv) {
+    return argc + argv;
+}
@@ -10,6 +10,20 @@ fn get_unchecked(idx: usize) -> i32 {
     unsafe { *arr.get_unchecked(idx) }
 }
 
+fn get_sum(a: usize, b: usize) -> i32 {
+    let arr = [0, 1, 2, 3];
+    arr[a] + arr[b]
+}
+
+fn sum_shifted(shift: usize) -> i32 {
+    let arr = [0, 1, 2, 3];
+    let mut sum = 0;
+    for i in 0..4 {
+        sum += arr[i + (shift & 7)];
+    }
+    sum
+}
+
 fn indexing_works() -> i32 {
     get(3)
 }
@@ -17,5 +31,7 @@ fn indexing_works() -> i32 {
 fn main() {
     get(1);
     get_unchecked(1);
+    get_sum(1, 2);
+    sum_shifted(0);
     indexing_works();
 }
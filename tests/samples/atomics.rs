@@ -0,0 +1,39 @@
+#![allow(dead_code)]
+
+use std::sync::atomic::{compiler_fence, fence, AtomicBool, AtomicU32, Ordering};
+
+fn fetch_add(x: u32) -> u32 {
+    let counter = AtomicU32::new(10);
+    let old = counter.fetch_add(x, Ordering::SeqCst);
+    old.wrapping_add(counter.load(Ordering::SeqCst))
+}
+
+fn compare_exchange(x: u32) -> u32 {
+    let value = AtomicU32::new(5);
+    match value.compare_exchange(x, 7, Ordering::SeqCst, Ordering::SeqCst) {
+        Ok(old) => old + value.load(Ordering::SeqCst),
+        Err(old) => old * 100 + value.load(Ordering::SeqCst),
+    }
+}
+
+fn fences(x: u32) -> u32 {
+    let ready = AtomicBool::new(false);
+    let value = AtomicU32::new(0);
+
+    value.store(x, Ordering::Relaxed);
+    fence(Ordering::SeqCst);
+    ready.store(true, Ordering::Release);
+    compiler_fence(Ordering::SeqCst);
+
+    if ready.load(Ordering::Acquire) {
+        value.load(Ordering::SeqCst)
+    } else {
+        0
+    }
+}
+
+fn main() {
+    fetch_add(1);
+    compare_exchange(5);
+    fences(1);
+}
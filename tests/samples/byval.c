@@ -0,0 +1,18 @@
+struct triple {
+    long a;
+    long b;
+    long c;
+};
+
+long weighted(struct triple t) {
+    long result = t.a + 2 * t.b + 3 * t.c;
+    // Only changes the copy of the callee.
+    t.a = 100;
+    return result;
+}
+
+long pass_by_value() {
+    struct triple t = { 1, 2, 3 };
+    long result = weighted(t);
+    return result + t.a;
+}
@@ -0,0 +1,8 @@
+int clz(unsigned int x) {
+    // Zero is undefined for `__builtin_clz`.
+    return __builtin_clz(x);
+}
+
+int main(void) {
+    return clz(1);
+}
@@ -0,0 +1,27 @@
+// Only uses concrete values, so all operations can be evaluated natively.
+int collatz_steps(unsigned int n) {
+    int steps = 0;
+    while (n != 1) {
+        if (n % 2 == 0) {
+            n = n / 2;
+        } else {
+            n = 3 * n + 1;
+        }
+        steps++;
+    }
+    return steps;
+}
+
+int mix(int seed) {
+    int value = seed;
+    for (int i = 0; i < 16; i++) {
+        value = (value << 3) ^ (value >> 2);
+        value = value / 7 - (value % 5);
+        value |= i & 3;
+    }
+    return value;
+}
+
+int main() {
+    return collatz_steps(27) + mix(-12345);
+}
@@ -0,0 +1,26 @@
+int table[4] = {10, 20, 30, 40};
+
+// Global initializers with constant expressions.
+int *third = &table[2];
+char *bytes = (char *)&table[1];
+int *fixed = (int *)0x1000;
+
+int *element_address(void) {
+    return &table[2];
+}
+
+long element_integer(void) {
+    return (long)&table[1];
+}
+
+int read_third(void) {
+    return *third;
+}
+
+int read_byte(void) {
+    return bytes[0];
+}
+
+long fixed_address(void) {
+    return (long)fixed;
+}
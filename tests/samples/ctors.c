@@ -0,0 +1,17 @@
+static int value = 0;
+
+__attribute__((constructor(102))) static void init_second(void) {
+    value = value * 10 + 2;
+}
+
+__attribute__((constructor(101))) static void init_first(void) {
+    value = value * 10 + 1;
+}
+
+int get_value(void) {
+    return value;
+}
+
+int main(void) {
+    return get_value();
+}
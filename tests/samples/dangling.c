@@ -0,0 +1,13 @@
+int *get_local(int value) {
+    int local = value;
+    return &local;
+}
+
+int dangling(int value) {
+    int *ptr = get_local(value);
+    return *ptr;
+}
+
+int main() {
+    return dangling(1);
+}
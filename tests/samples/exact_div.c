@@ -0,0 +1,12 @@
+// Divisions that are expected to be exact, as with `exact_div` in Rust.
+unsigned int half(unsigned int x) {
+    return x / 2;
+}
+
+int third(int x) {
+    return x / 3;
+}
+
+int main() {
+    return half(4) + third(9);
+}
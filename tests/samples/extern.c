@@ -0,0 +1,9 @@
+int external(int x);
+
+int call_external(int x) {
+    return external(x);
+}
+
+int main() {
+    return call_external(1);
+}
@@ -0,0 +1,13 @@
+extern int config;
+
+int check_config() {
+    if (config > 10) {
+        return 1;
+    } else {
+        return 0;
+    }
+}
+
+int main() {
+    return check_config();
+}
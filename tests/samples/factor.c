@@ -0,0 +1,14 @@
+#include <stdint.h>
+
+// Finding inputs for the true branch requires factoring a product of two 31-bit primes.
+int is_factor(uint64_t x, uint64_t y) {
+    if (x > 1 && y > 1 && x <= 0xffffffff && y <= 0xffffffff &&
+        x * y == 4611685975477714963ULL) {
+        return 1;
+    }
+    return 0;
+}
+
+int main() {
+    return is_factor(2147483647, 2147483629);
+}
@@ -0,0 +1,14 @@
+int get(int i) {
+    int arr[4] = {0, 1, 2, 3};
+    int *p = &arr[2];
+
+    if (i > 1) {
+        return -1;
+    }
+
+    return p[i];
+}
+
+int main() {
+    return get(-2);
+}
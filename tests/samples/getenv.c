@@ -0,0 +1,13 @@
+#include <stdlib.h>
+
+int is_set() {
+    if (getenv("X") == 0) {
+        return 0;
+    } else {
+        return 1;
+    }
+}
+
+int main() {
+    return is_set();
+}
@@ -0,0 +1,38 @@
+#![allow(dead_code)]
+
+struct Point {
+    x: u32,
+    y: u32,
+}
+
+static POINTS: [Point; 3] = [
+    Point { x: 1, y: 2 },
+    Point { x: 3, y: 4 },
+    Point { x: 5, y: 6 },
+];
+
+static mut COUNTER: u32 = 0;
+
+fn read_static() -> u32 {
+    POINTS[2].y
+}
+
+fn increment() -> u32 {
+    unsafe {
+        COUNTER += 1;
+        COUNTER += 1;
+        COUNTER
+    }
+}
+
+static mut ENABLED: bool = false;
+
+fn feature() -> u32 {
+    if unsafe { ENABLED } {
+        1
+    } else {
+        2
+    }
+}
+
+fn main() {}
@@ -0,0 +1,16 @@
+int past_end() {
+    int arr[4] = {0, 1, 2, 3};
+    int *end = arr + 4;
+    int *p = arr + 5;
+    return (int)(p - end);
+}
+
+int offset(int i) {
+    int arr[4] = {0, 1, 2, 3};
+    int *p = arr + i;
+    return (int)(p - arr);
+}
+
+int main() {
+    return past_end() + offset(2);
+}
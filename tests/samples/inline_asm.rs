@@ -0,0 +1,12 @@
+#![allow(dead_code)]
+
+use std::arch::asm;
+
+fn nop(x: u32) -> u32 {
+    unsafe { asm!("nop") };
+    x
+}
+
+fn main() {
+    nop(1);
+}
@@ -0,0 +1,47 @@
+struct Padded {
+    short a;
+    int b;
+};
+
+struct Mixed {
+    char c;
+    double d;
+    char e;
+};
+
+struct Nested {
+    char c;
+    struct Padded p;
+};
+
+struct __attribute__((packed)) Packed {
+    char c;
+    int i;
+};
+
+struct Padded padded = {0};
+struct Mixed mixed = {0};
+struct Nested nested = {0};
+struct Packed packed = {0};
+struct Mixed mixed_array[3] = {0};
+_Bool flag = 0;
+
+struct Padded padded_values[2] = {{1, 2}, {3, 4}};
+struct Mixed mixed_value = {5, 6.0, 7};
+
+int read_padded() {
+    return padded_values[1].b;
+}
+
+int read_mixed() {
+    return mixed_value.e;
+}
+
+int copy_mixed() {
+    struct Mixed copy = mixed_value;
+    return copy.c + copy.e;
+}
+
+int main() {
+    return 0;
+}
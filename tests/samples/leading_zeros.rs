@@ -0,0 +1,9 @@
+#![allow(dead_code)]
+
+fn count(x: u32) -> u32 {
+    x.leading_zeros()
+}
+
+fn main() {
+    count(1);
+}
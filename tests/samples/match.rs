@@ -14,7 +14,18 @@ impl Shape {
     }
 }
 
+fn classify(x: u8) -> i32 {
+    match x {
+        0 => 1,
+        1 => 2,
+        10 => 3,
+        20 | 21 => 4,
+        _ => 5,
+    }
+}
+
 fn main() {
     let s = Shape::Square(5);
     let _z = s.area();
+    let _c = classify(10);
 }
@@ -0,0 +1,23 @@
+#![allow(dead_code)]
+
+use std::ptr::NonNull;
+
+fn is_some_ref(value: Option<&i32>) -> i32 {
+    match value {
+        Some(_) => 1,
+        None => 0,
+    }
+}
+
+fn is_some_non_null(value: Option<NonNull<i32>>) -> i32 {
+    match value {
+        Some(_) => 1,
+        None => 0,
+    }
+}
+
+fn main() {
+    let x = 5;
+    is_some_ref(Some(&x));
+    is_some_non_null(NonNull::new(&x as *const i32 as *mut i32));
+}
@@ -0,0 +1,12 @@
+int deref(int *p) {
+    return *p;
+}
+
+__attribute__((nonnull)) int deref_nonnull(int *p) {
+    return *p;
+}
+
+int main() {
+    int x = 1;
+    return deref(&x) + deref_nonnull(&x);
+}
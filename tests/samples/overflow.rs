@@ -0,0 +1,9 @@
+#![allow(dead_code)]
+
+fn add(a: u8, b: u8) -> u8 {
+    a + b
+}
+
+fn main() {
+    add(1, 2);
+}
@@ -0,0 +1,12 @@
+#![allow(dead_code)]
+
+fn check(x: u32) -> u32 {
+    if x == 7 {
+        panic!("unexpected value {x}");
+    }
+    x
+}
+
+fn main() {
+    check(1);
+}
@@ -0,0 +1,18 @@
+#include <stdio.h>
+
+int print_value() {
+    int x = -42;
+    printf("value: %d, hex: %x, %s\n", x, 255, "ok");
+    puts("done");
+    return 0;
+}
+
+int print_symbolic(int x) {
+    printf("%d\n", x);
+    return 0;
+}
+
+int main() {
+    print_value();
+    return print_symbolic(1);
+}
@@ -0,0 +1,13 @@
+#![allow(dead_code)]
+
+fn round_trip(x: u32) -> u32 {
+    let value = x.wrapping_add(1);
+    let addr = &value as *const u32 as usize;
+
+    let ptr = addr as *const u32;
+    unsafe { *ptr }
+}
+
+fn main() {
+    round_trip(1);
+}
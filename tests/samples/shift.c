@@ -0,0 +1,15 @@
+int shl(int x, int n) {
+    return x << n;
+}
+
+unsigned int lshr(unsigned int x, unsigned int n) {
+    return x >> n;
+}
+
+int ashr(int x, int n) {
+    return x >> n;
+}
+
+int main() {
+    return shl(1, 2) + lshr(8, 1) + ashr(-8, 1);
+}
@@ -0,0 +1,37 @@
+// Variable length arrays declared in a loop are allocated with `alloca` on each iteration, and
+// freed at the end of the iteration with `llvm.stackrestore`.
+
+int read_in_loop(unsigned n) {
+    if (n < 1 || n > 8) {
+        return -1;
+    }
+
+    int total = 0;
+    for (int i = 0; i < 3; i++) {
+        char buf[n];
+        buf[0] = 1;
+        total += buf[0];
+    }
+
+    return total;
+}
+
+int use_after_restore(unsigned n) {
+    if (n < 1 || n > 8) {
+        return -1;
+    }
+
+    char *previous = 0;
+    for (int i = 0; i < 2; i++) {
+        char buf[n];
+        buf[0] = i;
+
+        // Points to the array of the previous iteration, which has been freed.
+        if (previous) {
+            return *previous;
+        }
+        previous = buf;
+    }
+
+    return 0;
+}
@@ -0,0 +1,10 @@
+int pick(int x) {
+    if (x > 10) {
+        return x - 10;
+    }
+    return x + 1;
+}
+
+int main() {
+    return pick(5);
+}
@@ -36,4 +36,8 @@ fn foobar() -> MyStruct {
     s
 }
 
+fn pair() -> (u32, u8) {
+    (10, 3)
+}
+
 fn main() {}
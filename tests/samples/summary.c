@@ -0,0 +1,11 @@
+int affine(int x, int y) {
+    return 3 * x + y - 7;
+}
+
+int abs_value(int x) {
+    return x < 0 ? -x : x;
+}
+
+int main() {
+    return affine(2, 5) + abs_value(-1);
+}
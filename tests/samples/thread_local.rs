@@ -0,0 +1,23 @@
+#![allow(dead_code)]
+
+use std::cell::Cell;
+
+thread_local! {
+    static VALUE: Cell<u32> = const { Cell::new(42) };
+}
+
+fn read() -> u32 {
+    VALUE.with(|value| value.get())
+}
+
+fn add(x: u32) -> u32 {
+    VALUE.with(|value| {
+        value.set(value.get().wrapping_add(x));
+        value.get()
+    })
+}
+
+fn main() {
+    read();
+    add(1);
+}
@@ -0,0 +1,23 @@
+#![allow(dead_code)]
+
+use std::panic::catch_unwind;
+
+fn get(idx: usize) -> i32 {
+    let arr = [0, 1, 2, 3];
+    arr[idx]
+}
+
+fn catch(idx: usize) -> i32 {
+    match catch_unwind(|| get(idx)) {
+        Ok(value) => value,
+        Err(payload) => {
+            // The payload is not available in the VM, so it cannot be dropped.
+            std::mem::forget(payload);
+            -1
+        }
+    }
+}
+
+fn main() {
+    catch(1);
+}
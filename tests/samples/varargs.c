@@ -0,0 +1,20 @@
+#include <stdarg.h>
+
+int difference(int count, ...) {
+    va_list args;
+    va_start(args, count);
+
+    int first = va_arg(args, int);
+    int second = va_arg(args, int);
+
+    va_end(args);
+    return first - second;
+}
+
+int call_variadic(int a, int b) {
+    return difference(2, a, b);
+}
+
+int main() {
+    return call_variadic(3, 1);
+}
@@ -0,0 +1,10 @@
+typedef unsigned char bytes16 __attribute__((vector_size(16)));
+
+int ignore_bytes(bytes16 bytes) {
+    return 0;
+}
+
+int main() {
+    bytes16 bytes = {0};
+    return ignore_bytes(bytes);
+}
@@ -0,0 +1,35 @@
+// Variable length arrays are allocated with `alloca` where the size is symbolic.
+
+int read_past_end(unsigned n) {
+    if (n < 4 || n > 16) {
+        return -1;
+    }
+
+    char buf[n];
+    buf[n - 1] = 1;
+
+    // The last two bytes read are past the end of `buf`.
+    return *(int *)&buf[n - 2];
+}
+
+int read_at_end(unsigned n) {
+    if (n < 4 || n > 16) {
+        return -1;
+    }
+
+    char buf[n];
+    buf[n - 1] = 1;
+
+    return *(int *)&buf[n - 4];
+}
+
+int unbounded(unsigned long n) {
+    char buf[n];
+    buf[0] = 1;
+
+    return buf[0];
+}
+
+int main() {
+    return read_at_end(8);
+}
@@ -0,0 +1,13 @@
+// Signed overflow is undefined, so the addition is `add nsw`.
+int add(int a, int b) {
+    return a + b;
+}
+
+// Unsigned multiplication wraps, so the multiplication is a plain `mul`.
+unsigned int mul(unsigned int a, unsigned int b) {
+    return a * b;
+}
+
+int main() {
+    return add(1, 2) + mul(3, 4);
+}
@@ -58,10 +58,13 @@ impl ConcreteValue {
             } => {
                 let el_size = project.bit_size(&element_type).unwrap() as usize;
 
+                // The binary string starts with the most significant bit, while the first element
+                // is stored in the least significant bits.
                 let mut elements = Vec::new();
                 for i in 0..*num_elements {
-                    let start = i * el_size;
-                    let end = (i + 1) * el_size;
+                    let (offset, _) = project.bit_offset_concrete(ty, i as u64).unwrap();
+                    let end = binary_str.len() - offset as usize;
+                    let start = end - el_size;
                     let s = &binary_str[start..end];
                     let element = Self::from_binary_str(s, element_type, project);
                     elements.push(element);
@@ -71,15 +74,17 @@ impl ConcreteValue {
             }
 
             StructType { element_types, .. } => {
+                // As for arrays, the first field is stored in the least significant bits, and the
+                // fields are placed by the data layout.
                 let mut fields = Vec::new();
-                let mut current_offset = 0;
-                for el_ty in element_types {
-                    let size = project.bit_size(&el_ty).unwrap() as usize;
-                    let end = current_offset + size;
-                    let s = &binary_str[current_offset..end];
+                for (i, el_ty) in element_types.iter().enumerate() {
+                    let (offset, _) = project.bit_offset_concrete(ty, i as u64).unwrap();
+                    let size = project.bit_size(el_ty).unwrap() as usize;
+                    let end = binary_str.len() - offset as usize;
+                    let start = end - size;
+                    let s = &binary_str[start..end];
 
                     fields.push(Self::from_binary_str(s, el_ty, project));
-                    current_offset = end;
                 }
 
                 Self::Struct { fields }
@@ -144,10 +149,16 @@ pub struct PathResult {
 }
 
 pub fn run(path: impl AsRef<Path>, function: &str) -> Result<Vec<PathResult>> {
+    let project = Project::from_path(path)?;
+    let vm = VM::new(function, &project)?;
+    run_vm(vm)
+}
+
+/// Run all paths of an already set up VM.
+pub fn run_vm(mut vm: VM<'_>) -> Result<Vec<PathResult>> {
     let _ = env_logger::builder().is_test(true).try_init();
 
-    let project = Project::from_path(path)?;
-    let mut vm = VM::new(function, &project)?;
+    let project = vm.project;
     let mut results = Vec::new();
 
     // Go through all paths.
@@ -157,8 +168,8 @@ pub fn run(path: impl AsRef<Path>, function: &str) -> Result<Vec<PathResult>> {
         // Solutions cannot be cached between paths, so instantiate a new one for each path.
         let mut cache = SolutionGenerator::new(vm.solver.clone())?;
 
-        let inputs = generate_solutions(vm.parameters.iter(), &mut cache, &project)?;
-        let symbolics = generate_solutions(vm.state.symbols.iter(), &mut cache, &project)?;
+        let inputs = generate_solutions(vm.parameters.iter(), &mut cache, project)?;
+        let symbolics = generate_solutions(vm.state.symbols.iter(), &mut cache, project)?;
 
         let result = match path_result {
             Ok(return_value) => {
@@ -180,14 +191,26 @@ pub fn run(path: impl AsRef<Path>, function: &str) -> Result<Vec<PathResult>> {
                             let value = ConcreteValue::from_binary_str(
                                 value.as_01x_str(),
                                 ty.as_ref(),
-                                &project,
+                                project,
                             );
                             Some(value)
                         } else {
                             None
                         }
                     }
-                    ReturnValue::Void => None,
+                    // Aggregates may instead be returned through an `sret` parameter.
+                    ReturnValue::Void => match vm.sret_value()? {
+                        Some((value, ty)) => {
+                            let value = cache.get_solution(&value)?;
+                            let value = ConcreteValue::from_binary_str(
+                                value.as_01x_str(),
+                                ty.as_ref(),
+                                project,
+                            );
+                            Some(value)
+                        }
+                        None => None,
+                    },
                 };
 
                 Ok(return_value)
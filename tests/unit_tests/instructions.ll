@@ -12,8 +12,7 @@ target triple = "x86_64-pc-linux-gnu"
 ; --------------------------------------------------------------------------------------------------
 ; Binary Operations
 ;
-; add, sub, mul, udiv, sdiv, urem, srem
-; Unsupported: fadd, fsub, fmul, fdiv, frem
+; add, sub, mul, udiv, sdiv, urem, srem, fadd, fsub, fmul, fdiv, frem
 ; --------------------------------------------------------------------------------------------------
 
 ; Test add
@@ -56,12 +55,24 @@ define dso_local i64 @test_udiv() #0 {
     ret i64 %1 ; expect 20
 }
 
+; Test udiv treats the operands as unsigned
+define dso_local i64 @test_udiv_unsigned() #0 {
+    %1 = udiv i64 -1, 2
+    ret i64 %1 ; expect 0x7fffffffffffffff
+}
+
 ; Test sdiv
 define dso_local i64 @test_sdiv() #0 {
     %1 = sdiv i64 200, -10
     ret i64 %1 ; expect -20
 }
 
+; Test sdiv rounds towards zero
+define dso_local i64 @test_sdiv_round() #0 {
+    %1 = sdiv i64 -7, 2
+    ret i64 %1 ; expect -3
+}
+
 ; Test urem
 define dso_local i64 @test_urem() #0 {
     %1 = urem i64 15, 4
@@ -80,6 +91,47 @@ define dso_local i64 @test_srem2() #0 {
     ret i64 %1 ; expect -3
 }
 
+; Test fadd, the same as the constrained intrinsic
+define dso_local i64 @test_fadd() #0 {
+    %1 = fadd double 1.5, 2.25
+    %2 = bitcast double %1 to i64
+    ret i64 %2 ; expect 3.75 (0x400e000000000000)
+}
+
+; Test fadd with a symbolic operand
+define dso_local double @test_fadd_symbolic(double %x) #0 {
+    %1 = fadd double %x, 1.0
+    ret double %1 ; expect UnsupportedInstruction
+}
+
+; Test fsub
+define dso_local i64 @test_fsub() #0 {
+    %1 = fsub double 1.0, 4.0
+    %2 = bitcast double %1 to i64
+    ret i64 %2 ; expect -3.0 (0xc008000000000000)
+}
+
+; Test fmul on floats
+define dso_local i32 @test_fmul_f32() #0 {
+    %1 = fmul float 1.5, -4.0
+    %2 = bitcast float %1 to i32
+    ret i32 %2 ; expect -6.0 (0xc0c00000)
+}
+
+; Test fdiv
+define dso_local i64 @test_fdiv() #0 {
+    %1 = fdiv double 1.0, 8.0
+    %2 = bitcast double %1 to i64
+    ret i64 %2 ; expect 0.125 (0x3fc0000000000000)
+}
+
+; Test frem
+define dso_local i64 @test_frem() #0 {
+    %1 = frem double 7.5, 2.0
+    %2 = bitcast double %1 to i64
+    ret i64 %2 ; expect 1.5 (0x3ff8000000000000)
+}
+
 ; --------------------------------------------------------------------------------------------------
 ; Bitwise Binary Operations
 ;
@@ -175,6 +227,15 @@ define dso_local i32 @test_extract_value_struct4() #0 {
     ret i32 %1 ; expect 2
 }
 
+define dso_local i32 @test_extract_value_nested() #0 {
+    ; ((1, 2), 3)
+    %1 = insertvalue { { i32, i32 }, i32 } { { i32, i32 } { i32 0, i32 0 }, i32 0 }, i32 1, 0, 0
+    %2 = insertvalue { { i32, i32 }, i32 } %1, i32 2, 0, 1
+    %3 = insertvalue { { i32, i32 }, i32 } %2, i32 3, 1
+    %4 = extractvalue { { i32, i32 }, i32 } %3, 0, 1
+    ret i32 %4 ; expect 2
+}
+
 ; insert value
 
 define dso_local [4 x i8] @test_insert_value_arr1() #0 {
@@ -242,6 +303,14 @@ define dso_local { i16, i16, [2 x i16] } @test_insert_value_struct4() #0 {
     ;   0x000f0001000a0004 (expected)
 }
 
+define dso_local { { i16, i16 }, i16 } @test_insert_value_nested() #0 {
+    %1 = insertvalue { { i16, i16 }, i16 } { { i16, i16 } { i16 1, i16 2 }, i16 3 }, i16 9, 0, 1
+    ret { { i16, i16 }, i16 } %1
+    ; { { 0x01, 0x09 }, 0x03 } as i64 gives
+    ;   0x0001 | 0x0009 << 16 | 0x0003 << 32 =
+    ;   0x000300090001 (expected)
+}
+
 
 ; --------------------------------------------------------------------------------------------------
 ; Memory access and Addressing Operations
@@ -349,7 +418,7 @@ define dso_local i32 @test_gep_arr2() #0 {
 ; Conversion Operations
 ;
 ; trunc, zext, sext, ptrtoint, inttoptr, bitcast, addrspacecast
-; Unsupported: fptrunc, fpext, fptoui, fptosi, uitpfp, sitofp
+; Only concrete values: fptrunc, fpext, fptoui, fptosi, uitofp, sitofp
 ; --------------------------------------------------------------------------------------------------
 
 define dso_local i8 @test_trunc() #0 {
@@ -372,6 +441,49 @@ define dso_local i16 @test_sext() #0 {
     ret i16 %1 ; expect: 0xFFFF
 }
 
+; i64 as i8
+define dso_local i8 @test_trunc_i64_i8() #0 {
+    %1 = trunc i64 u0x1234567890ABCDEF to i8
+    ret i8 %1 ; expect: 0xEF
+}
+
+define dso_local <2 x i8> @test_trunc_vec() #0 {
+    %1 = trunc <2 x i16> <i16 u0x1101, i16 u0x2202> to <2 x i8>
+    ret <2 x i8> %1 ; expect: 0x0201
+}
+
+; u8 as i32
+define dso_local i32 @test_zext_i8_i32() #0 {
+    %1 = zext i8 u0x80 to i32
+    ret i32 %1 ; expect: 0x00000080
+}
+
+define dso_local i8 @test_zext_i1() #0 {
+    %1 = zext i1 true to i8
+    ret i8 %1 ; expect: 0x01
+}
+
+; i8 as i32
+define dso_local i32 @test_sext_i8_i32() #0 {
+    %1 = sext i8 u0x80 to i32
+    ret i32 %1 ; expect: 0xFFFFFF80
+}
+
+define dso_local i32 @test_sext_i8_i32_positive() #0 {
+    %1 = sext i8 u0x7F to i32
+    ret i32 %1 ; expect: 0x0000007F
+}
+
+define dso_local i64 @test_sext_i1() #0 {
+    %1 = sext i1 true to i64
+    ret i64 %1 ; expect: 0xFFFFFFFFFFFFFFFF
+}
+
+define dso_local <2 x i16> @test_sext_vec() #0 {
+    %1 = sext <2 x i8> <i8 u0xFF, i8 u0x01> to <2 x i16>
+    ret <2 x i16> %1 ; expect: 0x0001FFFF
+}
+
 ; on the current architecture the pointer size is 64 bits.
 
 ; inttoptr
@@ -414,6 +526,38 @@ define dso_local <2 x i32> @test_ptrtoint_vec_trunc() #0 {
     ret <2 x i32> %2 ; expect: 0x7777888833334444
 }
 
+; floating point
+
+define dso_local float @test_fptrunc() #0 {
+    %1 = fptrunc double 0.25 to float
+    ret float %1 ; expect: 0.25 (0x3e800000)
+}
+
+define dso_local double @test_fpext() #0 {
+    %1 = fpext float 1.5 to double
+    ret double %1 ; expect: 1.5 (0x3ff8000000000000)
+}
+
+define dso_local i8 @test_fptoui() #0 {
+    %1 = fptoui double 200.75 to i8
+    ret i8 %1 ; expect: 200
+}
+
+define dso_local i64 @test_fptosi() #0 {
+    %1 = fptosi float -3.75 to i64
+    ret i64 %1 ; expect: -3
+}
+
+define dso_local double @test_uitofp() #0 {
+    %1 = uitofp i8 u0xFF to double
+    ret double %1 ; expect: 255.0 (0x406fe00000000000)
+}
+
+define dso_local double @test_sitofp() #0 {
+    %1 = sitofp i8 u0xFF to double
+    ret double %1 ; expect: -1.0 (0xbff0000000000000)
+}
+
 define dso_local i32 @test_bitcast1() #0 {
     ; <0x1234, 0x5678> as i32
     %1 = bitcast <2 x i16> <i16 4660, i16 22136> to i32
@@ -426,6 +570,18 @@ define dso_local i32 @test_bitcast2() #0 {
     ret i32 %1 ; expect: 0x78563412
 }
 
+define dso_local <2 x i32> @test_bitcast_int_to_vec() #0 {
+    %1 = bitcast i64 u0x1122334455667788 to <2 x i32>
+    ret <2 x i32> %1 ; expect: 0x1122334455667788
+}
+
+define dso_local i64 @test_bitcast_ptr() #0 {
+    %1 = inttoptr i64 u0x1111222233334444 to i32*
+    %2 = bitcast i32* %1 to i8*
+    %3 = ptrtoint i8* %2 to i64
+    ret i64 %3 ; expect: 0x1111222233334444
+}
+
 ; these are currently the same as bitcasts, since addressspaces aren't supported.
 define dso_local i32 addrspace(1)* @test_addrspacecast() #0 {
     %1 = inttoptr i64 u0x1111222233334444 to i32*
@@ -550,6 +706,17 @@ define dso_local i1 @test_icmp_sle_true() #0 {
     ret i1 %1 ; expect: 0x1
 }
 
+; vector comparisons give one bit per element
+define dso_local <4 x i1> @test_icmp_ult_vec() #0 {
+    %1 = icmp ult <4 x i32> <i32 1, i32 5, i32 2, i32 7>, <i32 4, i32 4, i32 4, i32 4>
+    ret <4 x i1> %1 ; expect: 0b0101
+}
+
+define dso_local <2 x i1> @test_icmp_eq_vec_i64() #0 {
+    %1 = icmp eq <2 x i64> <i64 3, i64 u0x1000000000000003>, <i64 3, i64 3>
+    ret <2 x i1> %1 ; expect: 0b01
+}
+
 ; eq vec
 define dso_local <3 x i1> @test_icmp_eq_vec() #0 {
     %1 = icmp eq <3 x i32> <i32 1, i32 2, i32 3>, <i32 1, i32 3, i32 3>
@@ -588,6 +755,36 @@ bb3:
     ret i32 %5 ; expect: 0xcd
 }
 
+define dso_local i32 @test_phi_symbolic(i1 %cond) #0 {
+    br i1 %cond, label %bb1, label %bb2
+bb1:
+    %1 = add i32 0, u0xab
+    br label %bb3
+bb2:
+    %2 = add i32 0, u0xcd
+    br label %bb3
+bb3:
+    %3 = phi i32 [%1, %bb1], [%2, %bb2]
+    ret i32 %3 ; expect: 0xab and 0xcd
+}
+
+; The phis swap `a` and `b` each iteration, they must use the values from the previous iteration.
+define dso_local i32 @test_phi_swap() #0 {
+entry:
+    br label %loop
+loop:
+    %a = phi i32 [1, %entry], [%b, %loop]
+    %b = phi i32 [2, %entry], [%a, %loop]
+    %i = phi i32 [0, %entry], [%i.next, %loop]
+    %i.next = add i32 %i, 1
+    %done = icmp eq i32 %i.next, 3
+    br i1 %done, label %exit, label %loop
+exit:
+    %1 = shl i32 %a, 4
+    %2 = or i32 %1, %b
+    ret i32 %2 ; expect: 0x12
+}
+
 ; select
 
 define dso_local i32 @test_select1() #0 {
@@ -629,6 +826,58 @@ define dso_local i32 @test_call() #0 {
     ret i32 %1 ; expect 0xabcd
 }
 
+; callbr
+
+define dso_local i32 @test_callbr_asm_goto() #0 {
+    callbr void asm sideeffect "", "X"(i8* blockaddress(@test_callbr_asm_goto, %jump))
+        to label %fallthrough [label %jump]
+fallthrough:
+    ret i32 1 ; expect 1
+jump:
+    ret i32 2
+}
+
+define dso_local i32 @test_callbr_asm_goto_output() #0 {
+    %1 = callbr i32 asm sideeffect "", "=r,X"(i8* blockaddress(@test_callbr_asm_goto_output, %jump))
+        to label %fallthrough [label %jump]
+fallthrough:
+    ret i32 %1 ; expect UnsupportedInstruction
+jump:
+    ret i32 2
+}
+
+; indirectbr
+
+define dso_local i32 @test_indirectbr(i32 %x) #0 {
+    %cond = icmp eq i32 %x, 0
+    %addr = select i1 %cond, i8* blockaddress(@test_indirectbr, %first), i8* blockaddress(@test_indirectbr, %second)
+    indirectbr i8* %addr, [label %first, label %second, label %first] ; expect UnsupportedInstruction
+first:
+    ret i32 1
+second:
+    ret i32 2
+}
+
+define dso_local i32 @test_indirectbr_single() #0 {
+    indirectbr i8* blockaddress(@test_indirectbr_single, %second), [label %second, label %second]
+first:
+    ret i32 1
+second:
+    ret i32 2 ; expect 2
+}
+
+@test_indirectbr_table.targets = internal constant [2 x i8*] [i8* blockaddress(@test_indirectbr_table, %first), i8* blockaddress(@test_indirectbr_table, %second)]
+
+define dso_local i32 @test_indirectbr_table() #0 {
+    %ptr = getelementptr inbounds [2 x i8*], [2 x i8*]* @test_indirectbr_table.targets, i64 0, i64 1
+    %addr = load i8*, i8** %ptr, align 8
+    indirectbr i8* %addr, [label %first, label %second] ; expect UnsupportedInstruction
+first:
+    ret i32 1
+second:
+    ret i32 2
+}
+
 ; --------------------------------------------------------------------------------------------------
 ; Constants
 ; --------------------------------------------------------------------------------------------------
@@ -653,6 +902,29 @@ define dso_local i32 @test_vector_constant2() #0 {
     ret i32 %val
 }
 
+; --------------------------------------------------------------------------------------------------
+; Taint
+; --------------------------------------------------------------------------------------------------
+
+define dso_local i32 @test_taint(i32 %x, i32 %y) #0 {
+    %1 = add i32 %x, 5
+    %2 = mul i32 %1, 3
+    %3 = shl i32 %2, 1
+    %4 = and i32 %y, 7
+    %5 = or i32 %3, %4
+    ret i32 %5
+}
+
+define dso_local i32 @test_taint_memory(i32 %x, i32 %y) #0 {
+    %1 = alloca [2 x i32], align 4
+    %2 = getelementptr inbounds [2 x i32], [2 x i32]* %1, i64 0, i64 0
+    %3 = getelementptr inbounds [2 x i32], [2 x i32]* %1, i64 0, i64 1
+    store i32 %x, i32* %2, align 4
+    store i32 %y, i32* %3, align 4
+    %4 = load i32, i32* %2, align 4
+    ret i32 %4
+}
+
 declare void @assume(i32) #1
 
 attributes #0 = { noinline nounwind optnone sspstrong uwtable "frame-pointer"="all" "min-legal-vector-width"="0" "no-trapping-math"="true" "stack-protector-buffer-size"="8" "target-cpu"="x86-64" "target-features"="+cx8,+fxsr,+mmx,+sse,+sse2,+x87" "tune-cpu"="generic" }
@@ -10,12 +10,34 @@ target triple = "x86_64-pc-linux-gnu"
 ; memcpy
 declare void @llvm.memcpy.p0i8.p0i8.i32(i8* %dst, i8* %src, i32 %len, i1 %isvolatile)
 
+; memcpy.inline
+declare void @llvm.memcpy.inline.p0i8.p0i8.i32(i8* %dst, i8* %src, i32 %len, i1 %isvolatile)
+
 ; memset
 declare void @llvm.memset.p0i8.i32(i8* %dest, i8 %val, i32 %len, i1 %isvolatile)
 
 ; umax
 declare i32 @llvm.umax.i32(i32, i32)
 declare <2 x i32> @llvm.umax.v2i32(<2 x i32>, <2 x i32>)
+declare <4 x i16> @llvm.umax.v4i16(<4 x i16>, <4 x i16>)
+
+; smax
+declare i32 @llvm.smax.i32(i32, i32)
+declare <4 x i16> @llvm.smax.v4i16(<4 x i16>, <4 x i16>)
+
+; powi
+declare double @llvm.powi.f64.i32(double, i32)
+declare float @llvm.powi.f32.i32(float, i32)
+
+; lround, llround, lrint, llrint
+declare i64 @llvm.lround.i64.f64(double)
+declare i64 @llvm.llround.i64.f64(double)
+declare i64 @llvm.lrint.i64.f64(double)
+declare i64 @llvm.llrint.i64.f32(float)
+
+; fptosi.sat, fptoui.sat
+declare i32 @llvm.fptosi.sat.i32.f32(float)
+declare i32 @llvm.fptoui.sat.i32.f64(double)
 
 
 define dso_local [4 x i16] @test_memcpy() #0 {
@@ -35,6 +57,39 @@ define dso_local [4 x i16] @test_memcpy() #0 {
     ;   -> 0x6543fe671234abcd
 }
 
+define dso_local i32 @test_memcpy_symbolic_size(i32 %len) #0 {
+    %1 = alloca i32, align 4
+    %2 = alloca i32, align 4
+    store i32 u0x1234cdab, i32* %1
+    store i32 0, i32* %2
+
+    %c = icmp ule i32 %len, 2
+    call void @llvm.assume(i1 %c)
+
+    %src = bitcast i32* %1 to i8*
+    %dst = bitcast i32* %2 to i8*
+    call void @llvm.memcpy.p0i8.p0i8.i32(i8* %dst, i8* %src, i32 %len, i1 0)
+
+    %ret = load i32, i32* %2
+    ret i32 %ret
+    ; expect 0x0, 0xab, or 0xcdab depending on the length
+}
+
+define dso_local [4 x i16] @test_memcpy_inline() #0 {
+    %1 = alloca [4 x i16], align 4
+    %2 = alloca [4 x i16], align 4
+    store [4 x i16] [i16 u0xabcd, i16 u0x1234, i16 u0x5667, i16 u0xbebe], [4 x i16]* %1
+    store [4 x i16] [i16 6, i16 7, i16 u0xfecb, i16 u0x6543], [4 x i16]* %2
+
+    %src = bitcast [4 x i16]* %1 to i8*
+    %dst = bitcast [4 x i16]* %2 to i8*
+    call void @llvm.memcpy.inline.p0i8.p0i8.i32(i8* %dst, i8* %src, i32 5, i1 0)
+
+    %ret = load [4 x i16], [4 x i16]* %2
+    ret [4 x i16] %ret
+    ; expect 0x6543fe671234abcd, same as memcpy
+}
+
 define dso_local [8 x i8] @test_memset() #0 {
     %1 = alloca [8 x i8], align 4
     %2 = bitcast [8 x i8]* %1 to i8*
@@ -51,6 +106,11 @@ define dso_local i32 @test_umax() #0 {
     ret i32 %3 ; expect 0xbcef
 }
 
+define dso_local i32 @test_umax_symbolic(i32 %0) #0 {
+    %2 = call i32 @llvm.umax.i32(i32 %0, i32 10)
+    ret i32 %2
+}
+
 define dso_local <2 x i32> @test_umax_vec() #0 {
     %1 = add <2 x i32> <i32 0, i32 0>, <i32 u0xabcd, i32 u0x4321>
     %2 = add <2 x i32> <i32 0, i32 0>, <i32 u0xbcef, i32 u0x1234>
@@ -60,6 +120,189 @@ define dso_local <2 x i32> @test_umax_vec() #0 {
     ;   -> 0x000043210000bcef
 }
 
+define dso_local <4 x i16> @test_umax_vec4() #0 {
+    %1 = add <4 x i16> zeroinitializer, <i16 1, i16 u0xffff, i16 5, i16 u0x8000>
+    %2 = add <4 x i16> zeroinitializer, <i16 2, i16 3, i16 5, i16 u0x7fff>
+    %3 = call <4 x i16> @llvm.umax.v4i16(<4 x i16> %1, <4 x i16> %2)
+    ret <4 x i16> %3
+    ; expect <0x0002, 0xffff, 0x0005, 0x8000>
+    ;   -> 0x80000005ffff0002
+}
+
+define dso_local i32 @test_smax() #0 {
+    %1 = add i32 0, -5
+    %2 = add i32 0, 3
+    %3 = call i32 @llvm.smax.i32(i32 %1, i32 %2)
+    ret i32 %3 ; expect 3
+}
+
+define dso_local <4 x i16> @test_smax_vec4() #0 {
+    %1 = add <4 x i16> zeroinitializer, <i16 1, i16 -1, i16 5, i16 u0x8000>
+    %2 = add <4 x i16> zeroinitializer, <i16 2, i16 3, i16 5, i16 u0x7fff>
+    %3 = call <4 x i16> @llvm.smax.v4i16(<4 x i16> %1, <4 x i16> %2)
+    ret <4 x i16> %3
+    ; expect <0x0002, 0x0003, 0x0005, 0x7fff>
+    ;   -> 0x7fff000500030002
+}
+
+define dso_local i64 @test_powi() #0 {
+    %1 = call double @llvm.powi.f64.i32(double 2.0, i32 10)
+    %2 = bitcast double %1 to i64
+    ret i64 %2 ; expect 1024.0 (0x4090000000000000)
+}
+
+define dso_local i32 @test_powi_f32() #0 {
+    %1 = call float @llvm.powi.f32.i32(float 2.0, i32 -2)
+    %2 = bitcast float %1 to i32
+    ret i32 %2 ; expect 0.25 (0x3e800000)
+}
+
+define dso_local i32 @test_fptosi_sat() #0 {
+    %1 = call i32 @llvm.fptosi.sat.i32.f32(float 0x4200000000000000)
+    ret i32 %1 ; expect 0x7fffffff
+}
+
+define dso_local i32 @test_fptosi_sat_negative() #0 {
+    %1 = call i32 @llvm.fptosi.sat.i32.f32(float 0xC200000000000000)
+    ret i32 %1 ; expect 0x80000000
+}
+
+define dso_local i32 @test_fptoui_sat_nan() #0 {
+    %1 = call i32 @llvm.fptoui.sat.i32.f64(double 0x7FF8000000000000)
+    ret i32 %1 ; expect 0
+}
+
+define dso_local i64 @test_lround() #0 {
+    %1 = call i64 @llvm.lround.i64.f64(double 2.5)
+    ret i64 %1 ; expect 3
+}
+
+define dso_local i64 @test_llround_negative() #0 {
+    %1 = call i64 @llvm.llround.i64.f64(double -2.5)
+    ret i64 %1 ; expect -3
+}
+
+define dso_local i64 @test_lrint() #0 {
+    %1 = call i64 @llvm.lrint.i64.f64(double 2.5)
+    ret i64 %1 ; expect 2
+}
+
+define dso_local i64 @test_llrint_f32() #0 {
+    %1 = call i64 @llvm.llrint.i64.f32(float 3.5)
+    ret i64 %1 ; expect 4
+}
+
+
+; --------------------------------------------------------------------------------------------------
+; Constrained floating point intrinsics
+; --------------------------------------------------------------------------------------------------
+
+declare double @llvm.experimental.constrained.fadd.f64(double, double, metadata, metadata)
+declare double @llvm.experimental.constrained.fsub.f64(double, double, metadata, metadata)
+declare float @llvm.experimental.constrained.fmul.f32(float, float, metadata, metadata)
+declare double @llvm.experimental.constrained.fdiv.f64(double, double, metadata, metadata)
+declare double @llvm.experimental.constrained.frem.f64(double, double, metadata, metadata)
+declare double @llvm.experimental.constrained.sqrt.f64(double, metadata, metadata)
+
+define dso_local i64 @test_constrained_fadd() #2 {
+    %1 = call double @llvm.experimental.constrained.fadd.f64(double 1.5, double 2.25, metadata !"round.dynamic", metadata !"fpexcept.strict") #3
+    %2 = bitcast double %1 to i64
+    ret i64 %2 ; expect 3.75 (0x400e000000000000)
+}
+
+define dso_local i64 @test_constrained_fsub() #2 {
+    %1 = call double @llvm.experimental.constrained.fsub.f64(double 1.0, double 4.0, metadata !"round.dynamic", metadata !"fpexcept.strict") #3
+    %2 = bitcast double %1 to i64
+    ret i64 %2 ; expect -3.0 (0xc008000000000000)
+}
+
+define dso_local i32 @test_constrained_fmul_f32() #2 {
+    %1 = call float @llvm.experimental.constrained.fmul.f32(float 1.5, float -4.0, metadata !"round.tonearest", metadata !"fpexcept.ignore") #3
+    %2 = bitcast float %1 to i32
+    ret i32 %2 ; expect -6.0 (0xc0c00000)
+}
+
+define dso_local i64 @test_constrained_fdiv() #2 {
+    %1 = call double @llvm.experimental.constrained.fdiv.f64(double 1.0, double 8.0, metadata !"round.dynamic", metadata !"fpexcept.strict") #3
+    %2 = bitcast double %1 to i64
+    ret i64 %2 ; expect 0.125 (0x3fc0000000000000)
+}
+
+define dso_local i64 @test_constrained_frem() #2 {
+    %1 = call double @llvm.experimental.constrained.frem.f64(double 7.5, double 2.0, metadata !"round.dynamic", metadata !"fpexcept.strict") #3
+    %2 = bitcast double %1 to i64
+    ret i64 %2 ; expect 1.5 (0x3ff8000000000000)
+}
+
+define dso_local i64 @test_constrained_unsupported() #2 {
+    %1 = call double @llvm.experimental.constrained.sqrt.f64(double 4.0, metadata !"round.dynamic", metadata !"fpexcept.strict") #3
+    %2 = bitcast double %1 to i64
+    ret i64 %2 ; expect UnsupportedIntrinsic
+}
+
+
+; --------------------------------------------------------------------------------------------------
+; Unregistered intrinsics
+; --------------------------------------------------------------------------------------------------
+
+declare double @llvm.cos.f64(double)
+
+define dso_local i64 @test_unregistered() #0 {
+    %1 = call double @llvm.cos.f64(double 0.0)
+    %2 = bitcast double %1 to i64
+    ret i64 %2 ; expect UnsupportedIntrinsic
+}
+
+declare <4 x i32> @llvm.experimental.vector.reverse.v4i32(<4 x i32>)
+
+define dso_local i32 @test_experimental_unsupported() #0 {
+    %1 = call <4 x i32> @llvm.experimental.vector.reverse.v4i32(<4 x i32> <i32 1, i32 2, i32 3, i32 4>)
+    %2 = extractelement <4 x i32> %1, i32 0
+    ret i32 %2 ; expect UnsupportedIntrinsic
+}
+
+
+; --------------------------------------------------------------------------------------------------
+; Bit manipulation intrinsics
+; --------------------------------------------------------------------------------------------------
+
+declare i32 @llvm.ctlz.i32(i32, i1)
+declare <2 x i16> @llvm.ctlz.v2i16(<2 x i16>, i1)
+declare i32 @llvm.cttz.i32(i32, i1)
+
+define dso_local i32 @test_ctlz() #0 {
+    %1 = call i32 @llvm.ctlz.i32(i32 u0x00f00000, i1 false)
+    ret i32 %1 ; expect 8
+}
+
+define dso_local i32 @test_ctlz_zero() #0 {
+    %1 = call i32 @llvm.ctlz.i32(i32 0, i1 false)
+    ret i32 %1 ; expect 32
+}
+
+define dso_local <2 x i16> @test_ctlz_vec() #0 {
+    %1 = call <2 x i16> @llvm.ctlz.v2i16(<2 x i16> <i16 1, i16 u0x100>, i1 false)
+    ret <2 x i16> %1
+    ; expect <15, 7>
+    ;   -> 0x0007000f
+}
+
+define dso_local i32 @test_ctlz_zero_poison(i32 %0) #0 {
+    %2 = call i32 @llvm.ctlz.i32(i32 %0, i1 true)
+    %3 = icmp ult i32 %2, 32
+    %4 = zext i1 %3 to i32
+    ret i32 %4 ; expect 1 for non-zero inputs, and poison for zero
+}
+
+define dso_local i32 @test_cttz() #0 {
+    %1 = call i32 @llvm.cttz.i32(i32 u0x00f00000, i1 false)
+    ret i32 %1 ; expect 20
+}
+
+define dso_local i32 @test_cttz_zero() #0 {
+    %1 = call i32 @llvm.cttz.i32(i32 0, i1 false)
+    ret i32 %1 ; expect 32
+}
 
 ; --------------------------------------------------------------------------------------------------
 ; Arithmetic with overflow intrinsics
@@ -196,8 +439,122 @@ define dso_local i32 @test_assume(i32 %0) #0 {
     ret i32 %0 ; expect 5
 }
 
+@.annotation = private unnamed_addr constant [4 x i8] c"foo\00", section "llvm.metadata"
+@.annotation_file = private unnamed_addr constant [7 x i8] c"test.c\00", section "llvm.metadata"
+
+declare void @llvm.var.annotation(i8*, i8*, i8*, i32, i8*)
+declare i8* @llvm.ptr.annotation.p0i8(i8*, i8*, i8*, i32, i8*)
+
+define dso_local i32 @test_var_annotation() #0 {
+    %1 = alloca i32, align 4
+    %2 = bitcast i32* %1 to i8*
+    call void @llvm.var.annotation(
+        i8* %2,
+        i8* getelementptr inbounds ([4 x i8], [4 x i8]* @.annotation, i32 0, i32 0),
+        i8* getelementptr inbounds ([7 x i8], [7 x i8]* @.annotation_file, i32 0, i32 0),
+        i32 10,
+        i8* null
+    )
+    store i32 5, i32* %1
+    %3 = load i32, i32* %1
+    ret i32 %3 ; expect 5
+}
+
+define dso_local i32 @test_ptr_annotation() #0 {
+    %1 = alloca i32, align 4
+    store i32 7, i32* %1
+    %2 = bitcast i32* %1 to i8*
+    %3 = call i8* @llvm.ptr.annotation.p0i8(
+        i8* %2,
+        i8* getelementptr inbounds ([4 x i8], [4 x i8]* @.annotation, i32 0, i32 0),
+        i8* getelementptr inbounds ([7 x i8], [7 x i8]* @.annotation_file, i32 0, i32 0),
+        i32 20,
+        i8* null
+    )
+    %4 = bitcast i8* %3 to i32*
+    %5 = load i32, i32* %4
+    ret i32 %5 ; expect 7
+}
+
+; objectsize
+
+declare i64 @llvm.objectsize.i64.p0i8(i8*, i1, i1, i1)
+
+define dso_local i64 @test_objectsize() #0 {
+    %buf = alloca [16 x i8], align 1
+    %1 = bitcast [16 x i8]* %buf to i8*
+    %2 = call i64 @llvm.objectsize.i64.p0i8(i8* %1, i1 false, i1 false, i1 false)
+    ret i64 %2 ; expect 16
+}
+
+define dso_local i64 @test_objectsize_offset() #0 {
+    %buf = alloca [16 x i8], align 1
+    %1 = getelementptr inbounds [16 x i8], [16 x i8]* %buf, i64 0, i64 4
+    %2 = call i64 @llvm.objectsize.i64.p0i8(i8* %1, i1 true, i1 false, i1 false)
+    ret i64 %2 ; expect 12
+}
+
+define dso_local i64 @test_objectsize_unknown() #0 {
+    %1 = inttoptr i64 16 to i8*
+    %2 = call i64 @llvm.objectsize.i64.p0i8(i8* %1, i1 false, i1 false, i1 false)
+    ret i64 %2 ; expect -1
+}
+
+; prefetch
+
+declare void @llvm.prefetch.p0i8(i8* nocapture readonly, i32 immarg, i32 immarg, i32 immarg)
+
+define dso_local i32 @test_prefetch() #0 {
+    %buf = alloca [16 x i8], align 1
+    %1 = bitcast [16 x i8]* %buf to i8*
+    call void @llvm.prefetch.p0i8(i8* %1, i32 0, i32 3, i32 1)
+    ret i32 0 ; expect 0
+}
+
+; readcyclecounter
+
+declare i64 @llvm.readcyclecounter()
+
+define dso_local i32 @test_readcyclecounter() #0 {
+    %1 = call i64 @llvm.readcyclecounter()
+    %2 = call i64 @llvm.readcyclecounter()
+    %3 = sub i64 %2, %1
+    %4 = icmp ugt i64 %3, 1000
+    br i1 %4, label %slow, label %fast
+
+slow:
+    ret i32 1 ; expect 1
+
+fast:
+    ret i32 0 ; expect 0
+}
+
+; localescape and localrecover
+
+declare void @llvm.localescape(...)
+declare i8* @llvm.localrecover(i8*, i8*, i32)
+
+define dso_local i32 @test_localescape() #0 {
+    %x = alloca i32, align 4
+    call void (...) @llvm.localescape(i32* %x)
+    store i32 1, i32* %x, align 4
+    %1 = load i32, i32* %x, align 4
+    ret i32 %1 ; expect 1
+}
+
+define dso_local i32 @test_localrecover() #0 {
+    %x = alloca i32, align 4
+    call void (...) @llvm.localescape(i32* %x)
+    store i32 1, i32* %x, align 4
+    %1 = call i8* @llvm.localrecover(i8* bitcast (i32 ()* @test_localrecover to i8*), i8* null, i32 0)
+    %2 = bitcast i8* %1 to i32*
+    %3 = load i32, i32* %2, align 4
+    ret i32 %3 ; expect UnsupportedIntrinsic
+}
 
 declare void @assume(i32) #1
 
 attributes #0 = { noinline nounwind optnone sspstrong uwtable "frame-pointer"="all" "min-legal-vector-width"="0" "no-trapping-math"="true" "stack-protector-buffer-size"="8" "target-cpu"="x86-64" "target-features"="+cx8,+fxsr,+mmx,+sse,+sse2,+x87" "tune-cpu"="generic" }
 attributes #1 = { "frame-pointer"="all" "no-trapping-math"="true" "stack-protector-buffer-size"="8" "target-cpu"="x86-64" "target-features"="+cx8,+fxsr,+mmx,+sse,+sse2,+x87" "tune-cpu"="generic" }
+attributes #2 = { noinline nounwind optnone strictfp }
+attributes #3 = { strictfp }